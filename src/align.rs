@@ -0,0 +1,114 @@
+//! module align: sequence alignment of residues and atom matching based on it.
+use nalgebra::Vector3;
+use std::string::String;
+use std::vec::Vec;
+use super::Particle;
+use super::pdb::{AtomData, ResidueData, ResidueId, ResidueIterator};
+use super::superpose::{Superposition, superpose};
+
+/// Scores of the Needleman-Wunsch algorithm. Gaps are penalized linearly.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Scoring {
+    pub matched    : i32,
+    pub mismatched : i32,
+    pub gap        : i32,
+}
+
+impl Default for Scoring {
+    /// A substitution is preferred to a pair of gaps, so that a mutant is
+    /// aligned to the wild type residue by residue.
+    fn default() -> Scoring {
+        Scoring{matched: 2, mismatched: -1, gap: -2}
+    }
+}
+
+/// Globally aligns two residue sequences by residue names. Each element of
+/// the result is a pair of indices; `None` means a gap.
+pub fn align<R: ResidueData, S: ResidueData>(lhs: &[R], rhs: &[S], scoring: &Scoring)
+    -> Vec<(Option<usize>, Option<usize>)> {
+    let (n, m) = (lhs.len(), rhs.len());
+    let mut table = vec![vec![0i32; m + 1]; n + 1];
+    for i in 1..n+1 { table[i][0] = scoring.gap * i as i32; }
+    for j in 1..m+1 { table[0][j] = scoring.gap * j as i32; }
+    for i in 1..n+1 {
+        for j in 1..m+1 {
+            let s = if lhs[i-1].residue_name() == rhs[j-1].residue_name() {
+                scoring.matched
+            } else {
+                scoring.mismatched
+            };
+            table[i][j] = *[table[i-1][j-1] + s,
+                            table[i-1][j]   + scoring.gap,
+                            table[i][j-1]   + scoring.gap].iter().max().unwrap();
+        }
+    }
+
+    let mut path = Vec::new();
+    let (mut i, mut j) = (n, m);
+    while i > 0 || j > 0 {
+        if i > 0 && j > 0 {
+            let s = if lhs[i-1].residue_name() == rhs[j-1].residue_name() {
+                scoring.matched
+            } else {
+                scoring.mismatched
+            };
+            if table[i][j] == table[i-1][j-1] + s {
+                path.push((Some(i-1), Some(j-1)));
+                i -= 1; j -= 1;
+                continue;
+            }
+        }
+        if i > 0 && table[i][j] == table[i-1][j] + scoring.gap {
+            path.push((Some(i-1), None));
+            i -= 1;
+        } else {
+            path.push((None, Some(j-1)));
+            j -= 1;
+        }
+    }
+    path.reverse();
+    path
+}
+
+/// Residues and the ranges of their atom indices.
+fn residue_ranges<T: AtomData>(atoms: &[T]) -> (Vec<ResidueId>, Vec<(usize, usize)>) {
+    let mut ids    = Vec::new();
+    let mut ranges = Vec::new();
+    let mut offset = 0;
+    for res in ResidueIterator::from_slice(atoms) {
+        ids.push(res.residue_id());
+        ranges.push((offset, offset + res.atoms.len()));
+        offset += res.atoms.len();
+    }
+    (ids, ranges)
+}
+
+/// Pairs atoms of two chains. Residues are aligned by sequence, and then
+/// atoms that have the same name in aligned residues are paired.
+/// Returns pairs of indices into `lhs` and `rhs`.
+pub fn match_atoms<T: AtomData, U: AtomData>(lhs: &[T], rhs: &[U], scoring: &Scoring)
+    -> Vec<(usize, usize)> {
+    let (lres, lranges) = residue_ranges(lhs);
+    let (rres, rranges) = residue_ranges(rhs);
+    let mut pairs = Vec::new();
+    for (i, j) in align(&lres, &rres, scoring) {
+        let (i, j) = match (i, j) {(Some(i), Some(j)) => (i, j), _ => continue};
+        for l in lranges[i].0..lranges[i].1 {
+            let name = lhs[l].atom_name();
+            if let Some(r) = (rranges[j].0..rranges[j].1).find(|r| rhs[*r].atom_name() == name) {
+                pairs.push((l, r));
+            }
+        }
+    }
+    pairs
+}
+
+/// Superposes `mobile` onto `target` using the atoms paired by `match_atoms`.
+pub fn superpose_matched<T, U>(mobile: &[T], target: &[U], scoring: &Scoring)
+    -> Result<Superposition, String>
+    where T: AtomData + Particle, U: AtomData + Particle {
+    let pairs = match_atoms(mobile, target, scoring);
+    let lhs: Vec<Vector3<f64>> = pairs.iter().map(|&(i, _)| mobile[i].vec()).collect();
+    let rhs: Vec<Vector3<f64>> = pairs.iter().map(|&(_, j)| target[j].vec()).collect();
+    superpose(&lhs, &rhs)
+}
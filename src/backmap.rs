@@ -0,0 +1,454 @@
+//! module backmap: all-atom models rebuilt from CA traces.
+//!
+//! The backbone is taken from a library of ideal fragments of four residues
+//! with the same phi and psi on a grid of 10 degrees. For each four CA atoms
+//! of a chain, the fragment with the closest CA distances is superposed on
+//! them and gives the C, O and N atoms of the peptide in the middle, as in
+//! BBQ (Gront et al., 2007). Side chains are built with ideal geometry (Engh
+//! and Huber) in the rotamer of Lovell et al. (2000) with the fewest clashes
+//! with the backbone and the side chains placed before, the most common one
+//! in a tie. Hydrogens are not built.
+use std::cmp::Ordering;
+use std::string::String;
+use std::vec::Vec;
+use nalgebra::Vector3;
+use super::Particle;
+use super::dihedral::dihedral;
+use super::go::CHAIN_BREAK;
+use super::neighbor::CellList;
+use super::pdb::{Atom, AtomBuilder, AtomData, Model, Structure, Ter};
+use super::pdb::{ResidueId, ResidueIterator};
+use super::superpose::{superpose, Superposition};
+
+/// Side-chain atoms of different residues closer than this clash.
+pub const CLASH_DISTANCE: f64 = 2.8;
+
+/// Side-chain atoms are within this of their CA atom.
+const SIDE_CHAIN_REACH: f64 = 8.0;
+
+const N_CA    : f64 = 1.458;
+const CA_C    : f64 = 1.525;
+const C_N     : f64 = 1.329;
+const C_O     : f64 = 1.231;
+const N_CA_C  : f64 = 111.2;
+const CA_C_N  : f64 = 116.2;
+const C_N_CA  : f64 = 121.7;
+const CA_C_O  : f64 = 120.5;
+
+/// The position `d` such that |cd| = bond, the angle b-c-d = angle and the
+/// dihedral a-b-c-d = torsion, in degrees.
+pub fn place(a: &Vector3<f64>, b: &Vector3<f64>, c: &Vector3<f64>,
+             bond: f64, angle: f64, torsion: f64) -> Vector3<f64> {
+    let bc = (c - b).normalize();
+    let n = (b - a).cross(&bc).normalize();
+    let m = n.cross(&bc);
+    let (angle, torsion) = (angle.to_radians(), torsion.to_radians());
+    c - bc * (bond * angle.cos()) + m * (bond * angle.sin() * torsion.cos())
+      + n * (bond * angle.sin() * torsion.sin())
+}
+
+/// Backbone atoms of a residue. OXT is only at the end of a chain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Backbone {
+    pub n   : Vector3<f64>,
+    pub ca  : Vector3<f64>,
+    pub c   : Vector3<f64>,
+    pub o   : Vector3<f64>,
+    pub oxt : Option<Vector3<f64>>,
+}
+
+struct Fragment {
+    key      : [f64; 3],
+    alpha    : [Vector3<f64>; 4],
+    /// C, O and N of the peptides between the CA atoms.
+    peptides : [[Vector3<f64>; 3]; 3],
+    /// N of the first residue, and C, O and OXT of the last one.
+    first    : Vector3<f64>,
+    last     : [Vector3<f64>; 3],
+}
+
+/// CA-CA distances of i-(i+2), (i+1)-(i+3) and i-(i+3), the last one with
+/// the sign of the CA dihedral.
+fn key(alpha: &[Vector3<f64>]) -> [f64; 3] {
+    let d = |i: usize, j: usize| (alpha[j] - alpha[i]).norm();
+    let sign = if dihedral(&alpha[0], &alpha[1], &alpha[2], &alpha[3]) < 0.0 {-1.0} else {1.0};
+    [d(0, 2), d(1, 3), sign * d(0, 3)]
+}
+
+fn fragment(phi: f64, psi: f64) -> Fragment {
+    let mut n  = vec![Vector3::new(0.0, 0.0, 0.0)];
+    let mut ca = vec![Vector3::new(N_CA, 0.0, 0.0)];
+    let angle = N_CA_C.to_radians();
+    let mut c  = vec![ca[0] + Vector3::new(-angle.cos(), angle.sin(), 0.0) * CA_C];
+    for k in 0..3 {
+        n.push(place(&n[k], &ca[k], &c[k], C_N, CA_C_N, psi));
+        ca.push(place(&ca[k], &c[k], &n[k + 1], N_CA, C_N_CA, 180.0));
+        c.push(place(&c[k], &n[k + 1], &ca[k + 1], CA_C, N_CA_C, phi));
+    }
+    let o = |k: usize| place(&n[k + 1], &ca[k], &c[k], C_O, CA_C_O, 180.0);
+    let alpha = [ca[0], ca[1], ca[2], ca[3]];
+    Fragment{
+        key: key(&alpha), alpha: alpha,
+        peptides: [[c[0], o(0), n[1]], [c[1], o(1), n[2]], [c[2], o(2), n[3]]],
+        first: n[0],
+        last: [c[3], place(&n[3], &ca[3], &c[3], C_O, CA_C_O, psi + 180.0),
+               place(&n[3], &ca[3], &c[3], C_O, CA_C_O, psi)],
+    }
+}
+
+fn library() -> Vec<Fragment> {
+    let mut fragments = Vec::with_capacity(36 * 36);
+    for i in 0..36 {
+        for j in 0..36 {
+            fragments.push(fragment(-180.0 + 10.0 * i as f64, -180.0 + 10.0 * j as f64));
+        }
+    }
+    fragments
+}
+
+fn backbone_with(library: &[Fragment], alpha: &[Vector3<f64>])
+    -> Result<Vec<Backbone>, String> {
+    let n = alpha.len();
+    if n == 0 {
+        return Err("no CA atoms to rebuild the backbone".to_owned());
+    }
+    // a quadruplet of CA atoms from q, or the first ones of an extended
+    // fragment for short chains
+    let extended = fragment(-120.0, 130.0);
+    if n == 1 {
+        // a lone residue has no direction; the fragment is moved onto it
+        let t = alpha[0] - extended.alpha[0];
+        let peptide = &extended.peptides[0];
+        return Ok(vec![Backbone{n: extended.first + t, ca: alpha[0], c: peptide[0] + t,
+                                o: peptide[1] + t, oxt: Some(peptide[2] + t)}]);
+    }
+    let fit = |q: usize| -> Result<(&Fragment, Superposition), String> {
+        if n < 4 {
+            let sp = try!(superpose(&extended.alpha[..n], alpha));
+            return Ok((&extended, sp));
+        }
+        let target = key(&alpha[q..q + 4]);
+        let distance = |f: &Fragment| {
+            (0..3).map(|i| (f.key[i] - target[i]).powi(2)).sum::<f64>()
+        };
+        let best = library.iter().min_by(|a, b| {
+            distance(a).partial_cmp(&distance(b)).unwrap_or(Ordering::Equal)
+        }).expect("the library is not empty");
+        let sp = try!(superpose(&best.alpha, &alpha[q..q + 4]));
+        Ok((best, sp))
+    };
+
+    let mut backbone: Vec<Backbone> = alpha.iter().map(|ca| {
+        Backbone{n: *ca, ca: *ca, c: *ca, o: *ca, oxt: None}
+    }).collect();
+    let (f, sp) = try!(fit(0));
+    backbone[0].n = sp.apply(&f.first);
+    for p in 0..n - 1 {
+        let q = if n < 4 {0} else {p.saturating_sub(1).min(n - 4)};
+        let (f, sp) = try!(fit(q));
+        let peptide = &f.peptides[p - q];
+        backbone[p].c = sp.apply(&peptide[0]);
+        backbone[p].o = sp.apply(&peptide[1]);
+        backbone[p + 1].n = sp.apply(&peptide[2]);
+    }
+    let (f, sp) = try!(fit(if n < 4 {0} else {n - 4}));
+    let last = &mut backbone[n - 1];
+    if n < 4 {
+        // the CA atoms are the first ones of the fragment
+        let peptide = &f.peptides[n - 1];
+        last.c = sp.apply(&peptide[0]);
+        last.o = sp.apply(&peptide[1]);
+        last.oxt = Some(sp.apply(&peptide[2]));
+    } else {
+        last.c = sp.apply(&f.last[0]);
+        last.o = sp.apply(&f.last[1]);
+        last.oxt = Some(sp.apply(&f.last[2]));
+    }
+    Ok(backbone)
+}
+
+/// Rebuilds the backbone of a chain from its CA atoms, which should be
+/// about 3.8 angstroms apart. A lone CA atom gets an extended residue in an
+/// arbitrary orientation.
+pub fn backbone(alpha: &[Vector3<f64>]) -> Result<Vec<Backbone>, String> {
+    backbone_with(&library(), alpha)
+}
+
+/// An atom placed from three atoms by its bond length, bond angle and
+/// dihedral. The dihedral is the offset from a chi angle, counted from 1,
+/// or absolute for 0.
+pub type Internal = (&'static str, [&'static str; 3], f64, f64, f64, usize);
+
+const CB: Internal = ("CB", ["N", "C", "CA"], 1.53, 109.5, 122.69, 0);
+
+/// Residue names of force fields as the standard ones.
+pub fn standard_residue(residue: &str) -> &str {
+    match residue {
+        "HID" | "HIE" | "HIP" | "HSD" | "HSE" | "HSP" => "HIS",
+        "CYX" => "CYS",
+        "ASH" => "ASP",
+        "GLH" => "GLU",
+        "LYN" => "LYS",
+        _ => residue,
+    }
+}
+
+/// Side-chain atoms of an amino acid, in the order of PDB files; None if
+/// the residue is not known.
+pub fn side_chain_atoms(residue: &str) -> Option<&'static [Internal]> {
+    const PHE: &'static [Internal] = &[
+        CB, ("CG", ["N", "CA", "CB"], 1.50, 113.85, 0.0, 1),
+        ("CD1", ["CA", "CB", "CG"], 1.39, 120.0, 0.0, 2),
+        ("CD2", ["CA", "CB", "CG"], 1.39, 120.0, 180.0, 2),
+        ("CE1", ["CB", "CG", "CD1"], 1.39, 120.0, 180.0, 0),
+        ("CE2", ["CB", "CG", "CD2"], 1.39, 120.0, 180.0, 0),
+        ("CZ", ["CG", "CD1", "CE1"], 1.39, 120.0, 0.0, 0),
+    ];
+    Some(match standard_residue(residue) {
+        "GLY" => &[],
+        "ALA" => &[CB],
+        "SER" => &[CB, ("OG", ["N", "CA", "CB"], 1.417, 110.8, 0.0, 1)],
+        "CYS" => &[CB, ("SG", ["N", "CA", "CB"], 1.808, 113.8, 0.0, 1)],
+        "THR" => &[CB, ("OG1", ["N", "CA", "CB"], 1.43, 109.2, 0.0, 1),
+                   ("CG2", ["N", "CA", "CB"], 1.53, 111.1, -120.0, 1)],
+        "VAL" => &[CB, ("CG1", ["N", "CA", "CB"], 1.527, 110.7, 0.0, 1),
+                   ("CG2", ["N", "CA", "CB"], 1.527, 110.4, 120.0, 1)],
+        "ILE" => &[CB, ("CG1", ["N", "CA", "CB"], 1.527, 110.7, 0.0, 1),
+                   ("CG2", ["N", "CA", "CB"], 1.527, 110.4, -120.0, 1),
+                   ("CD1", ["CA", "CB", "CG1"], 1.52, 113.97, 0.0, 2)],
+        "LEU" => &[CB, ("CG", ["N", "CA", "CB"], 1.53, 116.1, 0.0, 1),
+                   ("CD1", ["CA", "CB", "CG"], 1.524, 110.3, 0.0, 2),
+                   ("CD2", ["CA", "CB", "CG"], 1.525, 110.6, -120.0, 2)],
+        "MET" => &[CB, ("CG", ["N", "CA", "CB"], 1.52, 113.68, 0.0, 1),
+                   ("SD", ["CA", "CB", "CG"], 1.81, 112.69, 0.0, 2),
+                   ("CE", ["CB", "CG", "SD"], 1.79, 100.61, 0.0, 3)],
+        "ASP" => &[CB, ("CG", ["N", "CA", "CB"], 1.52, 113.06, 0.0, 1),
+                   ("OD1", ["CA", "CB", "CG"], 1.25, 119.22, 0.0, 2),
+                   ("OD2", ["CA", "CB", "CG"], 1.25, 118.22, 180.0, 2)],
+        "ASN" => &[CB, ("CG", ["N", "CA", "CB"], 1.52, 112.62, 0.0, 1),
+                   ("OD1", ["CA", "CB", "CG"], 1.23, 120.85, 0.0, 2),
+                   ("ND2", ["CA", "CB", "CG"], 1.33, 116.48, 180.0, 2)],
+        "GLU" => &[CB, ("CG", ["N", "CA", "CB"], 1.52, 113.82, 0.0, 1),
+                   ("CD", ["CA", "CB", "CG"], 1.52, 113.31, 0.0, 2),
+                   ("OE1", ["CB", "CG", "CD"], 1.25, 119.02, 0.0, 3),
+                   ("OE2", ["CB", "CG", "CD"], 1.25, 118.08, 180.0, 3)],
+        "GLN" => &[CB, ("CG", ["N", "CA", "CB"], 1.52, 113.75, 0.0, 1),
+                   ("CD", ["CA", "CB", "CG"], 1.52, 112.78, 0.0, 2),
+                   ("OE1", ["CB", "CG", "CD"], 1.24, 120.86, 0.0, 3),
+                   ("NE2", ["CB", "CG", "CD"], 1.33, 116.50, 180.0, 3)],
+        "LYS" => &[CB, ("CG", ["N", "CA", "CB"], 1.52, 113.83, 0.0, 1),
+                   ("CD", ["CA", "CB", "CG"], 1.52, 111.79, 0.0, 2),
+                   ("CE", ["CB", "CG", "CD"], 1.52, 111.68, 0.0, 3),
+                   ("NZ", ["CG", "CD", "CE"], 1.49, 111.85, 0.0, 4)],
+        "ARG" => &[CB, ("CG", ["N", "CA", "CB"], 1.52, 113.83, 0.0, 1),
+                   ("CD", ["CA", "CB", "CG"], 1.52, 111.79, 0.0, 2),
+                   ("NE", ["CB", "CG", "CD"], 1.46, 111.68, 0.0, 3),
+                   ("CZ", ["CG", "CD", "NE"], 1.33, 124.79, 0.0, 4),
+                   ("NH1", ["CD", "NE", "CZ"], 1.33, 120.64, 0.0, 0),
+                   ("NH2", ["CD", "NE", "CZ"], 1.33, 119.63, 180.0, 0)],
+        "PRO" => &[CB, ("CG", ["N", "CA", "CB"], 1.50, 104.21, 0.0, 1),
+                   ("CD", ["CA", "CB", "CG"], 1.51, 105.03, 0.0, 2)],
+        "PHE" => PHE,
+        "TYR" => &[CB, ("CG", ["N", "CA", "CB"], 1.51, 113.8, 0.0, 1),
+                   ("CD1", ["CA", "CB", "CG"], 1.39, 120.98, 0.0, 2),
+                   ("CD2", ["CA", "CB", "CG"], 1.39, 120.82, 180.0, 2),
+                   ("CE1", ["CB", "CG", "CD1"], 1.39, 120.0, 180.0, 0),
+                   ("CE2", ["CB", "CG", "CD2"], 1.39, 120.0, 180.0, 0),
+                   ("CZ", ["CG", "CD1", "CE1"], 1.39, 120.0, 0.0, 0),
+                   ("OH", ["CD1", "CE1", "CZ"], 1.36, 120.0, 180.0, 0)],
+        "TRP" => &[CB, ("CG", ["N", "CA", "CB"], 1.50, 114.10, 0.0, 1),
+                   ("CD1", ["CA", "CB", "CG"], 1.37, 127.07, 0.0, 2),
+                   ("CD2", ["CA", "CB", "CG"], 1.43, 126.66, 180.0, 2),
+                   ("NE1", ["CB", "CG", "CD1"], 1.38, 110.2, 180.0, 0),
+                   ("CE2", ["CB", "CG", "CD2"], 1.40, 107.3, 180.0, 0),
+                   ("CE3", ["CB", "CG", "CD2"], 1.40, 133.83, 0.0, 0),
+                   ("CZ2", ["CG", "CD2", "CE2"], 1.40, 122.3, 180.0, 0),
+                   ("CZ3", ["CG", "CD2", "CE3"], 1.39, 118.8, 180.0, 0),
+                   ("CH2", ["CD2", "CE2", "CZ2"], 1.37, 117.5, 0.0, 0)],
+        "HIS" => &[CB, ("CG", ["N", "CA", "CB"], 1.49, 113.74, 0.0, 1),
+                   ("ND1", ["CA", "CB", "CG"], 1.38, 122.85, 0.0, 2),
+                   ("CD2", ["CA", "CB", "CG"], 1.36, 130.61, 180.0, 2),
+                   ("CE1", ["CB", "CG", "ND1"], 1.32, 108.5, 180.0, 0),
+                   ("NE2", ["CB", "CG", "CD2"], 1.35, 107.0, 180.0, 0)],
+        _ => return None,
+    })
+}
+
+/// Chi angles of the rotamers of an amino acid, the most common first.
+pub fn rotamers(residue: &str) -> &'static [&'static [f64]] {
+    match standard_residue(residue) {
+        "SER" => &[&[62.0], &[-65.0], &[180.0]],
+        "CYS" => &[&[-65.0], &[-177.0], &[62.0]],
+        "THR" => &[&[62.0], &[-65.0], &[-175.0]],
+        "VAL" => &[&[175.0], &[-60.0], &[63.0]],
+        "ILE" => &[&[-65.0, 170.0], &[-57.0, -60.0], &[62.0, 170.0], &[-177.0, 66.0]],
+        "LEU" => &[&[-65.0, 175.0], &[-177.0, 65.0], &[-172.0, 145.0], &[-85.0, 65.0]],
+        "MET" => &[&[-65.0, 180.0, -70.0], &[-68.0, -65.0, -70.0], &[-177.0, 180.0, 75.0],
+                   &[-65.0, 180.0, 75.0]],
+        "ASP" => &[&[-70.0, -15.0], &[-177.0, 0.0], &[62.0, 10.0]],
+        "ASN" => &[&[-65.0, -20.0], &[-177.0, 30.0], &[62.0, -10.0], &[-65.0, -75.0]],
+        "GLU" => &[&[-65.0, 180.0, -10.0], &[-177.0, 180.0, 0.0], &[-65.0, -65.0, -40.0]],
+        "GLN" => &[&[-65.0, 180.0, -25.0], &[-177.0, 180.0, 20.0], &[-65.0, -65.0, -40.0]],
+        "LYS" => &[&[-62.0, 180.0, 180.0, 180.0], &[-177.0, 180.0, 180.0, 180.0],
+                   &[62.0, 180.0, 180.0, 180.0]],
+        "ARG" => &[&[-67.0, 180.0, 180.0, 180.0], &[-177.0, 180.0, 180.0, 180.0],
+                   &[62.0, 180.0, 180.0, 180.0]],
+        "PRO" => &[&[30.0, -35.0], &[-30.0, 40.0]],
+        "PHE" | "TYR" => &[&[-65.0, -85.0], &[-177.0, 80.0], &[62.0, 90.0]],
+        "TRP" => &[&[-65.0, 95.0], &[-177.0, -105.0], &[-65.0, -5.0], &[62.0, -90.0]],
+        "HIS" => &[&[-65.0, -70.0], &[-177.0, 80.0], &[62.0, -75.0], &[-65.0, 80.0]],
+        _ => &[&[]],
+    }
+}
+
+/// Side-chain atoms of a residue with the chi angles in degrees; None if
+/// the residue is not known or too few angles are given.
+pub fn side_chain(residue: &str, backbone: &Backbone, chi: &[f64])
+    -> Option<Vec<(&'static str, Vector3<f64>)>> {
+    let internals = match side_chain_atoms(residue) {Some(i) => i, None => return None};
+    let mut atoms = vec![("N", backbone.n), ("CA", backbone.ca), ("C", backbone.c)];
+    for &(name, parents, bond, angle, torsion, k) in internals.iter() {
+        let torsion = if k == 0 {torsion} else {
+            match chi.get(k - 1) {Some(x) => x + torsion, None => return None}
+        };
+        let p: Vec<Vector3<f64>> = parents.iter().map(|parent| {
+            atoms.iter().find(|a| a.0 == *parent).expect("parents are placed before").1
+        }).collect();
+        atoms.push((name, place(&p[0], &p[1], &p[2], bond, angle, torsion)));
+    }
+    Some(atoms.split_off(3))
+}
+
+/// How CA traces are rebuilt.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Backmapping {
+    pub side_chains : bool,
+}
+
+impl Backmapping {
+    pub fn new(side_chains: bool) -> Backmapping {
+        Backmapping{side_chains: side_chains}
+    }
+
+    /// Rebuilds a chain from the CA atom of each residue, or its only atom
+    /// such as a bead. The chain is split where CA atoms are farther than
+    /// `CHAIN_BREAK`, and a lone residue between breaks is rebuilt as an
+    /// extended one. Atoms are numbered from `first`.
+    pub fn chain<T: AtomData + Particle>(&self, atoms: &[T], first: i32)
+        -> Result<Vec<Atom>, String> {
+        self.chain_with(&library(), atoms, first)
+    }
+
+    fn chain_with<T: AtomData + Particle>(&self, library: &[Fragment], atoms: &[T], first: i32)
+        -> Result<Vec<Atom>, String> {
+        let mut beads = Vec::new();
+        for residue in ResidueIterator::from_slice(atoms) {
+            let bead = match residue.atoms.iter().find(|a| a.atom_name() == "CA") {
+                Some(ca) => ca,
+                None if residue.atoms.len() == 1 => &residue.atoms[0],
+                None => return Err(format!("{} has no CA atom", residue.residue_id())),
+            };
+            beads.push(bead);
+        }
+
+        let mut backbones = Vec::with_capacity(beads.len());
+        let mut begin = 0;
+        for end in 1..beads.len() + 1 {
+            let bonded = |i: usize| (beads[i].vec() - beads[i - 1].vec()).norm() <= CHAIN_BREAK;
+            if end < beads.len() && bonded(end) {
+                continue;
+            }
+            let alpha: Vec<Vector3<f64>> = beads[begin..end].iter().map(|b| b.vec()).collect();
+            backbones.extend(try!(backbone_with(library, &alpha).map_err(|e| {
+                format!("{}: {}", ResidueId::of(beads[begin]), e)
+            })));
+            begin = end;
+        }
+
+        let mut residues: Vec<Vec<(&'static str, Vector3<f64>)>> = backbones.iter().map(|bb| {
+            vec![("N", bb.n), ("CA", bb.ca), ("C", bb.c), ("O", bb.o)]
+        }).collect();
+        if self.side_chains {
+            // only residues with CA atoms this close have atoms that may clash
+            let cutoff = 2.0 * SIDE_CHAIN_REACH + CLASH_DISTANCE;
+            let alpha: Vec<Vector3<f64>> = backbones.iter().map(|bb| bb.ca).collect();
+            let cells = CellList::new(&alpha, cutoff);
+            for k in 0..residues.len() {
+                let near: Vec<usize> = cells.within(&alpha[k], cutoff).into_iter()
+                    .filter(|&j| j != k).collect();
+                let clashes = |chain: &[(&str, Vector3<f64>)]| {
+                    near.iter().flat_map(|&j| residues[j].iter())
+                        .filter(|a| chain.iter().any(|b| (a.1 - b.1).norm() < CLASH_DISTANCE))
+                        .count()
+                };
+                let name = beads[k].residue_name();
+                let mut best: Option<(usize, Vec<(&'static str, Vector3<f64>)>)> = None;
+                for chi in rotamers(name).iter() {
+                    if let Some(chain) = side_chain(name, &backbones[k], chi) {
+                        let n = clashes(&chain);
+                        if best.as_ref().map_or(true, |b| n < b.0) {
+                            best = Some((n, chain));
+                        }
+                    }
+                }
+                if let Some((_, chain)) = best {
+                    residues[k].extend(chain);
+                }
+            }
+        }
+        for (atoms, bb) in residues.iter_mut().zip(backbones.iter()) {
+            if let Some(oxt) = bb.oxt {
+                atoms.push(("OXT", oxt));
+            }
+        }
+
+        let mut number = first;
+        let mut rebuilt = Vec::new();
+        for (bead, atoms) in beads.iter().zip(residues.iter()) {
+            for &(name, p) in atoms.iter() {
+                rebuilt.push(AtomBuilder::new()
+                    .atom_number(number)
+                    .atom_residue_chain(name, bead.residue_name(), bead.chain_id())
+                    .residue_number(bead.residue_number())
+                    .insertion_code(bead.insertion_code())
+                    .pos(p[0], p[1], p[2])
+                    .occupancy(1.0)
+                    .temperature_factor(bead.temperature_factor())
+                    .element(&name[..1])
+                    .finalize());
+                number += 1;
+            }
+        }
+        Ok(rebuilt)
+    }
+
+    /// Rebuilds ATOM records, chain by chain with TERs. HETATMs are dropped.
+    pub fn model(&self, model: &Model) -> Result<Model, String> {
+        self.model_with(&library(), model)
+    }
+
+    fn model_with(&self, library: &[Fragment], model: &Model) -> Result<Model, String> {
+        let mut rebuilt = Model::new(model.serial);
+        if let Some(chains) = model.chains() {
+            for chain in chains {
+                let first = rebuilt.atoms.len() as i32 + 1;
+                let atoms = try!(self.chain_with(library, chain.atoms, first));
+                if let Some(last) = atoms.last() {
+                    rebuilt.ters.push(Ter::new(last));
+                }
+                rebuilt.atoms.extend(atoms);
+            }
+        }
+        Ok(rebuilt)
+    }
+
+    /// Rebuilds all the models. Bonds are not kept.
+    pub fn structure(&self, structure: &Structure) -> Result<Structure, String> {
+        let mut rebuilt = Structure{header: structure.header.clone(), ..Structure::new()};
+        let library = library();
+        for model in structure.models.iter() {
+            rebuilt.models.push(try!(self.model_with(&library, model)));
+        }
+        Ok(rebuilt)
+    }
+}
@@ -0,0 +1,29 @@
+use mill::pdb::AltlocPolicy;
+use mill::pdb::altloc::resolve_structure;
+use args::{Args, Error};
+use io::{self, Format};
+
+pub const USAGE: &'static str = "mill altloc <input> [highest|first|<altloc ID>] \
+[-o <output>] [--from <format>] [--to <format>] [--select <selection>]";
+
+/// Writes the structure without alternate locations, stdout by default.
+pub fn run(args: &[String]) -> Result<(), Error> {
+    let mut args  = Args::new(args, USAGE);
+    let selection = try!(args.selection());
+    let output    = try!(args.option(&["o", "output"])).unwrap_or("-".to_owned());
+    let from      = try!(args.parsed::<Format>(&["from"]));
+    let to        = try!(args.parsed::<Format>(&["to"]));
+    let args      = try!(args.positional(1, 2));
+    let policy = match args.get(1) {
+        Some(p) => try!(p.parse::<AltlocPolicy>().map_err(Error::Usage)),
+        None    => AltlocPolicy::HighestOccupancy,
+    };
+    if policy == AltlocPolicy::All {
+        return Err(Error::Usage("mill altloc keeps only one conformer".to_owned()));
+    }
+    let mut structure = resolve_structure(&try!(io::read(&args[0], from)), policy);
+    if let Some(selection) = selection {
+        structure = selection.select_structure(&structure);
+    }
+    io::write(&output, to, &structure)
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+use std::string::String;
+use std::vec::Vec;
+use mill::select::Selection;
+
+/// Errors of subcommands. Each kind has its own exit code.
+#[derive(Debug)]
+pub enum Error {
+    /// Invalid command line. Exit code 2.
+    Usage(String),
+    /// Failures in reading, processing or writing data. Exit code 1.
+    Failure(String),
+    /// The reader of stdout has gone, e.g. `mill ... | head`. Exits quietly.
+    BrokenPipe,
+}
+
+impl Error {
+    pub fn code(&self) -> i32 {
+        match *self {
+            Error::Usage(_)   => 2,
+            Error::Failure(_) => 1,
+            Error::BrokenPipe => 0,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Usage(ref s)   => write!(f, "{}", s),
+            Error::Failure(ref s) => write!(f, "{}", s),
+            Error::BrokenPipe     => write!(f, "broken pipe"),
+        }
+    }
+}
+
+impl From<String> for Error {
+    fn from(s: String) -> Error {Error::Failure(s)}
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        if e.kind() == io::ErrorKind::BrokenPipe {
+            Error::BrokenPipe
+        } else {
+            Error::Failure(e.to_string())
+        }
+    }
+}
+
+/// Command line arguments of a subcommand. Options are taken out first and
+/// then the remaining positional arguments are checked.
+pub struct Args {
+    values : Vec<String>,
+    usage  : &'static str,
+}
+
+impl Args {
+    pub fn new(values: &[String], usage: &'static str) -> Args {
+        Args{values: values.to_vec(), usage: usage}
+    }
+
+    pub fn usage(&self) -> Error {
+        Error::Usage(format!("usage: {}", self.usage))
+    }
+
+    fn flags(name: &str) -> Vec<String> {
+        if name.len() == 1 {vec![format!("-{}", name)]} else {vec![format!("--{}", name)]}
+    }
+
+    /// Removes `--name <value>` or `--name=<value>` and returns the value.
+    /// A one-letter name is written as `-n <value>`.
+    pub fn option(&mut self, names: &[&str]) -> Result<Option<String>, Error> {
+        for name in names.iter() {
+            for flag in Args::flags(name) {
+                let prefix = format!("{}=", flag);
+                if let Some(idx) = self.values.iter().position(|a| a.starts_with(&prefix)) {
+                    let arg = self.values.remove(idx);
+                    return Ok(Some(arg[prefix.len()..].to_owned()));
+                }
+                if let Some(idx) = self.values.iter().position(|a| *a == flag) {
+                    if idx + 1 == self.values.len() {
+                        return Err(Error::Usage(format!("{} needs a value", flag)));
+                    }
+                    self.values.remove(idx);
+                    return Ok(Some(self.values.remove(idx)));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Parses the value of an option.
+    pub fn parsed<T: FromStr>(&mut self, names: &[&str]) -> Result<Option<T>, Error>
+        where T::Err: fmt::Display {
+        match try!(self.option(names)) {
+            Some(v) => v.parse::<T>().map(Some).map_err(|e| {
+                Error::Usage(format!("invalid value for --{}: {}: {}", names[0], v, e))
+            }),
+            None => Ok(None),
+        }
+    }
+
+    /// Removes `--name` and returns whether it was given.
+    pub fn flag(&mut self, name: &str) -> bool {
+        let flag = Args::flags(name).remove(0);
+        match self.values.iter().position(|a| *a == flag) {
+            Some(idx) => {self.values.remove(idx); true}
+            None      => false,
+        }
+    }
+
+    /// Reads `--select <selection>` common to all the subcommands.
+    pub fn selection(&mut self) -> Result<Option<Selection>, Error> {
+        match try!(self.option(&["select"])) {
+            Some(s) => s.parse::<Selection>().map(Some).map_err(Error::Usage),
+            None    => Ok(None),
+        }
+    }
+
+    /// The remaining arguments, between `min` and `max` of them. An
+    /// unknown option is an error. `-` stands for stdin or stdout.
+    pub fn positional(self, min: usize, max: usize) -> Result<Vec<String>, Error> {
+        if let Some(unknown) = self.values.iter().find(|a| a.starts_with('-') && a.len() > 1) {
+            return Err(Error::Usage(format!("unknown option: {}\nusage: {}", unknown, self.usage)));
+        }
+        if self.values.len() < min || max < self.values.len() {
+            return Err(self.usage());
+        }
+        Ok(self.values)
+    }
+}
@@ -0,0 +1,31 @@
+use mill::backmap::Backmapping;
+use args::{Args, Error};
+use io::{self, Format};
+
+pub const USAGE: &'static str = "mill backmap <input> [--side-chains] [-o <output>] \
+[--from <format>] [--to <format>] [--select <selection>]
+
+Rebuilds the backbone of each model of a CA trace, e.g. the output of `mill cg`
+or a frame of a CG trajectory, from a library of ideal fragments. The CA atom
+of each residue is used, or its only atom. --side-chains adds side chains in
+the most common rotamers that do not clash. Hydrogens are not built.";
+
+/// Writes the rebuilt structure to stdout by default. The selection is
+/// applied to the input.
+pub fn run(args: &[String]) -> Result<(), Error> {
+    let mut args    = Args::new(args, USAGE);
+    let selection   = try!(args.selection());
+    let side_chains = args.flag("side-chains");
+    let output      = try!(args.option(&["o", "output"])).unwrap_or("-".to_owned());
+    let from        = try!(args.parsed::<Format>(&["from"]));
+    let to          = try!(args.parsed::<Format>(&["to"]));
+    let args        = try!(args.positional(1, 1));
+
+    let mut structure = try!(io::read(&args[0], from));
+    if let Some(selection) = selection {
+        structure = selection.select_structure(&structure);
+    }
+    let rebuilt = try!(Backmapping::new(side_chains).structure(&structure)
+                           .map_err(|e| format!("{}: {}", args[0], e)));
+    io::write(&output, to, &rebuilt)
+}
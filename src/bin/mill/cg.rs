@@ -0,0 +1,78 @@
+use std::fs::File;
+use std::io::{self as stdio, Read, Write};
+use mill::cg::{Mapping, Position, Scheme, THREE_SITE_DNA};
+use args::{Args, Error};
+use io::{self, Format};
+
+pub const USAGE: &'static str = "mill cg <input> [--bead ca|com|sidechain] \
+[--name <bead name>] [--scheme <file>|3spn] [-o <output>] [--from <format>] \
+[--to <format>] [--select <selection>]
+
+Each residue of ATOM records becomes a bead at the CA atom (ca, default),
+the center of mass (com) or the side-chain center of mass (sidechain; CA for
+glycine). Beads are named CA, CM and CB respectively unless --name is given.
+--scheme maps residues to several beads as defined in a file (see the cg
+module), or by the built-in 3SPN-style DNA mapping (3spn). Residues with
+missing atoms are reported to stderr.";
+
+fn read_scheme(path: &str) -> Result<Scheme, Error> {
+    if path == "3spn" {
+        return Ok(THREE_SITE_DNA.parse().expect("the built-in scheme is valid"));
+    }
+    let mut text = String::new();
+    try!(File::open(path).and_then(|mut f| f.read_to_string(&mut text))
+             .map_err(|e| Error::Failure(format!("{}: {}", path, e))));
+    text.parse::<Scheme>().map_err(|e| Error::Failure(format!("{}: {}", path, e)))
+}
+
+/// Writes one bead per residue, or the beads of a scheme, to stdout by
+/// default. The selection is applied to the atoms before mapping.
+pub fn run(args: &[String]) -> Result<(), Error> {
+    let mut args  = Args::new(args, USAGE);
+    let selection = try!(args.selection());
+    let output    = try!(args.option(&["o", "output"])).unwrap_or("-".to_owned());
+    let position  = try!(args.parsed::<Position>(&["bead"]));
+    let name      = try!(args.option(&["name"]));
+    let scheme    = try!(args.option(&["scheme"]));
+    let from      = try!(args.parsed::<Format>(&["from"]));
+    let to        = try!(args.parsed::<Format>(&["to"]));
+    let args      = try!(args.positional(1, 1));
+    if scheme.is_some() && (position.is_some() || name.is_some()) {
+        return Err(Error::Usage("--scheme defines beads; --bead and --name are not used \
+                                 with it".to_owned()));
+    }
+
+    let mut structure = try!(io::read(&args[0], from));
+    if let Some(selection) = selection {
+        structure = selection.select_structure(&structure);
+    }
+    let cg = match scheme {
+        Some(path) => {
+            let scheme = try!(read_scheme(&path));
+            let (cg, report) = try!(scheme.structure(&structure)
+                                          .map_err(|e| format!("{}: {}", args[0], e)));
+            let stderr = stdio::stderr();
+            let mut stderr = stderr.lock();
+            for missing in report.missing.iter() {
+                try!(writeln!(stderr, "warning: {} {}: missing {}", missing.residue,
+                              missing.bead, missing.atoms.join(" ")));
+            }
+            for residue in report.unmapped.iter() {
+                try!(writeln!(stderr, "warning: {} is not in the scheme", residue));
+            }
+            cg
+        }
+        None => {
+            let mut mapping = Mapping::new(position.unwrap_or(Position::Alpha));
+            if let Some(name) = name {
+                if name.is_empty() || name.len() > 4 || !name.is_ascii() {
+                    return Err(Error::Usage("a bead name has 1 to 4 ASCII characters"
+                                                .to_owned()));
+                }
+                mapping.name = name;
+            }
+            try!(mapping.structure(&structure).map_err(|e| format!("{}: {}", args[0], e)))
+        }
+    };
+    io::write(&output, to, &cg)
+}
@@ -0,0 +1,30 @@
+use std::io::{self, Write};
+use args::{Args, Error};
+use io::{read, Format};
+
+pub const USAGE: &'static str = "mill compare <model> <reference> [--from <format>] \
+[--select <selection>]";
+
+/// Prints RMSD, TM-score, GDT-TS and GDT-HA computed from CA atoms.
+pub fn run(args: &[String]) -> Result<(), Error> {
+    let mut args  = Args::new(args, USAGE);
+    let selection = try!(args.selection());
+    let from      = try!(args.parsed::<Format>(&["from"]));
+    let args      = try!(args.positional(2, 2));
+
+    let mut model     = try!(read(&args[0], from)).models.remove(0).atoms;
+    let mut reference = try!(read(&args[1], from)).models.remove(0).atoms;
+    if let Some(selection) = selection {
+        model     = selection.select(&model);
+        reference = selection.select(&reference);
+    }
+    let score = try!(::mill::similarity::compare(&model, &reference));
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    try!(writeln!(out, "aligned  : {}", score.aligned));
+    try!(writeln!(out, "RMSD     : {:.3}", score.rmsd));
+    try!(writeln!(out, "TM-score : {:.4}", score.tm_score));
+    try!(writeln!(out, "GDT-TS   : {:.4}", score.gdt_ts));
+    try!(writeln!(out, "GDT-HA   : {:.4}", score.gdt_ha));
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use std::io::{self as stdio, Write};
+use mill::complete::{Completion, Naming};
+use args::{Args, Error};
+use io::{self, Format};
+
+pub const USAGE: &'static str = "mill complete <input> [--no-hydrogens] \
+[--naming pdb|charmm|amber] [--ph <pH>] [-o <output>] [--from <format>] [--to <format>] \
+[--select <selection>]
+
+Adds missing heavy atoms of amino acids from residue templates, keeping the chi
+angles of the atoms present, and rebuilds their hydrogens; --no-hydrogens keeps
+the hydrogens of the input as they are. Atom and residue names follow PDB v3
+(pdb, default), CHARMM or Amber. HIS, ASP, GLU and LYS are protonated by their
+names (HIS as HIE), or by model pKa values at --ph. Added atoms and residues
+left as they are reported to stderr.";
+
+/// Writes the completed structure to stdout by default. The selection is
+/// applied to the input.
+pub fn run(args: &[String]) -> Result<(), Error> {
+    let mut args  = Args::new(args, USAGE);
+    let selection = try!(args.selection());
+    let hydrogens = !args.flag("no-hydrogens");
+    let naming    = try!(args.parsed::<Naming>(&["naming"]));
+    let ph        = try!(args.parsed::<f64>(&["ph"]));
+    let output    = try!(args.option(&["o", "output"])).unwrap_or("-".to_owned());
+    let from      = try!(args.parsed::<Format>(&["from"]));
+    let to        = try!(args.parsed::<Format>(&["to"]));
+    let args      = try!(args.positional(1, 1));
+    if let Some(ph) = ph {
+        if !(ph >= 0.0 && ph <= 14.0) {
+            return Err(Error::Usage(format!("pH {} is not between 0 and 14", ph)));
+        }
+    }
+
+    let mut structure = try!(io::read(&args[0], from));
+    if let Some(selection) = selection {
+        structure = selection.select_structure(&structure);
+    }
+    let completion = Completion{hydrogens: hydrogens, naming: naming.unwrap_or(Naming::Pdb),
+                                ph: ph};
+    let (completed, report) = completion.structure(&structure);
+    let stderr = stdio::stderr();
+    let mut stderr = stderr.lock();
+    for added in report.added.iter() {
+        try!(writeln!(stderr, "warning: {}: added {}", added.residue, added.atoms.join(" ")));
+    }
+    for residue in report.incomplete.iter() {
+        try!(writeln!(stderr, "warning: {} has no N, CA or C; not completed", residue));
+    }
+    for residue in report.unknown.iter() {
+        try!(writeln!(stderr, "warning: {} has no template; not completed", residue));
+    }
+    io::write(&output, to, &completed)
+}
@@ -0,0 +1,60 @@
+use std::io::{self, Write};
+use mill::contact::{Definition, NativeContacts, contacts};
+use args::{Args, Error};
+use io::{read, Format};
+
+pub const USAGE: &'static str = "mill contacts <input> \
+[--definition ca[:8.0]|heavy[:4.5]|shadow[:6.0[:1.0]]] [--from <format>] \
+[--select <selection>]";
+
+pub const Q_USAGE: &'static str = "mill q <reference> <trajectory> \
+[--definition <definition>] [--tolerance 1.2] [--from <format>] [--select <selection>]";
+
+fn definition(args: &mut Args) -> Result<Definition, Error> {
+    Ok(try!(args.parsed::<Definition>(&["definition"])).unwrap_or(Definition::default()))
+}
+
+/// Prints residue pairs in contact in the first model and their distances.
+pub fn run(args: &[String]) -> Result<(), Error> {
+    let mut args   = Args::new(args, USAGE);
+    let selection  = try!(args.selection());
+    let definition = try!(definition(&mut args));
+    let from       = try!(args.parsed::<Format>(&["from"]));
+    let args       = try!(args.positional(1, 1));
+
+    let mut atoms = try!(read(&args[0], from)).models.remove(0).atoms;
+    if let Some(selection) = selection {
+        atoms = selection.select(&atoms);
+    }
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for contact in contacts(&atoms, definition) {
+        try!(writeln!(out, "{} {} {:.3}", contact.lhs, contact.rhs, contact.distance));
+    }
+    Ok(())
+}
+
+/// Prints the fraction of native contacts in each model of the trajectory.
+pub fn q(args: &[String]) -> Result<(), Error> {
+    let mut args   = Args::new(args, Q_USAGE);
+    let selection  = try!(args.selection());
+    let definition = try!(definition(&mut args));
+    let tolerance  = try!(args.parsed::<f64>(&["tolerance"])).unwrap_or(1.2);
+    let from       = try!(args.parsed::<Format>(&["from"]));
+    let args       = try!(args.positional(2, 2));
+
+    let mut reference  = try!(read(&args[0], from)).models.remove(0).atoms;
+    let mut trajectory = try!(read(&args[1], from));
+    if let Some(selection) = selection {
+        reference  = selection.select(&reference);
+        trajectory = selection.select_structure(&trajectory);
+    }
+    let native = NativeContacts::new(&reference, definition);
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for model in trajectory.models.iter() {
+        let q = try!(native.q(&model.atoms, tolerance));
+        try!(writeln!(out, "{} {:.4}", model.serial, q));
+    }
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use std::str::FromStr;
+use std::string::String;
+use args::{Args, Error};
+use io::{self, Format};
+
+pub const USAGE: &'static str = "mill convert <input> <output> [--from <format>] \
+[--to <format>] [--topology <file>] [--frames <first>:<last>[:<stride>]] \
+[--select <selection>]
+
+formats: pdb, gro, xyz, cif (mmCIF) and dcd. Each model is a frame.
+--topology gives atom names to an input without them, e.g. dcd or xyz.
+--frames counts from 1 and includes the last; `10:`, `:50` and `::5` work.";
+
+/// Frames to convert: `first:last:stride`, 1-based and inclusive.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Frames {
+    first  : usize,
+    last   : Option<usize>,
+    stride : usize,
+}
+
+impl FromStr for Frames {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Frames, String> {
+        let fields: Vec<&str> = s.split(':').collect();
+        if fields.len() > 3 {
+            return Err("too many fields".to_owned());
+        }
+        let number = |i: usize| -> Result<Option<usize>, String> {
+            match fields.get(i).map(|f| f.trim()) {
+                None | Some("") => Ok(None),
+                Some(f) => match f.parse::<usize>() {
+                    Ok(0)  => Err("frames count from 1".to_owned()),
+                    Ok(n)  => Ok(Some(n)),
+                    Err(e) => Err(e.to_string()),
+                },
+            }
+        };
+        let first = try!(number(0));
+        let last  = if fields.len() == 1 {first} else {try!(number(1))};
+        Ok(Frames{first: first.unwrap_or(1), last: last, stride: try!(number(2)).unwrap_or(1)})
+    }
+}
+
+impl Frames {
+    fn contains(&self, frame: usize) -> bool {
+        self.first <= frame && self.last.map_or(true, |l| frame <= l) &&
+            (frame - self.first) % self.stride == 0
+    }
+}
+
+/// Converts a file into another format, determined by the extensions.
+/// Frames are taken first, then the selection is applied.
+pub fn run(args: &[String]) -> Result<(), Error> {
+    let mut args  = Args::new(args, USAGE);
+    let selection = try!(args.selection());
+    let from      = try!(args.parsed::<Format>(&["from"]));
+    let to        = try!(args.parsed::<Format>(&["to"]));
+    let topology  = try!(args.option(&["topology"]));
+    let frames    = try!(args.parsed::<Frames>(&["frames"]));
+    let args      = try!(args.positional(2, 2));
+
+    let mut structure = try!(io::read(&args[0], from));
+    if let Some(path) = topology {
+        let topology = try!(io::read(&path, None));
+        structure = try!(structure.with_topology(&topology).map_err(|e| {
+            Error::Failure(format!("{} on {}: {}", args[0], path, e))
+        }));
+    }
+    if let Some(frames) = frames {
+        let models = ::std::mem::replace(&mut structure.models, Vec::new());
+        structure.models = models.into_iter().enumerate()
+            .filter(|&(i, _)| frames.contains(i + 1)).map(|(_, m)| m).collect();
+        if structure.models.is_empty() {
+            return Err(Error::Failure(format!("{}: no frame in the range", args[0])));
+        }
+    }
+    if let Some(selection) = selection {
+        structure = selection.select_structure(&structure);
+    }
+    io::write(&args[1], to, &structure)
+}
@@ -0,0 +1,47 @@
+use std::io::{self, Write};
+use std::string::String;
+use args::{Args, Error};
+use io::{read, Format};
+
+pub const USAGE: &'static str = "mill dihedrals <input> [--from <format>] \
+[--select <selection>]";
+
+fn format_angle(angle: Option<f64>) -> String {
+    match angle {
+        Some(a) => format!("{:8.2}", a),
+        None    => format!("{:>8}", "-"),
+    }
+}
+
+/// Prints phi, psi, omega and chi1-chi4 of each residue in each model.
+pub fn run(args: &[String]) -> Result<(), Error> {
+    let mut args  = Args::new(args, USAGE);
+    let selection = try!(args.selection());
+    let from      = try!(args.parsed::<Format>(&["from"]));
+    let args      = try!(args.positional(1, 1));
+
+    let mut structure = try!(read(&args[0], from));
+    if let Some(selection) = selection {
+        structure = selection.select_structure(&structure);
+    }
+    let angles = try!(::mill::dihedral::per_model(&structure));
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    try!(writeln!(out, "{:7} {:<9}{:>8} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8}",
+                  "# model", "residue", "phi", "psi", "omega", "chi1", "chi2", "chi3", "chi4"));
+    for (model, residues) in structure.models.iter().zip(angles.iter()) {
+        for residue in residues.iter() {
+            let mut line = format!("{:7} {:<9}", model.serial, residue.residue.to_string());
+            for angle in [residue.phi, residue.psi, residue.omega].iter() {
+                line.push_str(&format_angle(*angle));
+                line.push(' ');
+            }
+            for i in 0..4 {
+                line.push_str(&format_angle(residue.chi.get(i).cloned().unwrap_or(None)));
+                line.push(' ');
+            }
+            try!(writeln!(out, "{}", line.trim_end()));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use std::collections::HashSet;
+use std::io::{self as stdio, Write};
+use mill::Atom;
+use mill::enm::{ElasticNetwork, Network, b_factors, correlation};
+use mill::pdb::{AltlocPolicy, AtomData, Model, ResidueId, ResidueKind, Structure};
+use mill::pdb::{classify, resolve_altlocs};
+use args::{Args, Error};
+use io::{self, Format};
+
+pub const USAGE: &'static str = "mill enm <input> [--network anm|gnm] [--cutoff <angstroms>] \
+[--gamma 1.0] [--modes <count>] [--fluctuations] [--animate <mode> -o <output>] \
+[--amplitude 2.0] [--frames 20] [--from <format>] [--to <format>] \
+[--select <selection>]
+
+Normal modes of an elastic network of the CA atoms of amino acids in the first
+model, with alternate locations of the highest occupancy; ANM by default, with
+a cutoff of 15 angstroms (7.3 for GNM). Modes are counted from 1, the slowest
+first, skipping the rigid-body ones. By default the eigenvalues and
+frequencies of the slowest --modes (10) are printed. --fluctuations prints the
+B-factors by the modes, all unless --modes is given, fitted to the B-factors
+in the file, with their correlation. --animate writes the ANM mode as models
+of a cycle with the largest RMSD of --amplitude angstroms, in the format of
+--to or of the output file name.";
+
+/// Prints the modes or fluctuations, or writes a mode as a trajectory.
+pub fn run(args: &[String]) -> Result<(), Error> {
+    let mut args     = Args::new(args, USAGE);
+    let selection    = try!(args.selection());
+    let network      = try!(args.parsed::<Network>(&["network"]))
+                           .unwrap_or(Network::Anisotropic);
+    let cutoff       = try!(args.parsed::<f64>(&["cutoff"]));
+    let gamma        = try!(args.parsed::<f64>(&["gamma"])).unwrap_or(1.0);
+    let count        = try!(args.parsed::<usize>(&["modes"]));
+    let fluctuations = args.flag("fluctuations");
+    let animate      = try!(args.parsed::<usize>(&["animate"]));
+    let output       = try!(args.option(&["o", "output"]));
+    let amplitude    = try!(args.parsed::<f64>(&["amplitude"])).unwrap_or(2.0);
+    let frames       = try!(args.parsed::<usize>(&["frames"])).unwrap_or(20);
+    let from         = try!(args.parsed::<Format>(&["from"]));
+    let to           = try!(args.parsed::<Format>(&["to"]));
+    let args         = try!(args.positional(1, 1));
+    if animate.is_some() && fluctuations {
+        return Err(Error::Usage("--animate and --fluctuations are exclusive".to_owned()));
+    }
+    if animate == Some(0) || frames == 0 {
+        return Err(Error::Usage("modes and frames count from 1".to_owned()));
+    }
+
+    let mut atoms = try!(io::read(&args[0], from)).models.remove(0).atoms;
+    if let Some(selection) = selection {
+        atoms = selection.select(&atoms);
+    }
+    // one CA atom per residue
+    let mut residues = HashSet::new();
+    let atoms: Vec<Atom> = resolve_altlocs(&atoms, AltlocPolicy::HighestOccupancy).into_iter()
+        .filter(|a| {
+            a.atom_name() == "CA" && classify(a.residue_name()) == ResidueKind::AminoAcid &&
+                residues.insert(ResidueId::of(a))
+        }).collect();
+    let mut enm = ElasticNetwork::new(network);
+    enm.gamma = gamma;
+    if let Some(cutoff) = cutoff {
+        enm.cutoff = cutoff;
+    }
+    let modes = try!(enm.modes(&atoms).map_err(|e| format!("{}: {}", args[0], e)));
+
+    if let Some(mode) = animate {
+        let positions = try!(modes.animate(&atoms, mode - 1, amplitude, frames));
+        let mut model = Model::new(1);
+        model.atoms = atoms;
+        let mut structure = Structure::new();
+        for (i, p) in positions.iter().enumerate() {
+            structure.models.push(try!(model.with_positions(i as i32 + 1, p)));
+        }
+        return io::write(&output.unwrap_or("-".to_owned()), to, &structure);
+    }
+    if output.is_some() {
+        return Err(Error::Usage("-o is for --animate".to_owned()));
+    }
+
+    let stdout = stdio::stdout();
+    let mut out = stdout.lock();
+    if fluctuations {
+        let predicted = b_factors(&modes.fluctuations(count));
+        let observed: Vec<f64> = atoms.iter().map(|a| a.temperature_factor()).collect();
+        // least squares through the origin; the scale is kT/gamma
+        let scale = predicted.iter().zip(observed.iter()).map(|(p, o)| p * o).sum::<f64>() /
+                    predicted.iter().map(|p| p * p).sum::<f64>();
+        match correlation(&predicted, &observed) {
+            Some(r) => try!(writeln!(out, "# correlation {:.3}, kT/gamma {:.4} angstrom^2",
+                                     r, scale)),
+            None    => try!(writeln!(out, "# no variance in the B-factors")),
+        }
+        for (atom, (p, o)) in atoms.iter().zip(predicted.iter().zip(observed.iter())) {
+            try!(writeln!(out, "{} {:.2} {:.2}", ResidueId::of(atom), p * scale, o));
+        }
+        return Ok(());
+    }
+    for (k, mode) in modes.modes.iter().take(count.unwrap_or(10)).enumerate() {
+        try!(writeln!(out, "{} {:.6} {:.6}", k + 1, mode.eigenvalue, mode.frequency()));
+    }
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use std::str::FromStr;
+use std::path::Path;
+use mill::contact::Definition;
+use mill::go::{ForceConstants, GoModel};
+use args::{Args, Error};
+use io::{self, Format};
+
+pub const USAGE: &'static str = "mill go <input> [-o <output>] [--to toml|top|itp] \
+[--definition <definition>] [--epsilon 1.0] [--name <molecule>] [--from <format>] \
+[--select <selection>]
+
+A Go-like model with a bead at each CA atom of the first model: bonds, angles
+and dihedrals of the native structure, and native contacts by the definition
+as in `mill contacts` (ca:8.0 by default). The force constants are those of
+SMOG in units of --epsilon kJ/mol. The output is TOML, a GROMACS topology
+(top) or its molecule type (itp), as guessed from the extension; TOML is
+written to stdout by default. Write the coordinates with `mill cg`.";
+
+/// Output formats of the topology.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Topology {
+    Toml,
+    Top,
+    Itp,
+}
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Topology, String> {
+        match s {
+            "toml" => Ok(Topology::Toml),
+            "top"  => Ok(Topology::Top),
+            "itp"  => Ok(Topology::Itp),
+            _ => Err(format!("unknown topology format `{}`; use toml, top or itp", s)),
+        }
+    }
+}
+
+/// Writes the topology of a Go-like model of the first model.
+pub fn run(args: &[String]) -> Result<(), Error> {
+    let mut args   = Args::new(args, USAGE);
+    let selection  = try!(args.selection());
+    let output     = try!(args.option(&["o", "output"])).unwrap_or("-".to_owned());
+    let to         = try!(args.parsed::<Topology>(&["to"]));
+    let definition = try!(args.parsed::<Definition>(&["definition"]));
+    let epsilon    = try!(args.parsed::<f64>(&["epsilon"]));
+    let name       = try!(args.option(&["name"])).unwrap_or("Protein".to_owned());
+    let from       = try!(args.parsed::<Format>(&["from"]));
+    let args       = try!(args.positional(1, 1));
+    let to = match to {
+        Some(to) => to,
+        None if output == "-" => Topology::Toml,
+        None => {
+            let extension = Path::new(&output).extension().and_then(|e| e.to_str());
+            try!(extension.unwrap_or("").parse::<Topology>().map_err(|_| Error::Usage(
+                format!("{}: unknown extension; give --to toml, top or itp", output))))
+        }
+    };
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(Error::Usage("a molecule name is a word".to_owned()));
+    }
+
+    let mut atoms = try!(io::read(&args[0], from)).models.remove(0).atoms;
+    if let Some(selection) = selection {
+        atoms = selection.select(&atoms);
+    }
+    let model = try!(GoModel::new(&atoms, definition.unwrap_or(Definition::default()))
+                         .map_err(|e| format!("{}: {}", args[0], e)));
+    let mut constants = ForceConstants::default();
+    if let Some(epsilon) = epsilon {
+        constants.epsilon = epsilon;
+    }
+    let mut out = try!(io::create(&output));
+    try!(match to {
+        Topology::Toml => model.write_toml(&mut out, &constants),
+        Topology::Top  => model.write_top(&mut out, &constants, &name),
+        Topology::Itp  => model.write_itp(&mut out, &constants, &name),
+    });
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use std::io::{self, Write};
+use std::string::String;
+use std::vec::Vec;
+use mill::pdb::{Header, Structure, Summary};
+use args::{Args, Error};
+use io::{read, Format};
+
+pub const USAGE: &'static str = "mill info <input> [--json] [--from <format>] \
+[--select <selection>]";
+
+/// A JSON string literal.
+fn quote(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"'  => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn json_number(value: Option<f64>) -> String {
+    match value {
+        Some(v) if v.is_finite() => format!("{}", v),
+        _ => "null".to_owned(),
+    }
+}
+
+fn json_list(items: Vec<String>) -> String {
+    format!("[{}]", items.join(", "))
+}
+
+fn write_json<W: Write>(out: &mut W, summary: &Summary, header: &Header)
+    -> Result<(), Error> {
+    let chains: Vec<String> = summary.chains.iter().map(|c| {
+        let breaks: Vec<String> = c.breaks.iter().map(|&(l, r)| {
+            json_list(vec![quote(&l.to_string()), quote(&r.to_string())])
+        }).collect();
+        format!("{{\"id\": {}, \"residues\": {}, \"atoms\": {}, \"sequence\": {}, \
+                 \"breaks\": {}}}", quote(&c.chain_id.to_string()), c.residues, c.atoms,
+                quote(&c.sequence), json_list(breaks))
+    }).collect();
+    let ligands: Vec<String> = summary.ligands.iter().map(|l| quote(&l.to_string())).collect();
+    let altlocs: Vec<String> = summary.altlocs.iter().map(|a| quote(&a.to_string())).collect();
+    let cell = match header.unit_cell {
+        Some(ref c) => format!("{{\"a\": {}, \"b\": {}, \"c\": {}, \"alpha\": {}, \
+                                \"beta\": {}, \"gamma\": {}, \"space_group\": {}}}",
+                               c.a, c.b, c.c, c.alpha, c.beta, c.gamma, quote(&c.space_group)),
+        None => "null".to_owned(),
+    };
+    try!(writeln!(out, "{{"));
+    try!(writeln!(out, "  \"header\": {{\"id\": {}, \"classification\": {}, \"date\": {}, \
+                        \"title\": {}, \"method\": {}, \"resolution\": {}, \"r_work\": {}, \
+                        \"r_free\": {}}},",
+                  quote(&header.id_code), quote(&header.classification),
+                  quote(&header.deposition_date), quote(&header.title),
+                  quote(&header.experimental_method), json_number(header.resolution),
+                  json_number(header.r_work), json_number(header.r_free)));
+    try!(writeln!(out, "  \"models\": {},", summary.models));
+    try!(writeln!(out, "  \"atoms\": {},", summary.atoms));
+    try!(writeln!(out, "  \"hetatms\": {},", summary.hetatms));
+    try!(writeln!(out, "  \"chains\": {},", json_list(chains)));
+    try!(writeln!(out, "  \"ligands\": {},", json_list(ligands)));
+    try!(writeln!(out, "  \"waters\": {},", summary.waters));
+    try!(writeln!(out, "  \"ions\": {},", summary.ions));
+    try!(writeln!(out, "  \"altlocs\": {},", json_list(altlocs)));
+    try!(writeln!(out, "  \"bonds\": {},", summary.bonds));
+    try!(writeln!(out, "  \"box\": {}", cell));
+    try!(writeln!(out, "}}"));
+    Ok(())
+}
+
+fn write_text<W: Write>(out: &mut W, summary: &Summary, header: &Header)
+    -> Result<(), Error> {
+    let fields = [("id", &header.id_code), ("class", &header.classification),
+                  ("date", &header.deposition_date), ("title", &header.title),
+                  ("method", &header.experimental_method)];
+    for &(name, value) in fields.iter() {
+        if !value.is_empty() {
+            try!(writeln!(out, "{:<10}: {}", name, value));
+        }
+    }
+    if let Some(resolution) = header.resolution {
+        try!(writeln!(out, "{:<10}: {:.2}", "resolution", resolution));
+    }
+    if let (Some(work), Some(free)) = (header.r_work, header.r_free) {
+        try!(writeln!(out, "{:<10}: {:.3} / {:.3}", "R / Rfree", work, free));
+    }
+    if let Some(ref c) = header.unit_cell {
+        try!(writeln!(out, "{:<10}: {:.3} {:.3} {:.3} {:.2} {:.2} {:.2} {}", "box",
+                      c.a, c.b, c.c, c.alpha, c.beta, c.gamma, c.space_group));
+    }
+    try!(writeln!(out, "{:<10}: {}", "models", summary.models));
+    try!(writeln!(out, "{:<10}: {}", "atoms", summary.atoms));
+    try!(writeln!(out, "{:<10}: {}", "hetatms", summary.hetatms));
+    try!(writeln!(out, "{:<10}: {}", "waters", summary.waters));
+    try!(writeln!(out, "{:<10}: {}", "ions", summary.ions));
+    let ligands: Vec<String> = summary.ligands.iter().map(|l| l.to_string()).collect();
+    try!(writeln!(out, "{:<10}: {}", "ligands", ligands.len()));
+    if !ligands.is_empty() {
+        try!(writeln!(out, "  {}", ligands.join(" ")));
+    }
+    if !summary.altlocs.is_empty() {
+        let altlocs: String = summary.altlocs.iter().collect();
+        try!(writeln!(out, "{:<10}: {}", "altlocs", altlocs));
+    }
+    if summary.bonds != 0 {
+        try!(writeln!(out, "{:<10}: {}", "bonds", summary.bonds));
+    }
+    try!(writeln!(out, "{:<10}: {}", "chains", summary.chains.len()));
+    for chain in summary.chains.iter() {
+        try!(writeln!(out, "chain {}: {} residues, {} atoms",
+                      chain.chain_id, chain.residues, chain.atoms));
+        try!(writeln!(out, "  sequence: {}", chain.sequence));
+        for &(l, r) in chain.breaks.iter() {
+            try!(writeln!(out, "  break   : {} - {}", l, r));
+        }
+    }
+    Ok(())
+}
+
+/// Prints what a structure contains: header, counts per chain, sequences,
+/// chain breaks, ligands, altlocs and the box.
+pub fn run(args: &[String]) -> Result<(), Error> {
+    let mut args  = Args::new(args, USAGE);
+    let selection = try!(args.selection());
+    let json      = args.flag("json");
+    let from      = try!(args.parsed::<Format>(&["from"]));
+    let args      = try!(args.positional(1, 1));
+
+    let mut structure: Structure = try!(read(&args[0], from));
+    if let Some(selection) = selection {
+        structure = selection.select_structure(&structure);
+    }
+    let summary = Summary::of(&structure);
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    if json {
+        write_json(&mut out, &summary, &structure.header)
+    } else {
+        write_text(&mut out, &summary, &structure.header)
+    }
+}
@@ -0,0 +1,109 @@
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::string::String;
+use mill::{dcd, gro, mmcif, xyz};
+use mill::pdb::{Reader, Writer, Structure};
+use args::Error;
+
+/// File formats known to mill.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Format {
+    Pdb,
+    Gro,
+    Xyz,
+    Cif,
+    Dcd,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s.to_lowercase().as_str() {
+            "pdb" | "ent"   => Ok(Format::Pdb),
+            "gro"           => Ok(Format::Gro),
+            "xyz"           => Ok(Format::Xyz),
+            "cif" | "mmcif" => Ok(Format::Cif),
+            "dcd"           => Ok(Format::Dcd),
+            _ => Err(format!("unknown format: {}", s)),
+        }
+    }
+}
+
+impl Format {
+    /// Guesses the format from the extension. stdin and stdout (`-`) are
+    /// PDB unless `--from` or `--to` says otherwise.
+    pub fn of_path(path: &str) -> Result<Format, Error> {
+        if path == "-" {
+            return Ok(Format::Pdb);
+        }
+        match Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some(ext) => ext.parse().map_err(|_| {
+                Error::Usage(format!("{}: unknown extension; use --from or --to", path))
+            }),
+            None => Err(Error::Usage(format!("{}: no extension; use --from or --to", path))),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match *self {
+            Format::Pdb => "pdb",
+            Format::Gro => "gro",
+            Format::Xyz => "xyz",
+            Format::Cif => "cif",
+            Format::Dcd => "dcd",
+        }
+    }
+}
+
+fn open(path: &str) -> Result<Box<dyn Read>, Error> {
+    if path == "-" {
+        Ok(Box::new(io::stdin()))
+    } else {
+        File::open(path).map(|f| Box::new(f) as Box<dyn Read>)
+            .map_err(|e| Error::Failure(format!("{}: {}", path, e)))
+    }
+}
+
+/// Opens a file to write, or stdout for `-`.
+pub fn create(path: &str) -> Result<Box<dyn Write>, Error> {
+    if path == "-" {
+        Ok(Box::new(io::stdout()))
+    } else {
+        File::create(path).map(|f| Box::new(f) as Box<dyn Write>)
+            .map_err(|e| Error::Failure(format!("{}: {}", path, e)))
+    }
+}
+
+/// Reads a structure. `format` overrides the extension.
+pub fn read(path: &str, format: Option<Format>) -> Result<Structure, Error> {
+    let format = match format {Some(f) => f, None => try!(Format::of_path(path))};
+    let input = try!(open(path));
+    let structure = try!(match format {
+        Format::Pdb => Reader::new(input).read_structure(),
+        Format::Gro => gro::Reader::new(input).read_structure(),
+        Format::Xyz => xyz::Reader::new(input).read_structure(),
+        Format::Cif => mmcif::Reader::new(input).read_structure(),
+        Format::Dcd => dcd::Reader::new(input).read_structure(),
+    }.map_err(|e| Error::Failure(format!("{}: {}", path, e))));
+    if structure.models.is_empty() {
+        return Err(Error::Failure(format!("{}: no atom found", path)));
+    }
+    Ok(structure)
+}
+
+/// Writes a structure. `format` overrides the extension.
+pub fn write(path: &str, format: Option<Format>, structure: &Structure) -> Result<(), Error> {
+    let format = match format {Some(f) => f, None => try!(Format::of_path(path))};
+    let output = try!(create(path));
+    try!(match format {
+        Format::Pdb => Writer::new(output).write_structure(structure),
+        Format::Gro => gro::Writer::new(output).write_structure(structure),
+        Format::Xyz => xyz::Writer::new(output).write_structure(structure),
+        Format::Cif => mmcif::Writer::new(output).write_structure(structure),
+        Format::Dcd => dcd::Writer::new(output).write_structure(structure),
+    });
+    Ok(())
+}
@@ -0,0 +1,109 @@
+extern crate rustymill as mill;
+extern crate nalgebra;
+
+mod args;
+mod io;
+mod altloc;
+mod backmap;
+mod cg;
+mod compare;
+mod complete;
+mod contacts;
+mod convert;
+mod dihedrals;
+mod enm;
+mod go;
+mod info;
+mod merge;
+mod pairs;
+mod renumber;
+mod rmsd;
+mod select;
+mod split;
+
+use std::env;
+use std::process;
+use args::Error;
+
+/// Subcommands, their usage and what they do.
+const COMMANDS: &'static [(&'static str, &'static str, &'static str)] = &[
+    ("info",      info::USAGE,      "summary of a structure"),
+    ("convert",   convert::USAGE,   "conversion between file formats"),
+    ("select",    select::USAGE,    "atoms that match a selection"),
+    ("rmsd",      rmsd::USAGE,      "RMSD of each model from a reference"),
+    ("renumber",  renumber::USAGE,  "renumbered atoms and residues"),
+    ("split",     split::USAGE,     "one file per model or chain"),
+    ("merge",     merge::USAGE,     "several files in one"),
+    ("altloc",    altloc::USAGE,    "one conformer per residue"),
+    ("compare",   compare::USAGE,   "RMSD, TM-score and GDT of CA atoms"),
+    ("contacts",  contacts::USAGE,  "residue contacts"),
+    ("q",         contacts::Q_USAGE, "fraction of native contacts per model"),
+    ("dihedrals", dihedrals::USAGE, "backbone and side-chain dihedrals"),
+    ("cg",        cg::USAGE,        "one bead per residue"),
+    ("backmap",   backmap::USAGE,   "all atoms rebuilt from CA atoms"),
+    ("complete",  complete::USAGE,  "missing heavy atoms and hydrogens"),
+    ("pairs",     pairs::USAGE,     "base pairs and stacking of nucleic acids"),
+    ("go",        go::USAGE,        "topology of a structure-based CA model"),
+    ("enm",       enm::USAGE,       "normal modes of an elastic network"),
+];
+
+fn usage() -> String {
+    let mut text = "usage: mill <command> [<args>]\n\ncommands:\n".to_owned();
+    for &(name, _, summary) in COMMANDS.iter() {
+        text.push_str(&format!("    {:<10} {}\n", name, summary));
+    }
+    text.push_str("\nThe format of a file is guessed from its extension. `-` stands for \
+stdin or stdout,\nwhich are PDB unless --from or --to is given. \
+Run `mill help <command>` for details.\n\
+\nexit status: 0 on success, 1 on failure, 2 on invalid arguments.");
+    text
+}
+
+fn help(args: &[String]) -> Result<(), Error> {
+    match args.first() {
+        None => {println!("{}", usage()); Ok(())}
+        Some(name) => match COMMANDS.iter().find(|c| c.0 == name) {
+            Some(command) => {println!("usage: {}", command.1); Ok(())}
+            None => Err(Error::Usage(format!("unknown command: {}", name))),
+        },
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() {
+        eprintln!("{}", usage());
+        process::exit(2);
+    }
+    let (command, rest) = (&args[0], &args[1..]);
+    let result = match command.as_str() {
+        "info"      => info::run(rest),
+        "convert"   => convert::run(rest),
+        "select"    => select::run(rest),
+        "rmsd"      => rmsd::run(rest),
+        "renumber"  => renumber::run(rest),
+        "split"     => split::run(rest),
+        "merge"     => merge::run(rest),
+        "altloc"    => altloc::run(rest),
+        "compare"   => compare::run(rest),
+        "contacts"  => contacts::run(rest),
+        "q"         => contacts::q(rest),
+        "dihedrals" => dihedrals::run(rest),
+        "cg"        => cg::run(rest),
+        "backmap"   => backmap::run(rest),
+        "complete"  => complete::run(rest),
+        "pairs"     => pairs::run(rest),
+        "go"        => go::run(rest),
+        "enm"       => enm::run(rest),
+        "help" | "-h" | "--help" => help(rest),
+        _ => Err(Error::Usage(format!("unknown command: {}\n{}", command, usage()))),
+    };
+    match result {
+        Ok(()) => {}
+        Err(Error::BrokenPipe) => {}
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(e.code());
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::{BTreeSet, HashMap};
+use mill::pdb::{AtomData, AtomDataMut, Model, Structure};
+use args::{Args, Error};
+use io::{self, Format};
+
+pub const USAGE: &'static str = "mill merge <input>... [-o <output>] [--models] \
+[--from <format>] [--to <format>] [--select <selection>]";
+
+/// Merges the inputs, each of one model, into one model, or with
+/// `--models`, all the models into one multi-model file. Atoms are
+/// renumbered and bonds follow them; with `--models` the bonds are those
+/// of the first input. Chain IDs are kept, so inputs merged into one model
+/// must not share any. Writes to stdout by default.
+pub fn run(args: &[String]) -> Result<(), Error> {
+    let mut args  = Args::new(args, USAGE);
+    let selection = try!(args.selection());
+    let output    = try!(args.option(&["o", "output"])).unwrap_or("-".to_owned());
+    let as_models = args.flag("models");
+    let from      = try!(args.parsed::<Format>(&["from"]));
+    let to        = try!(args.parsed::<Format>(&["to"]));
+    let inputs    = try!(args.positional(1, ::std::usize::MAX));
+
+    let mut merged = Structure::new();
+    let mut single = Model::new(1);
+    let mut chains = BTreeSet::new();
+    for (i, path) in inputs.iter().enumerate() {
+        let mut structure = try!(io::read(path, from));
+        if let Some(ref selection) = selection {
+            structure = selection.select_structure(&structure);
+        }
+        if i == 0 {
+            merged.header = structure.header.clone();
+        }
+        if as_models {
+            structure.renumber();
+            if i == 0 {
+                merged.bonds = structure.bonds.clone();
+            }
+            for mut model in structure.models.into_iter() {
+                model.serial = merged.models.len() as i32 + 1;
+                merged.models.push(model);
+            }
+            continue;
+        }
+
+        if structure.models.len() != 1 {
+            return Err(Error::Failure(format!(
+                "{} has {} models; merge it with --models", path, structure.models.len())));
+        }
+        let mut model = structure.models.remove(0);
+        let ids: BTreeSet<char> = model.atoms.iter().map(|a| a.chain_id())
+            .chain(model.hetatms.iter().map(|a| a.chain_id())).collect();
+        if let Some(id) = ids.intersection(&chains).next() {
+            return Err(Error::Failure(format!(
+                "chain '{}' of {} is also in an earlier input", id, path)));
+        }
+        chains.extend(ids);
+        // serials unique across the inputs until all are renumbered
+        let mut serials = HashMap::new();
+        let mut serial = (single.atoms.len() + single.hetatms.len()) as i32 + 1;
+        for atom in model.atoms.iter_mut() {
+            serials.insert(atom.atom_number(), serial);
+            atom.set_atom_number(serial);
+            serial += 1;
+        }
+        for hetatm in model.hetatms.iter_mut() {
+            serials.insert(hetatm.atom_number(), serial);
+            hetatm.set_atom_number(serial);
+            serial += 1;
+        }
+        for &(l, r) in structure.bonds.renumbered(&serials).iter() {
+            merged.bonds.add(l, r);
+        }
+        single.atoms.extend(model.atoms.into_iter());
+        single.ters.extend(model.ters.into_iter());
+        single.hetatms.extend(model.hetatms.into_iter());
+    }
+    if !as_models {
+        let serials = single.renumber();
+        merged.bonds = merged.bonds.renumbered(&serials);
+        merged.models.push(single);
+    }
+    io::write(&output, to, &merged)
+}
@@ -0,0 +1,36 @@
+use std::io::{self, Write};
+use mill::nucleic::{HBOND_CUTOFF, base_pairs, stackings};
+use args::{Args, Error};
+use io::{read, Format};
+
+pub const USAGE: &'static str = "mill pairs <input> [--cutoff 3.5] [--from <format>] \
+[--select <selection>]
+
+Prints base pairs, `pair <purine> <pyrimidine> watson-crick|hoogsteen
+<hydrogen bond lengths>`, and stacked bases, `stack <base> <base> <distance>
+<angle between planes>`. Hydrogen bonds are shorter than --cutoff.";
+
+/// Prints base pairs and stacked bases in the first model.
+pub fn run(args: &[String]) -> Result<(), Error> {
+    let mut args  = Args::new(args, USAGE);
+    let selection = try!(args.selection());
+    let cutoff    = try!(args.parsed::<f64>(&["cutoff"])).unwrap_or(HBOND_CUTOFF);
+    let from      = try!(args.parsed::<Format>(&["from"]));
+    let args      = try!(args.positional(1, 1));
+
+    let mut atoms = try!(read(&args[0], from)).models.remove(0).atoms;
+    if let Some(selection) = selection {
+        atoms = selection.select(&atoms);
+    }
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for pair in base_pairs(&atoms, cutoff) {
+        let lengths: Vec<String> = pair.hbonds.iter().map(|d| format!("{:.2}", d)).collect();
+        try!(writeln!(out, "pair {} {} {} {}", pair.lhs, pair.rhs, pair.kind, lengths.join(" ")));
+    }
+    for stack in stackings(&atoms) {
+        try!(writeln!(out, "stack {} {} {:.2} {:.1}", stack.lhs, stack.rhs, stack.distance,
+                      stack.angle));
+    }
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use args::{Args, Error};
+use io::{self, Format};
+
+pub const USAGE: &'static str = "mill renumber <input> [-o <output>] \
+[--residues-from <number>] [--from <format>] [--to <format>] [--select <selection>]";
+
+/// Renumbers atoms from 1, and residues of each chain if `--residues-from`
+/// is given. Writes to stdout by default.
+pub fn run(args: &[String]) -> Result<(), Error> {
+    let mut args  = Args::new(args, USAGE);
+    let selection = try!(args.selection());
+    let output    = try!(args.option(&["o", "output"])).unwrap_or("-".to_owned());
+    let residues  = try!(args.parsed::<i32>(&["residues-from"]));
+    let from      = try!(args.parsed::<Format>(&["from"]));
+    let to        = try!(args.parsed::<Format>(&["to"]));
+    let args      = try!(args.positional(1, 1));
+
+    let mut structure = try!(io::read(&args[0], from));
+    if let Some(selection) = selection {
+        structure = selection.select_structure(&structure);
+    }
+    if let Some(first) = residues {
+        for model in structure.models.iter_mut() {
+            model.renumber_residues(first);
+        }
+    }
+    structure.renumber();
+    io::write(&output, to, &structure)
+}
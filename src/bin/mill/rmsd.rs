@@ -0,0 +1,47 @@
+use nalgebra::Vector3;
+use std::io::{self, Write};
+use std::vec::Vec;
+use mill::Particle;
+use mill::pdb::Model;
+use mill::superpose::{rmsd, superpose};
+use args::{Args, Error};
+use io::{read, Format};
+
+pub const USAGE: &'static str = "mill rmsd <reference> <trajectory> [--no-fit] \
+[--from <format>] [--select <selection>]";
+
+fn positions(model: &Model) -> Vec<Vector3<f64>> {
+    model.atoms.iter().map(|a| a.vec())
+         .chain(model.hetatms.iter().map(|a| a.vec())).collect()
+}
+
+/// Prints RMSD of each model in the trajectory from the first model of the
+/// reference. Atoms are paired in order. They are superposed unless
+/// `--no-fit` is given.
+pub fn run(args: &[String]) -> Result<(), Error> {
+    let mut args  = Args::new(args, USAGE);
+    let selection = try!(args.selection());
+    let no_fit    = args.flag("no-fit");
+    let from      = try!(args.parsed::<Format>(&["from"]));
+    let args      = try!(args.positional(2, 2));
+
+    let mut reference  = try!(read(&args[0], from));
+    let mut trajectory = try!(read(&args[1], from));
+    if let Some(selection) = selection {
+        reference  = selection.select_structure(&reference);
+        trajectory = selection.select_structure(&trajectory);
+    }
+    let target = positions(&reference.models[0]);
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for model in trajectory.models.iter() {
+        let mobile = positions(model);
+        let value = if no_fit {
+            try!(rmsd(&mobile, &target))
+        } else {
+            try!(superpose(&mobile, &target)).rmsd
+        };
+        try!(writeln!(out, "{} {:.4}", model.serial, value));
+    }
+    Ok(())
+}
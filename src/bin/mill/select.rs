@@ -0,0 +1,19 @@
+use mill::select::Selection;
+use args::{Args, Error};
+use io::{self, Format};
+
+pub const USAGE: &'static str = "mill select <input> <selection> [-o <output>] \
+[--from <format>] [--to <format>]";
+
+/// Writes the selected atoms, stdout by default.
+pub fn run(args: &[String]) -> Result<(), Error> {
+    let mut args = Args::new(args, USAGE);
+    let output   = try!(args.option(&["o", "output"])).unwrap_or("-".to_owned());
+    let from     = try!(args.parsed::<Format>(&["from"]));
+    let to       = try!(args.parsed::<Format>(&["to"]));
+    let args     = try!(args.positional(2, 2));
+
+    let selection = try!(args[1].parse::<Selection>().map_err(Error::Usage));
+    let structure = try!(io::read(&args[0], from));
+    io::write(&output, to, &selection.select_structure(&structure))
+}
@@ -0,0 +1,88 @@
+use std::collections::{BTreeSet, HashSet};
+use std::io::{self, Write};
+use std::path::Path;
+use std::string::String;
+use mill::pdb::{AtomData, Model, Structure};
+use args::{Args, Error};
+use io::{self as file, Format};
+
+pub const USAGE: &'static str = "mill split <input> [--by model|chain] \
+[--prefix <prefix>] [--from <format>] [--to <format>] [--select <selection>]";
+
+/// The atoms of a chain, with its TERs and bonds, in each model.
+fn chain_of(structure: &Structure, chain: char) -> Structure {
+    let mut selected = Structure{header: structure.header.clone(), ..Structure::new()};
+    for model in structure.models.iter() {
+        let mut dst = Model::new(model.serial);
+        dst.atoms   = model.atoms.iter().filter(|a| a.chain_id() == chain).cloned().collect();
+        dst.hetatms = model.hetatms.iter().filter(|a| a.chain_id() == chain).cloned().collect();
+        dst.ters    = model.ters.iter().filter(|t| t.chain_id() == chain).cloned().collect();
+        selected.models.push(dst);
+    }
+    let kept: HashSet<i32> = selected.models.iter().flat_map(|m| {
+        m.atoms.iter().map(|a| a.atom_number())
+         .chain(m.hetatms.iter().map(|a| a.atom_number()))
+    }).collect();
+    selected.bonds = structure.bonds.clone();
+    selected.bonds.retain(|serial| kept.contains(&serial));
+    selected
+}
+
+/// Writes each model, or each chain, into `<prefix>_<model serial>.<ext>`
+/// or `<prefix>_<chain ID>.<ext>` (`_` for a blank ID), and prints the file
+/// names. The prefix is the input file name without the extension by default.
+pub fn run(args: &[String]) -> Result<(), Error> {
+    let mut args  = Args::new(args, USAGE);
+    let selection = try!(args.selection());
+    let by        = try!(args.option(&["by"])).unwrap_or("model".to_owned());
+    let prefix    = try!(args.option(&["prefix"]));
+    let from      = try!(args.parsed::<Format>(&["from"]));
+    let to        = try!(args.parsed::<Format>(&["to"]));
+    let args      = try!(args.positional(1, 1));
+
+    let prefix = match prefix {
+        Some(p) => p,
+        None if args[0] == "-" => "split".to_owned(),
+        None => Path::new(&args[0]).with_extension("").to_string_lossy().into_owned(),
+    };
+    let to = match to {
+        Some(f) => f,
+        None if args[0] == "-" => Format::Pdb,
+        None => try!(Format::of_path(&args[0])),
+    };
+    let mut structure = try!(file::read(&args[0], from));
+    if let Some(selection) = selection {
+        structure = selection.select_structure(&structure);
+    }
+
+    let mut pieces: Vec<(String, Structure)> = Vec::new();
+    match by.as_str() {
+        "model" => for model in structure.models.iter() {
+            let mut piece = Structure{header: structure.header.clone(), ..Structure::new()};
+            piece.models.push(model.clone());
+            piece.bonds = structure.bonds.clone();
+            pieces.push((model.serial.to_string(), piece));
+        },
+        "chain" => {
+            let chains: BTreeSet<char> = structure.models.iter().flat_map(|m| {
+                m.atoms.iter().map(|a| a.chain_id())
+                 .chain(m.hetatms.iter().map(|a| a.chain_id()))
+            }).collect();
+            for chain in chains {
+                // a blank chain ID is not a good file name
+                let name = if chain == ' ' {"_".to_owned()} else {chain.to_string()};
+                pieces.push((name, chain_of(&structure, chain)));
+            }
+        }
+        _ => return Err(Error::Usage(format!("--by must be model or chain: {}", by))),
+    }
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for (name, piece) in pieces {
+        let path = format!("{}_{}.{}", prefix, name, to.extension());
+        try!(file::write(&path, Some(to), &piece));
+        try!(writeln!(out, "{}", path));
+    }
+    Ok(())
+}
@@ -0,0 +1,449 @@
+//! module cg: coarse-grained models, with one bead per residue (`Mapping`)
+//! or several beads defined in a mapping file (`Scheme`).
+use std::str::FromStr;
+use std::string::String;
+use std::vec::Vec;
+use nalgebra::Vector3;
+use super::Particle;
+use super::element;
+use super::geometry::center_of_mass;
+use super::select::BACKBONE;
+use super::pdb::{Atom, AtomBuilder, AtomData, Model, Structure, Ter};
+use super::pdb::{ResidueData, ResidueId, ResidueIterator, ResidueSlice};
+
+/// Hydrogens bonded to backbone atoms, in PDB and force-field names.
+const BACKBONE_HYDROGENS: &'static [&'static str] = &[
+    "H", "HN", "H1", "H2", "H3", "HT1", "HT2", "HT3", "HA", "HA2", "HA3", "1HA", "2HA",
+];
+
+/// Where the bead of a residue is placed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Position {
+    /// At the CA atom.
+    Alpha,
+    /// At the center of mass of the residue.
+    CenterOfMass,
+    /// At the center of mass of the side chain; CA for glycine.
+    SideChain,
+}
+
+impl FromStr for Position {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Position, String> {
+        match s {
+            "ca"        => Ok(Position::Alpha),
+            "com"       => Ok(Position::CenterOfMass),
+            "sidechain" => Ok(Position::SideChain),
+            _ => Err(format!("unknown bead position `{}`; use ca, com or sidechain", s)),
+        }
+    }
+}
+
+impl Position {
+    /// The default bead name: CA, CM and CB respectively.
+    pub fn default_name(&self) -> &'static str {
+        match *self {
+            Position::Alpha        => "CA",
+            Position::CenterOfMass => "CM",
+            Position::SideChain    => "CB",
+        }
+    }
+}
+
+/// The atom name of a bead: the first four characters of an ASCII name.
+fn bead_name(name: &str) -> Result<&str, String> {
+    if name.is_empty() || !name.is_ascii() {
+        return Err(format!("bead name `{}` is not of ASCII characters", name));
+    }
+    Ok(&name[..name.len().min(4)])
+}
+
+/// How residues are mapped to beads. A bead keeps the residue name, number
+/// and chain ID, and has the average B-factor of the atoms.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mapping {
+    pub position : Position,
+    /// The atom name of beads, up to four ASCII characters.
+    pub name     : String,
+}
+
+impl Mapping {
+    pub fn new(position: Position) -> Mapping {
+        Mapping{position: position, name: position.default_name().to_owned()}
+    }
+
+    /// The position of the bead of a residue.
+    pub fn bead<T: AtomData + Particle>(&self, residue: &ResidueSlice<T>)
+        -> Result<Vector3<f64>, String> {
+        let missing = |what: &str| format!("{} has no {}", residue.residue_id(), what);
+        let alpha = || residue.atoms.iter().find(|a| a.atom_name() == "CA")
+                                   .map(|a| a.vec()).ok_or(missing("CA"));
+        let atoms: Vec<&T> = match self.position {
+            Position::Alpha        => return alpha(),
+            Position::CenterOfMass => residue.atoms.iter().collect(),
+            Position::SideChain    => residue.atoms.iter().filter(|a| {
+                !BACKBONE.contains(&a.atom_name()) &&
+                !BACKBONE_HYDROGENS.contains(&a.atom_name())
+            }).collect(),
+        };
+        if atoms.is_empty() {
+            return alpha();
+        }
+        let masses: Vec<f64> = try!(atoms.iter().map(|a| {
+            element::of(*a).map(|e| e.mass).ok_or(
+                format!("unknown element of atom {} {}", a.atom_number(), a.atom_name()))
+        }).collect());
+        center_of_mass(atoms, masses).ok_or(missing("atom with mass"))
+    }
+
+    /// Beads of residues of ATOM records, numbered from `first`.
+    pub fn beads<T: AtomData + Particle>(&self, atoms: &[T], first: i32)
+        -> Result<Vec<Atom>, String> {
+        let name = try!(bead_name(&self.name));
+        ResidueIterator::from_slice(atoms).enumerate().map(|(i, residue)| {
+            let pos = try!(self.bead(&residue));
+            let bfactor = residue.atoms.iter().map(|a| a.temperature_factor())
+                                 .sum::<f64>() / residue.atoms.len() as f64;
+            Ok(AtomBuilder::new()
+                .atom_number(first + i as i32)
+                .atom_residue_chain(&name, residue.residue_name(), residue.chain_id())
+                .residue_number(residue.residue_number())
+                .insertion_code(residue.insertion_code())
+                .pos(pos[0], pos[1], pos[2])
+                .occupancy(1.0)
+                .temperature_factor(bfactor)
+                .finalize())
+        }).collect()
+    }
+
+    /// The coarse-grained model of ATOM records, chain by chain with TERs.
+    /// HETATMs are dropped.
+    pub fn model(&self, model: &Model) -> Result<Model, String> {
+        let mut cg = Model::new(model.serial);
+        if let Some(chains) = model.chains() {
+            for chain in chains {
+                let beads = try!(self.beads(chain.atoms, cg.atoms.len() as i32 + 1));
+                if let Some(last) = beads.last() {
+                    cg.ters.push(Ter::new(last));
+                }
+                cg.atoms.extend(beads);
+            }
+        }
+        Ok(cg)
+    }
+
+    /// Maps all the models. Bonds are not kept.
+    pub fn structure(&self, structure: &Structure) -> Result<Structure, String> {
+        let mut cg = Structure{header: structure.header.clone(), ..Structure::new()};
+        for model in structure.models.iter() {
+            cg.models.push(try!(self.model(model)));
+        }
+        Ok(cg)
+    }
+}
+
+/// An atom of a bead. The weight is the mass of the atom if not given.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Member {
+    pub name   : String,
+    pub weight : Option<f64>,
+}
+
+/// A bead at the weighted center of its atoms.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bead {
+    pub name  : String,
+    pub atoms : Vec<Member>,
+}
+
+/// Atoms of a bead that are not found in a residue.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MissingAtoms {
+    pub residue : ResidueId,
+    pub bead    : String,
+    pub atoms   : Vec<String>,
+}
+
+/// Problems found while applying a scheme. A bead with some atoms missing
+/// is placed at the center of the rest; a bead without any is not placed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Report {
+    pub missing  : Vec<MissingAtoms>,
+    /// Residues the scheme does not define; they are skipped.
+    pub unmapped : Vec<ResidueId>,
+}
+
+impl Report {
+    pub fn new() -> Report {
+        Report{missing: Vec::new(), unmapped: Vec::new()}
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.unmapped.is_empty()
+    }
+}
+
+/// Beads of each residue, read from a mapping file in a subset of TOML.
+/// A table is a residue and each key is a bead, in the order of beads:
+///
+/// ```toml
+/// weights = "mass"     # or "equal"; the default weight of atoms
+///
+/// [DA]
+/// P = ["P", "OP1", "OP2", "O5'"]
+/// S = ["C5'", "C4'", "O4'", "C3'", "C2'", "C1'"]
+/// B = ["N9", "C8", "N7", "C5", "C6", "N6", "N1", "C2", "N3", "C4"]
+/// ```
+///
+/// A weight is given after a colon, as in `"CA:2.0"`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scheme {
+    pub residues : Vec<(String, Vec<Bead>)>,
+}
+
+/// A 3SPN-style mapping of DNA: phosphate, sugar and base of each
+/// nucleotide at their centers of mass.
+pub const THREE_SITE_DNA: &'static str = r#"
+weights = "mass"
+
+[DA]
+P = ["P", "OP1", "OP2", "O5'"]
+S = ["C5'", "C4'", "O4'", "C3'", "O3'", "C2'", "C1'"]
+B = ["N9", "C8", "N7", "C5", "C6", "N6", "N1", "C2", "N3", "C4"]
+
+[DT]
+P = ["P", "OP1", "OP2", "O5'"]
+S = ["C5'", "C4'", "O4'", "C3'", "O3'", "C2'", "C1'"]
+B = ["N1", "C2", "O2", "N3", "C4", "O4", "C5", "C7", "C6"]
+
+[DG]
+P = ["P", "OP1", "OP2", "O5'"]
+S = ["C5'", "C4'", "O4'", "C3'", "O3'", "C2'", "C1'"]
+B = ["N9", "C8", "N7", "C5", "C6", "O6", "N1", "C2", "N2", "N3", "C4"]
+
+[DC]
+P = ["P", "OP1", "OP2", "O5'"]
+S = ["C5'", "C4'", "O4'", "C3'", "O3'", "C2'", "C1'"]
+B = ["N1", "C2", "O2", "N3", "C4", "N4", "C5", "C6"]
+"#;
+
+/// Splits a line at `#` outside quotes.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '#') => return &line[..i],
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Parses a string, either "basic" or 'literal'.
+fn parse_string(text: &str) -> Result<String, String> {
+    let text = text.trim();
+    let quote = try!(text.chars().next().ok_or("empty value".to_owned()));
+    if text.len() < 2 || (quote != '"' && quote != '\'') || !text.ends_with(quote) {
+        return Err(format!("not a string: {}", text));
+    }
+    Ok(text[1..text.len()-1].replace("\\\"", "\"").replace("\\\\", "\\"))
+}
+
+/// Splits an array at commas outside quotes.
+fn parse_array(text: &str) -> Result<Vec<String>, String> {
+    let text = text.trim();
+    if !text.starts_with('[') || !text.ends_with(']') {
+        return Err(format!("not an array: {}", text));
+    }
+    let inner = &text[1..text.len()-1];
+    let mut items = Vec::new();
+    let (mut quote, mut first) = (None, 0);
+    for (i, c) in inner.char_indices() {
+        match (quote, c) {
+            (None, ',') => {items.push(&inner[first..i]); first = i + 1;}
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            _ => {}
+        }
+    }
+    items.push(&inner[first..]);
+    items.into_iter().filter(|s| !s.trim().is_empty()).map(parse_string).collect()
+}
+
+fn parse_member(text: &str, weights: Option<f64>) -> Result<Member, String> {
+    let mut fields = text.splitn(2, ':');
+    let name = fields.next().unwrap_or("").trim();
+    if name.is_empty() {
+        return Err(format!("no atom name in `{}`", text));
+    }
+    let weight = match fields.next() {
+        Some(w) => Some(try!(w.trim().parse::<f64>().map_err(
+            |e| format!("{}: weight of `{}`", e, text)))),
+        None => weights,
+    };
+    Ok(Member{name: name.to_owned(), weight: weight})
+}
+
+impl FromStr for Scheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Scheme, String> {
+        let mut scheme  = Scheme{residues: Vec::new()};
+        // `None` weighs atoms by mass
+        let mut weights = None;
+        let mut pending = String::new();
+        for (number, line) in s.lines().enumerate() {
+            let at = |e: String| format!("line {}: {}", number + 1, e);
+            // an array may continue over lines
+            pending.push_str(strip_comment(line));
+            pending.push(' ');
+            if pending.matches('[').count() > pending.matches(']').count() {
+                continue;
+            }
+            let line = ::std::mem::replace(&mut pending, String::new());
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                let name = line[1..line.len()-1].trim();
+                let name = if name.starts_with('"') || name.starts_with('\'') {
+                    try!(parse_string(name).map_err(&at))
+                } else {name.to_owned()};
+                if scheme.residues.iter().any(|r| r.0 == name) {
+                    return Err(at(format!("residue {} is defined twice", name)));
+                }
+                scheme.residues.push((name, Vec::new()));
+                continue;
+            }
+            let eq = try!(line.find('=').ok_or(at(format!("expected `key = value`: {}", line))));
+            let key = line[..eq].trim();
+            let key = if key.starts_with('"') || key.starts_with('\'') {
+                try!(parse_string(key).map_err(&at))
+            } else {key.to_owned()};
+            let value = line[eq+1..].trim();
+            match scheme.residues.last_mut() {
+                None if key == "weights" => {
+                    weights = match try!(parse_string(value).map_err(&at)).as_str() {
+                        "mass"  => None,
+                        "equal" => Some(1.0),
+                        w => return Err(at(format!("unknown weights `{}`", w))),
+                    };
+                }
+                None => return Err(at(format!("unknown key `{}`", key))),
+                Some(&mut (ref residue, ref mut beads)) => {
+                    if key.len() > 4 || !key.is_ascii() {
+                        return Err(at(format!("bead name {} is not of 1 to 4 ASCII characters",
+                                              key)));
+                    }
+                    if beads.iter().any(|b: &Bead| b.name == key) {
+                        return Err(at(format!("bead {} of {} is defined twice", key, residue)));
+                    }
+                    let names = try!(parse_array(value).map_err(&at));
+                    if names.is_empty() {
+                        return Err(at(format!("bead {} of {} has no atom", key, residue)));
+                    }
+                    let atoms = try!(names.iter().map(|n| parse_member(n, weights))
+                                          .collect::<Result<Vec<_>, _>>().map_err(&at));
+                    beads.push(Bead{name: key, atoms: atoms});
+                }
+            }
+        }
+        if !pending.trim().is_empty() {
+            return Err("unclosed array at the end".to_owned());
+        }
+        Ok(scheme)
+    }
+}
+
+impl Scheme {
+    /// Beads of a residue, if the scheme defines it.
+    pub fn residue(&self, name: &str) -> Option<&[Bead]> {
+        self.residues.iter().find(|r| r.0 == name).map(|r| &r.1[..])
+    }
+
+    /// Beads of residues of ATOM records, numbered from `first`. Missing
+    /// atoms and residues are added to `report`.
+    pub fn beads<T: AtomData + Particle>(&self, atoms: &[T], first: i32, report: &mut Report)
+        -> Result<Vec<Atom>, String> {
+        let mut result = Vec::new();
+        for residue in ResidueIterator::from_slice(atoms) {
+            let beads = match self.residue(residue.residue_name()) {
+                Some(beads) => beads,
+                None => {report.unmapped.push(residue.residue_id()); continue;}
+            };
+            for bead in beads.iter() {
+                let mut missing = Vec::new();
+                let mut found   = Vec::new();
+                let mut weights = Vec::new();
+                for member in bead.atoms.iter() {
+                    let atom = match residue.atoms.iter().find(|a| a.atom_name() == member.name) {
+                        Some(a) => a,
+                        None => {missing.push(member.name.clone()); continue;}
+                    };
+                    let weight = match member.weight {
+                        Some(w) => w,
+                        None => try!(element::of(atom).map(|e| e.mass).ok_or(format!(
+                            "unknown element of atom {} {}", atom.atom_number(),
+                            atom.atom_name()))),
+                    };
+                    found.push(atom);
+                    weights.push(weight);
+                }
+                if !missing.is_empty() {
+                    report.missing.push(MissingAtoms{
+                        residue: residue.residue_id(), bead: bead.name.clone(), atoms: missing,
+                    });
+                }
+                let pos = match center_of_mass(found.iter().cloned(), weights.iter().cloned()) {
+                    Some(pos) => pos,
+                    None      => continue,
+                };
+                let bfactor = found.iter().map(|a| a.temperature_factor()).sum::<f64>()
+                              / found.len() as f64;
+                let name = try!(bead_name(&bead.name));
+                result.push(AtomBuilder::new()
+                    .atom_number(first + result.len() as i32)
+                    .atom_residue_chain(&name, residue.residue_name(), residue.chain_id())
+                    .residue_number(residue.residue_number())
+                    .insertion_code(residue.insertion_code())
+                    .pos(pos[0], pos[1], pos[2])
+                    .occupancy(1.0)
+                    .temperature_factor(bfactor)
+                    .finalize());
+            }
+        }
+        Ok(result)
+    }
+
+    /// The coarse-grained model of ATOM records, chain by chain with TERs.
+    /// HETATMs are dropped.
+    pub fn model(&self, model: &Model, report: &mut Report) -> Result<Model, String> {
+        let mut cg = Model::new(model.serial);
+        if let Some(chains) = model.chains() {
+            for chain in chains {
+                let beads = try!(self.beads(chain.atoms, cg.atoms.len() as i32 + 1, report));
+                if let Some(last) = beads.last() {
+                    cg.ters.push(Ter::new(last));
+                }
+                cg.atoms.extend(beads);
+            }
+        }
+        Ok(cg)
+    }
+
+    /// Maps all the models. The report is of the first model. Bonds are
+    /// not kept.
+    pub fn structure(&self, structure: &Structure) -> Result<(Structure, Report), String> {
+        let mut cg = Structure{header: structure.header.clone(), ..Structure::new()};
+        let mut report = Report::new();
+        for (i, model) in structure.models.iter().enumerate() {
+            let mut ignored = Report::new();
+            cg.models.push(try!(self.model(model, if i == 0 {&mut report} else {&mut ignored})));
+        }
+        Ok((cg, report))
+    }
+}
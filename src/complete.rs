@@ -0,0 +1,588 @@
+//! module complete: missing heavy atoms and hydrogens of amino acids.
+//!
+//! Missing side-chain atoms are placed by the templates of `backmap`, with
+//! the chi angles of the atoms present or those of the most common rotamer.
+//! Hydrogens are removed and built again with ideal geometry, or kept as
+//! they are if only heavy atoms are completed. Names follow PDB v3
+//! (`HB2`/`HB3` by the IUPAC rule), CHARMM or Amber. Titratable side chains
+//! are protonated by the residue name or, if a pH is given, by the model pKa
+//! values; N- and C-termini are charged. CHARMM names of protonated ASP, GLU
+//! and neutral LYS (ASPP, GLUP, LSN) do not fit in PDB files and are left as
+//! ASP, GLU and LYS.
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::string::String;
+use std::vec::Vec;
+use nalgebra::Vector3;
+use super::Particle;
+use super::backmap::{place, rotamers, side_chain_atoms, standard_residue};
+use super::dihedral::{dihedral, PEPTIDE_BOND_CUTOFF};
+use super::pdb::{Atom, AtomBuilder, AtomData, AtomDataMut, Model, Structure, Ter};
+use super::pdb::{ResidueData, ResidueId, ResidueIterator, ResidueKind, ResidueSlice, classify};
+
+/// Model pKa values of side chains; a side chain is protonated below them.
+pub const PKA: &'static [(&'static str, f64)] = &[
+    ("ASP", 3.9), ("GLU", 4.3), ("HIS", 6.0), ("LYS", 10.5),
+];
+
+/// SG atoms closer than this form a disulfide bond.
+pub const DISULFIDE_CUTOFF: f64 = 2.5;
+
+/// Atom names of hydrogens and residue names of protonation states.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Naming {
+    /// PDB v3, with HIS, ASP, GLU, LYS and CYS in any state.
+    Pdb,
+    /// CHARMM: HN, HT1-3, OT1/OT2, HB1/HB2, ILE CD, and HSD/HSE/HSP.
+    Charmm,
+    /// Amber: as PDB v3, with HID/HIE/HIP, ASH, GLH, LYN and CYX.
+    Amber,
+}
+
+impl FromStr for Naming {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Naming, String> {
+        match s {
+            "pdb"    => Ok(Naming::Pdb),
+            "charmm" => Ok(Naming::Charmm),
+            "amber"  => Ok(Naming::Amber),
+            _ => Err(format!("unknown naming `{}`; use pdb, charmm or amber", s)),
+        }
+    }
+}
+
+/// A hydrogen placed from three atoms by its bond length, bond angle and
+/// dihedral. The dihedral is the offset from that of the reference atom
+/// bonded to the third one, or absolute if there is no reference.
+type Hydrogen = (&'static str, [&'static str; 3], f64, f64, &'static str, f64);
+
+const CH: f64 = 1.09;
+const NH: f64 = 1.01;
+const OH: f64 = 0.96;
+const SH: f64 = 1.34;
+const SP3: f64 = 109.5;
+const SP2: f64 = 120.0;
+
+/// Hydrogens of the side chain, with HA, of an amino acid in a protonation
+/// state, as Amber names the states.
+fn hydrogens(state: &str) -> &'static [Hydrogen] {
+    const HA: Hydrogen = ("HA", ["N", "C", "CA"], CH, SP3, "CB", 120.0);
+    match state {
+        "GLY" => &[("HA2", ["N", "C", "CA"], CH, SP3, "", -120.0),
+                   ("HA3", ["N", "C", "CA"], CH, SP3, "", 120.0)],
+        "ALA" => &[HA, ("HB1", ["N", "CA", "CB"], CH, SP3, "", 180.0),
+                   ("HB2", ["N", "CA", "CB"], CH, SP3, "", 60.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "", -60.0)],
+        "SER" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "OG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "OG", -120.0),
+                   ("HG", ["CA", "CB", "OG"], OH, SP3, "", 180.0)],
+        "CYS" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "SG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "SG", -120.0),
+                   ("HG", ["CA", "CB", "SG"], SH, 96.0, "", 180.0)],
+        "CYX" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "SG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "SG", -120.0)],
+        "THR" => &[HA, ("HB", ["N", "CA", "CB"], CH, SP3, "OG1", 120.0),
+                   ("HG1", ["CA", "CB", "OG1"], OH, SP3, "", 180.0),
+                   ("HG21", ["CA", "CB", "CG2"], CH, SP3, "", 180.0),
+                   ("HG22", ["CA", "CB", "CG2"], CH, SP3, "", 60.0),
+                   ("HG23", ["CA", "CB", "CG2"], CH, SP3, "", -60.0)],
+        "VAL" => &[HA, ("HB", ["N", "CA", "CB"], CH, SP3, "CG1", -120.0),
+                   ("HG11", ["CA", "CB", "CG1"], CH, SP3, "", 180.0),
+                   ("HG12", ["CA", "CB", "CG1"], CH, SP3, "", 60.0),
+                   ("HG13", ["CA", "CB", "CG1"], CH, SP3, "", -60.0),
+                   ("HG21", ["CA", "CB", "CG2"], CH, SP3, "", 180.0),
+                   ("HG22", ["CA", "CB", "CG2"], CH, SP3, "", 60.0),
+                   ("HG23", ["CA", "CB", "CG2"], CH, SP3, "", -60.0)],
+        "ILE" => &[HA, ("HB", ["N", "CA", "CB"], CH, SP3, "CG1", 120.0),
+                   ("HG12", ["CA", "CB", "CG1"], CH, SP3, "CD1", 120.0),
+                   ("HG13", ["CA", "CB", "CG1"], CH, SP3, "CD1", -120.0),
+                   ("HG21", ["CA", "CB", "CG2"], CH, SP3, "", 180.0),
+                   ("HG22", ["CA", "CB", "CG2"], CH, SP3, "", 60.0),
+                   ("HG23", ["CA", "CB", "CG2"], CH, SP3, "", -60.0),
+                   ("HD11", ["CB", "CG1", "CD1"], CH, SP3, "", 180.0),
+                   ("HD12", ["CB", "CG1", "CD1"], CH, SP3, "", 60.0),
+                   ("HD13", ["CB", "CG1", "CD1"], CH, SP3, "", -60.0)],
+        "LEU" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "CG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "CG", -120.0),
+                   ("HG", ["CA", "CB", "CG"], CH, SP3, "CD1", 120.0),
+                   ("HD11", ["CB", "CG", "CD1"], CH, SP3, "", 180.0),
+                   ("HD12", ["CB", "CG", "CD1"], CH, SP3, "", 60.0),
+                   ("HD13", ["CB", "CG", "CD1"], CH, SP3, "", -60.0),
+                   ("HD21", ["CB", "CG", "CD2"], CH, SP3, "", 180.0),
+                   ("HD22", ["CB", "CG", "CD2"], CH, SP3, "", 60.0),
+                   ("HD23", ["CB", "CG", "CD2"], CH, SP3, "", -60.0)],
+        "MET" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "CG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "CG", -120.0),
+                   ("HG2", ["CA", "CB", "CG"], CH, SP3, "SD", 120.0),
+                   ("HG3", ["CA", "CB", "CG"], CH, SP3, "SD", -120.0),
+                   ("HE1", ["CG", "SD", "CE"], CH, SP3, "", 180.0),
+                   ("HE2", ["CG", "SD", "CE"], CH, SP3, "", 60.0),
+                   ("HE3", ["CG", "SD", "CE"], CH, SP3, "", -60.0)],
+        "PHE" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "CG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "CG", -120.0),
+                   ("HD1", ["CB", "CG", "CD1"], CH, SP2, "", 0.0),
+                   ("HD2", ["CB", "CG", "CD2"], CH, SP2, "", 0.0),
+                   ("HE1", ["CG", "CD1", "CE1"], CH, SP2, "", 180.0),
+                   ("HE2", ["CG", "CD2", "CE2"], CH, SP2, "", 180.0),
+                   ("HZ", ["CD1", "CE1", "CZ"], CH, SP2, "", 180.0)],
+        "TYR" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "CG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "CG", -120.0),
+                   ("HD1", ["CB", "CG", "CD1"], CH, SP2, "", 0.0),
+                   ("HD2", ["CB", "CG", "CD2"], CH, SP2, "", 0.0),
+                   ("HE1", ["CG", "CD1", "CE1"], CH, SP2, "", 180.0),
+                   ("HE2", ["CG", "CD2", "CE2"], CH, SP2, "", 180.0),
+                   ("HH", ["CE1", "CZ", "OH"], OH, SP3, "", 0.0)],
+        "TRP" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "CG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "CG", -120.0),
+                   ("HD1", ["CB", "CG", "CD1"], CH, SP2, "", 0.0),
+                   ("HE1", ["CG", "CD1", "NE1"], NH, 125.0, "", 180.0),
+                   ("HE3", ["CG", "CD2", "CE3"], CH, SP2, "", 0.0),
+                   ("HZ2", ["CD2", "CE2", "CZ2"], CH, SP2, "", 180.0),
+                   ("HZ3", ["CD2", "CE3", "CZ3"], CH, SP2, "", 180.0),
+                   ("HH2", ["CE2", "CZ2", "CH2"], CH, SP2, "", 180.0)],
+        "HID" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "CG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "CG", -120.0),
+                   ("HD1", ["CB", "CG", "ND1"], NH, 126.0, "", 0.0),
+                   ("HD2", ["CB", "CG", "CD2"], CH, 126.0, "", 0.0),
+                   ("HE1", ["CG", "ND1", "CE1"], CH, 126.0, "", 180.0)],
+        "HIE" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "CG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "CG", -120.0),
+                   ("HD2", ["CB", "CG", "CD2"], CH, 126.0, "", 0.0),
+                   ("HE1", ["CG", "ND1", "CE1"], CH, 126.0, "", 180.0),
+                   ("HE2", ["CG", "CD2", "NE2"], NH, 126.0, "", 180.0)],
+        "HIP" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "CG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "CG", -120.0),
+                   ("HD1", ["CB", "CG", "ND1"], NH, 126.0, "", 0.0),
+                   ("HD2", ["CB", "CG", "CD2"], CH, 126.0, "", 0.0),
+                   ("HE1", ["CG", "ND1", "CE1"], CH, 126.0, "", 180.0),
+                   ("HE2", ["CG", "CD2", "NE2"], NH, 126.0, "", 180.0)],
+        "ASP" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "CG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "CG", -120.0)],
+        "ASH" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "CG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "CG", -120.0),
+                   ("HD2", ["CB", "CG", "OD2"], OH, SP3, "", 180.0)],
+        "ASN" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "CG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "CG", -120.0),
+                   ("HD21", ["CB", "CG", "ND2"], NH, SP2, "", 180.0),
+                   ("HD22", ["CB", "CG", "ND2"], NH, SP2, "", 0.0)],
+        "GLU" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "CG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "CG", -120.0),
+                   ("HG2", ["CA", "CB", "CG"], CH, SP3, "CD", 120.0),
+                   ("HG3", ["CA", "CB", "CG"], CH, SP3, "CD", -120.0)],
+        "GLH" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "CG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "CG", -120.0),
+                   ("HG2", ["CA", "CB", "CG"], CH, SP3, "CD", 120.0),
+                   ("HG3", ["CA", "CB", "CG"], CH, SP3, "CD", -120.0),
+                   ("HE2", ["CG", "CD", "OE2"], OH, SP3, "", 180.0)],
+        "GLN" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "CG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "CG", -120.0),
+                   ("HG2", ["CA", "CB", "CG"], CH, SP3, "CD", 120.0),
+                   ("HG3", ["CA", "CB", "CG"], CH, SP3, "CD", -120.0),
+                   ("HE21", ["CG", "CD", "NE2"], NH, SP2, "", 180.0),
+                   ("HE22", ["CG", "CD", "NE2"], NH, SP2, "", 0.0)],
+        "LYS" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "CG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "CG", -120.0),
+                   ("HG2", ["CA", "CB", "CG"], CH, SP3, "CD", 120.0),
+                   ("HG3", ["CA", "CB", "CG"], CH, SP3, "CD", -120.0),
+                   ("HD2", ["CB", "CG", "CD"], CH, SP3, "CE", 120.0),
+                   ("HD3", ["CB", "CG", "CD"], CH, SP3, "CE", -120.0),
+                   ("HE2", ["CG", "CD", "CE"], CH, SP3, "NZ", 120.0),
+                   ("HE3", ["CG", "CD", "CE"], CH, SP3, "NZ", -120.0),
+                   ("HZ1", ["CD", "CE", "NZ"], NH, SP3, "", 180.0),
+                   ("HZ2", ["CD", "CE", "NZ"], NH, SP3, "", 60.0),
+                   ("HZ3", ["CD", "CE", "NZ"], NH, SP3, "", -60.0)],
+        "LYN" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "CG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "CG", -120.0),
+                   ("HG2", ["CA", "CB", "CG"], CH, SP3, "CD", 120.0),
+                   ("HG3", ["CA", "CB", "CG"], CH, SP3, "CD", -120.0),
+                   ("HD2", ["CB", "CG", "CD"], CH, SP3, "CE", 120.0),
+                   ("HD3", ["CB", "CG", "CD"], CH, SP3, "CE", -120.0),
+                   ("HE2", ["CG", "CD", "CE"], CH, SP3, "NZ", 120.0),
+                   ("HE3", ["CG", "CD", "CE"], CH, SP3, "NZ", -120.0),
+                   ("HZ2", ["CD", "CE", "NZ"], NH, SP3, "", 60.0),
+                   ("HZ3", ["CD", "CE", "NZ"], NH, SP3, "", -60.0)],
+        "ARG" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "CG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "CG", -120.0),
+                   ("HG2", ["CA", "CB", "CG"], CH, SP3, "CD", 120.0),
+                   ("HG3", ["CA", "CB", "CG"], CH, SP3, "CD", -120.0),
+                   ("HD2", ["CB", "CG", "CD"], CH, SP3, "NE", 120.0),
+                   ("HD3", ["CB", "CG", "CD"], CH, SP3, "NE", -120.0),
+                   ("HE", ["CG", "CD", "NE"], NH, 118.0, "CZ", 180.0),
+                   ("HH11", ["NE", "CZ", "NH1"], NH, SP2, "", 0.0),
+                   ("HH12", ["NE", "CZ", "NH1"], NH, SP2, "", 180.0),
+                   ("HH21", ["NE", "CZ", "NH2"], NH, SP2, "", 0.0),
+                   ("HH22", ["NE", "CZ", "NH2"], NH, SP2, "", 180.0)],
+        "PRO" => &[HA, ("HB2", ["N", "CA", "CB"], CH, SP3, "CG", 120.0),
+                   ("HB3", ["N", "CA", "CB"], CH, SP3, "CG", -120.0),
+                   ("HG2", ["CA", "CB", "CG"], CH, SP3, "CD", 120.0),
+                   ("HG3", ["CA", "CB", "CG"], CH, SP3, "CD", -120.0),
+                   ("HD2", ["CB", "CG", "CD"], CH, SP3, "N", 120.0),
+                   ("HD3", ["CB", "CG", "CD"], CH, SP3, "N", -120.0)],
+        _ => &[],
+    }
+}
+
+/// The protonation state of an amino acid as Amber names it. Without a pH,
+/// force-field names such as HID or ASH are kept, and HIS is HIE.
+pub fn protonation_state(residue: &str, ph: Option<f64>) -> &str {
+    let name = standard_residue(residue);
+    let tautomer = match residue {
+        "HID" | "HSD" => "HID",
+        "HIP" | "HSP" => "HIP",
+        _ => "HIE",
+    };
+    let ph = match ph {
+        Some(ph) => ph,
+        None => return match name {
+            "HIS" => tautomer,
+            _ => match residue {"ASH" | "GLH" | "LYN" | "CYX" => residue, _ => name},
+        },
+    };
+    let protonated = PKA.iter().find(|p| p.0 == name).map_or(false, |p| ph < p.1);
+    match (name, protonated) {
+        ("HIS", true)  => "HIP",
+        ("HIS", false) => if tautomer == "HIP" {"HIE"} else {tautomer},
+        ("ASP", true)  => "ASH",
+        ("GLU", true)  => "GLH",
+        ("LYS", false) => "LYN",
+        _ => if residue == "CYX" {"CYX"} else {name},
+    }
+}
+
+/// Heavy atoms added to a residue.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Added {
+    pub residue : ResidueId,
+    pub atoms   : Vec<String>,
+}
+
+/// What was done, or could not be done, on the residues.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Report {
+    pub added      : Vec<Added>,
+    /// Amino acids without N, CA or C; they are left as they are.
+    pub incomplete : Vec<ResidueId>,
+    /// Amino acids without templates; they are left as they are.
+    pub unknown    : Vec<ResidueId>,
+}
+
+impl Report {
+    pub fn new() -> Report {
+        Report{added: Vec::new(), incomplete: Vec::new(), unknown: Vec::new()}
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.incomplete.is_empty() && self.unknown.is_empty()
+    }
+}
+
+/// How residues are completed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Completion {
+    /// Whether hydrogens are built again; if not, those of the input are
+    /// kept with their names.
+    pub hydrogens : bool,
+    pub naming    : Naming,
+    pub ph        : Option<f64>,
+}
+
+fn is_hydrogen(name: &str) -> bool {
+    name.trim_start_matches(|c: char| c.is_digit(10)).starts_with('H')
+}
+
+/// Heavy atom names of other force fields as those of PDB v3.
+fn pdb_name<'a>(residue: &str, atom: &'a str) -> &'a str {
+    match (residue, atom) {
+        ("ILE", "CD") => "CD1",
+        (_, "OT1")    => "O",
+        (_, "OT2")    => "OXT",
+        _ => atom,
+    }
+}
+
+/// Renames an atom of PDB v3; `names` are those of the residue.
+fn rename(naming: Naming, residue: &str, atom: &str, names: &[&str]) -> String {
+    if naming != Naming::Charmm {
+        return atom.to_owned();
+    }
+    match (residue, atom) {
+        (_, "H") => return "HN".to_owned(),
+        ("PRO", "H2") => return "HN1".to_owned(),
+        ("PRO", "H3") => return "HN2".to_owned(),
+        (_, "H1") | (_, "H2") | (_, "H3") if names.contains(&"H1") => {
+            return format!("HT{}", &atom[1..]);
+        }
+        (_, "O") if names.contains(&"OXT") => return "OT1".to_owned(),
+        (_, "OXT") => return "OT2".to_owned(),
+        ("ILE", "CD1") => return "CD".to_owned(),
+        ("ILE", "HD11") | ("ILE", "HD12") | ("ILE", "HD13") => {
+            return format!("HD{}", &atom[3..]);
+        }
+        ("SER", "HG") | ("CYS", "HG") => return "HG1".to_owned(),
+        _ => {}
+    }
+    // methylene hydrogens 2 and 3 are 1 and 2
+    let (stem, last) = atom.split_at(atom.len() - 1);
+    let pair = |i: char| names.contains(&format!("{}{}", stem, i).as_str());
+    if atom.starts_with('H') && pair('2') && pair('3') && !pair('1') {
+        match last {
+            "2" => return format!("{}1", stem),
+            "3" => return format!("{}2", stem),
+            _ => {}
+        }
+    }
+    atom.to_owned()
+}
+
+impl Completion {
+    /// Completes heavy atoms and hydrogens with PDB v3 names by default.
+    pub fn new() -> Completion {
+        Completion{hydrogens: true, naming: Naming::Pdb, ph: None}
+    }
+
+    fn residue_name(&self, state: &str) -> String {
+        match self.naming {
+            Naming::Pdb    => standard_residue(state).to_owned(),
+            Naming::Amber  => state.to_owned(),
+            Naming::Charmm => match state {
+                "HID" => "HSD", "HIE" => "HSE", "HIP" => "HSP",
+                _ => standard_residue(state),
+            }.to_owned(),
+        }
+    }
+
+    /// Completes a residue; `prev` is C of the previous residue and `next`
+    /// N of the next one if they are bonded.
+    fn residue(&self, residue: &ResidueSlice<Atom>, prev: Option<Vector3<f64>>,
+               next: Option<Vector3<f64>>, disulfide: bool, report: &mut Report)
+        -> Vec<Atom> {
+        let id = residue.residue_id();
+        let original = residue.residue_name();
+        let keep = || residue.atoms.to_vec();
+        if classify(original) != ResidueKind::AminoAcid {
+            return keep();
+        }
+        let name = standard_residue(original);
+        let template = match side_chain_atoms(name) {
+            Some(t) => t,
+            None => {report.unknown.push(id); return keep();}
+        };
+
+        // heavy atoms with PDB v3 names
+        let heavy: Vec<Atom> = residue.atoms.iter().filter(|a| !is_hydrogen(a.atom_name()))
+            .map(|a| {
+                let mut a = a.clone();
+                let renamed = pdb_name(name, a.atom_name()).to_owned();
+                a.set_atom_name(&renamed);
+                a
+            }).collect();
+        let mut placed: Vec<(String, Vector3<f64>)> = Vec::new();
+        for atom in heavy.iter() {
+            if !placed.iter().any(|p| p.0 == atom.atom_name()) {
+                placed.push((atom.atom_name().to_owned(), atom.vec()));
+            }
+        }
+        let find = |placed: &[(String, Vector3<f64>)], name: &str| {
+            placed.iter().find(|p| p.0 == name).map(|p| p.1)
+        };
+        let (n, ca, c) = match (find(&placed, "N"), find(&placed, "CA"), find(&placed, "C")) {
+            (Some(n), Some(ca), Some(c)) => (n, ca, c),
+            _ => {report.incomplete.push(id); return keep();}
+        };
+
+        let mut added = Vec::new();
+        if find(&placed, "O").is_none() {
+            let o = match next {
+                Some(next) => place(&next, &ca, &c, 1.231, 120.5, 180.0),
+                None => place(&n, &ca, &c, 1.231, 120.5, 180.0),
+            };
+            placed.push(("O".to_owned(), o));
+            added.push("O".to_owned());
+        }
+        // chi angles of the atoms present, or of the most common rotamer
+        let default = rotamers(name)[0];
+        let mut chi: Vec<f64> = default.to_vec();
+        for &(atom, parents, _, _, torsion, k) in template.iter() {
+            if k == 0 || torsion != 0.0 {
+                continue;
+            }
+            let p: Vec<Option<Vector3<f64>>> = parents.iter().map(|p| find(&placed, p)).collect();
+            if let (Some(a), Some(b), Some(c), Some(d)) = (p[0], p[1], p[2], find(&placed, atom)) {
+                chi[k - 1] = dihedral(&a, &b, &c, &d);
+            }
+        }
+        for &(atom, parents, bond, angle, torsion, k) in template.iter() {
+            if find(&placed, atom).is_some() {
+                continue;
+            }
+            let torsion = if k == 0 {torsion} else {chi[k - 1] + torsion};
+            let p: Vec<Vector3<f64>> = parents.iter().map(|p| {
+                find(&placed, p).expect("parents are placed before")
+            }).collect();
+            placed.push((atom.to_owned(), place(&p[0], &p[1], &p[2], bond, angle, torsion)));
+            added.push(atom.to_owned());
+        }
+        if next.is_none() && find(&placed, "OXT").is_none() {
+            let o = find(&placed, "O").unwrap();
+            placed.push(("OXT".to_owned(), place(&o, &ca, &c, 1.25, 117.0, 180.0)));
+            added.push("OXT".to_owned());
+        }
+        if !added.is_empty() {
+            report.added.push(Added{residue: id, atoms: added.clone()});
+        }
+
+        // hydrogens
+        let state = if disulfide {"CYX"} else {protonation_state(original, self.ph)};
+        let mut hs: Vec<(String, Vector3<f64>)> = Vec::new();
+        if self.hydrogens {
+            if name != "PRO" {
+                match prev {
+                    Some(prev) => {
+                        let bisector = ((n - prev).normalize() + (n - ca).normalize()).normalize();
+                        hs.push(("H".to_owned(), n + bisector * NH));
+                    }
+                    None => for (i, t) in [180.0, 60.0, -60.0].iter().enumerate() {
+                        hs.push((format!("H{}", i + 1), place(&c, &ca, &n, NH, SP3, *t)));
+                    },
+                }
+            } else if prev.is_none() {
+                let cd = find(&placed, "CD").unwrap();
+                let reference = dihedral(&c, &ca, &n, &cd);
+                for (i, t) in [120.0, -120.0].iter().enumerate() {
+                    hs.push((format!("H{}", i + 2),
+                             place(&c, &ca, &n, NH, SP3, reference + t)));
+                }
+            }
+            for &(atom, parents, bond, angle, reference, offset) in hydrogens(state).iter() {
+                let p: Vec<Vector3<f64>> = parents.iter().map(|p| {
+                    find(&placed, p).expect("heavy atoms are completed")
+                }).collect();
+                let torsion = if reference.is_empty() {offset} else {
+                    dihedral(&p[0], &p[1], &p[2], &find(&placed, reference).unwrap()) + offset
+                };
+                hs.push((atom.to_owned(), place(&p[0], &p[1], &p[2], bond, angle, torsion)));
+            }
+        }
+
+        // template order, then the other heavy atoms, then hydrogens
+        let mut order: Vec<&str> = vec!["N", "CA", "C", "O"];
+        order.extend(template.iter().map(|t| t.0));
+        order.push("OXT");
+        let first = &heavy[0];
+        // built atoms have no serial number, unlike those of the input
+        let build = |atom: &str, p: &Vector3<f64>| {
+            AtomBuilder::new()
+                .atom_number(0)
+                .atom_residue_chain(atom, original, first.chain_id())
+                .residue_number(first.residue_number())
+                .insertion_code(first.insertion_code())
+                .pos(p[0], p[1], p[2])
+                .occupancy(1.0)
+                .temperature_factor(first.temperature_factor())
+                .element(&atom[..1])
+                .finalize()
+        };
+        let mut atoms = Vec::new();
+        for atom in order.iter() {
+            let present: Vec<&Atom> = heavy.iter().filter(|a| a.atom_name() == *atom).collect();
+            if !present.is_empty() {
+                atoms.extend(present.into_iter().cloned());
+            } else if let Some(p) = find(&placed, atom) {
+                atoms.push(build(atom, &p));
+            }
+        }
+        atoms.extend(heavy.iter().filter(|a| !order.contains(&a.atom_name())).cloned());
+        atoms.extend(hs.iter().map(|h| build(&h.0, &h.1)));
+
+        let names: Vec<String> = atoms.iter().map(|a| a.atom_name().to_owned()).collect();
+        let names: Vec<&str> = names.iter().map(|s| s.as_str()).collect();
+        let residue_name = self.residue_name(state);
+        for atom in atoms.iter_mut() {
+            let renamed = rename(self.naming, name, atom.atom_name(), &names);
+            atom.set_atom_name(&renamed);
+            atom.set_residue_name(&residue_name);
+        }
+        if !self.hydrogens {
+            atoms.extend(residue.atoms.iter().filter(|a| is_hydrogen(a.atom_name())).map(|a| {
+                let mut a = a.clone();
+                a.set_residue_name(&residue_name);
+                a
+            }));
+        }
+        atoms
+    }
+
+    /// Completes the ATOM records chain by chain; TERs are put after each
+    /// chain and atoms are numbered again, HETATMs after ATOMs.
+    pub fn model(&self, model: &Model, report: &mut Report) -> Model {
+        self.complete(model, report).0
+    }
+
+    /// Completes a model and returns the old and new serial numbers of the
+    /// atoms kept from it.
+    fn complete(&self, model: &Model, report: &mut Report) -> (Model, HashMap<i32, i32>) {
+        let mut completed = Model::new(model.serial);
+        let mut serials = HashMap::new();
+        let sulfurs: Vec<(ResidueId, Vector3<f64>)> = model.atoms.iter()
+            .filter(|a| a.atom_name() == "SG").map(|a| (ResidueId::of(a), a.vec())).collect();
+        if let Some(chains) = model.chains() {
+            for chain in chains {
+                let residues: Vec<ResidueSlice<Atom>> =
+                    ResidueIterator::from_slice(chain.atoms).collect();
+                let atom = |i: usize, name: &str| residues[i].atoms.iter()
+                    .find(|a| a.atom_name() == name).map(|a| a.vec());
+                let bonded = |i: usize| match (atom(i, "C"), atom(i + 1, "N")) {
+                    (Some(c), Some(n)) => (c - n).norm() < PEPTIDE_BOND_CUTOFF,
+                    _ => false,
+                };
+                for (i, residue) in residues.iter().enumerate() {
+                    let prev = if i > 0 && bonded(i - 1) {atom(i - 1, "C")} else {None};
+                    let next = if i + 1 < residues.len() && bonded(i) {
+                        atom(i + 1, "N")
+                    } else {None};
+                    let id = residue.residue_id();
+                    let disulfide = atom(i, "SG").map_or(false, |sg| sulfurs.iter().any(|s| {
+                        s.0 != id && (s.1 - sg).norm() < DISULFIDE_CUTOFF
+                    }));
+                    for mut atom in self.residue(residue, prev, next, disulfide, report) {
+                        let serial = completed.atoms.len() as i32 + 1;
+                        if atom.atom_number() != 0 {
+                            serials.entry(atom.atom_number()).or_insert(serial);
+                        }
+                        atom.set_atom_number(serial);
+                        completed.atoms.push(atom);
+                    }
+                }
+                if let Some(last) = completed.atoms.last() {
+                    completed.ters.push(Ter::new(last));
+                }
+            }
+        }
+        for hetatm in model.hetatms.iter() {
+            let mut hetatm = hetatm.clone();
+            let serial = (completed.atoms.len() + completed.hetatms.len()) as i32 + 1;
+            serials.insert(hetatm.atom_number(), serial);
+            hetatm.set_atom_number(serial);
+            completed.hetatms.push(hetatm);
+        }
+        (completed, serials)
+    }
+
+    /// Completes all the models; the report is of the first one. Bonds
+    /// follow the serial numbers of the first model.
+    pub fn structure(&self, structure: &Structure) -> (Structure, Report) {
+        let mut completed = Structure{header: structure.header.clone(), ..Structure::new()};
+        let mut report = Report::new();
+        for (i, model) in structure.models.iter().enumerate() {
+            let mut r = Report::new();
+            let (model, serials) = self.complete(model, &mut r);
+            completed.models.push(model);
+            if i == 0 {
+                report = r;
+                completed.bonds = structure.bonds.renumbered(&serials);
+            }
+        }
+        (completed, report)
+    }
+}
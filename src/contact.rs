@@ -0,0 +1,242 @@
+//! module contact: residue contact maps and the fraction of native contacts.
+use nalgebra::Vector3;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::string::String;
+use std::vec::Vec;
+use super::Particle;
+use super::element;
+use super::neighbor::CellList;
+use super::pdb::{AtomData, ResidueData, ResidueId, ResidueIterator};
+
+/// Residues in the same chain whose residue numbers differ by less than
+/// this are never in contact; i.e. (i, i+1), (i, i+2) and (i, i+3).
+pub const MIN_SEPARATION: usize = 4;
+
+/// How a residue pair is judged to be in contact.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Definition {
+    /// CA atoms closer than the cutoff.
+    Alpha(f64),
+    /// Any pair of heavy atoms closer than the cutoff.
+    HeavyAtom(f64),
+    /// A pair of heavy atoms closer than the cutoff (first) that is not
+    /// hidden by another atom, regarded as a sphere of the shadowing radius
+    /// (second), when one atom is seen from the other.
+    Shadow(f64, f64),
+}
+
+impl Default for Definition {
+    fn default() -> Definition {Definition::Alpha(8.0)}
+}
+
+impl FromStr for Definition {
+    type Err = String;
+
+    /// Parses `ca[:cutoff]`, `heavy[:cutoff]` or `shadow[:cutoff[:radius]]`.
+    /// The default cutoffs are 8.0, 4.5 and 6.0 angstroms, and the default
+    /// shadowing radius is 1.0 angstrom.
+    fn from_str(s: &str) -> Result<Definition, String> {
+        let mut fields = s.split(':');
+        let kind = fields.next().unwrap_or("");
+        let mut values = Vec::new();
+        for field in fields {
+            values.push(try!(field.parse::<f64>().map_err(|_| {
+                format!("invalid number in contact definition: {}", field)
+            })));
+        }
+        let value = |i: usize, default: f64| *values.get(i).unwrap_or(&default);
+        match kind {
+            "ca" | "CA" if values.len() <= 1 => Ok(Definition::Alpha(value(0, 8.0))),
+            "heavy"     if values.len() <= 1 => Ok(Definition::HeavyAtom(value(0, 4.5))),
+            "shadow"    if values.len() <= 2 => {
+                Ok(Definition::Shadow(value(0, 6.0), value(1, 1.0)))
+            }
+            _ => Err(format!("unknown contact definition: {}", s)),
+        }
+    }
+}
+
+/// A pair of residues in contact.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Contact {
+    pub lhs      : ResidueId,
+    pub rhs      : ResidueId,
+    /// CA-CA distance for `Alpha`, and the shortest heavy atom distance
+    /// otherwise.
+    pub distance : f64,
+}
+
+struct Residues {
+    ids      : Vec<ResidueId>,
+    /// positions of the atoms used in the definition and their residues
+    atoms    : Vec<Vector3<f64>>,
+    owner    : Vec<usize>,
+}
+
+fn is_heavy<T: AtomData>(atom: &T) -> bool {
+    element::of(atom).map_or(true, |e| e.atomic_number != 1)
+}
+
+fn residues<T: AtomData + Particle>(atoms: &[T], definition: Definition) -> Residues {
+    let mut res = Residues{
+        ids: Vec::new(), atoms: Vec::new(), owner: Vec::new()
+    };
+    for residue in ResidueIterator::from_slice(atoms) {
+        let id = residue.residue_id();
+        for atom in residue.atoms.iter() {
+            let used = match definition {
+                Definition::Alpha(_) => atom.atom_name() == "CA",
+                _ => is_heavy(atom),
+            };
+            if used {
+                res.atoms.push(atom.vec());
+                res.owner.push(res.ids.len());
+            }
+        }
+        res.ids.push(id);
+    }
+    res
+}
+
+impl Residues {
+    fn separated(&self, i: usize, j: usize) -> bool {
+        let (lhs, rhs) = (&self.ids[i], &self.ids[j]);
+        lhs.chain_id() != rhs.chain_id() ||
+            (lhs.residue_number() - rhs.residue_number()).abs() as usize >= MIN_SEPARATION
+    }
+}
+
+/// Whether atom `j` is hidden from atom `i` by the sphere at any other atom.
+fn shadowed(atoms: &[Vector3<f64>], cells: &CellList, i: usize, j: usize, radius: f64)
+    -> bool {
+    let (pi, pj) = (atoms[i], atoms[j]);
+    let dij = (pj - pi).norm();
+    for k in cells.within(&pi, dij) {
+        if k == i || k == j {
+            continue;
+        }
+        let dik = (atoms[k] - pi).norm();
+        // atoms bonded to i or j do not cast a shadow
+        if dik < 2.0 || (atoms[k] - pj).norm() < 2.0 || dik <= radius {
+            continue;
+        }
+        let cos = (atoms[k] - pi).dot(&(pj - pi)) / (dik * dij);
+        let half_angle = (radius / dik).asin();
+        if cos > half_angle.cos() {
+            return true;
+        }
+    }
+    false
+}
+
+/// Residue pairs in contact, sorted by residue order. Residues in the same
+/// chain closer than `MIN_SEPARATION` are ignored.
+pub fn contacts<T: AtomData + Particle>(atoms: &[T], definition: Definition)
+    -> Vec<Contact> {
+    let res = residues(atoms, definition);
+    let (cutoff, shadow) = match definition {
+        Definition::Alpha(c) | Definition::HeavyAtom(c) => (c, None),
+        Definition::Shadow(c, r) => (c, Some(r)),
+    };
+    let cells = CellList::new(&res.atoms, cutoff);
+
+    let mut closest: HashMap<(usize, usize), f64> = HashMap::new();
+    for (i, j, d) in cells.pairs(cutoff) {
+        let (ri, rj) = (res.owner[i], res.owner[j]);
+        let key = if ri < rj {(ri, rj)} else {(rj, ri)};
+        if ri == rj || !res.separated(ri, rj) {
+            continue;
+        }
+        if closest.get(&key).map_or(false, |c| *c <= d) {
+            continue;
+        }
+        if let Some(radius) = shadow {
+            if shadowed(&res.atoms, &cells, i, j, radius) ||
+               shadowed(&res.atoms, &cells, j, i, radius) {
+                continue;
+            }
+        }
+        closest.insert(key, d);
+    }
+    let mut pairs: Vec<((usize, usize), f64)> = closest.into_iter().collect();
+    pairs.sort_by(|a, b| a.0.cmp(&b.0));
+    pairs.into_iter().map(|((i, j), d)| {
+        Contact{lhs: res.ids[i], rhs: res.ids[j], distance: d}
+    }).collect()
+}
+
+/// Native contacts taken from a reference structure.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NativeContacts {
+    pub definition : Definition,
+    pub contacts   : Vec<Contact>,
+}
+
+impl NativeContacts {
+    pub fn new<T: AtomData + Particle>(reference: &[T], definition: Definition)
+        -> NativeContacts {
+        NativeContacts{definition: definition, contacts: contacts(reference, definition)}
+    }
+
+    pub fn len(&self)      -> usize {self.contacts.len()}
+    pub fn is_empty(&self) -> bool  {self.contacts.is_empty()}
+
+    /// Distances of the native contacts in a frame, in the same order as
+    /// `contacts`. The shadow criterion is not applied here; the shortest
+    /// heavy atom distance is used. A residue missing in the frame gives
+    /// an error.
+    pub fn distances<T: AtomData + Particle>(&self, frame: &[T]) -> Result<Vec<f64>, String> {
+        let res = residues(frame, self.definition);
+        let index: HashMap<ResidueId, usize> =
+            res.ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        let mut members: Vec<Vec<usize>> = vec![Vec::new(); res.ids.len()];
+        for (a, r) in res.owner.iter().enumerate() {
+            members[*r].push(a);
+        }
+        let mut distances = Vec::with_capacity(self.contacts.len());
+        for contact in self.contacts.iter() {
+            let find = |id: &ResidueId| match index.get(id) {
+                Some(i) if !members[*i].is_empty() => Ok(*i),
+                _ => Err(format!("residue {} is not found in the frame", id)),
+            };
+            let (i, j) = (try!(find(&contact.lhs)), try!(find(&contact.rhs)));
+            let mut d = ::std::f64::INFINITY;
+            for a in members[i].iter() {
+                for b in members[j].iter() {
+                    d = d.min((res.atoms[*a] - res.atoms[*b]).norm());
+                }
+            }
+            distances.push(d);
+        }
+        Ok(distances)
+    }
+
+    /// The fraction of native contacts formed in a frame. A contact is
+    /// formed if its distance is shorter than `tolerance` times the native
+    /// distance.
+    pub fn q<T: AtomData + Particle>(&self, frame: &[T], tolerance: f64) -> Result<f64, String> {
+        if self.contacts.is_empty() {
+            return Err("no native contact".to_owned());
+        }
+        let distances = try!(self.distances(frame));
+        let formed = distances.iter().zip(self.contacts.iter())
+            .filter(|&(d, c)| *d < tolerance * c.distance).count();
+        Ok(formed as f64 / self.contacts.len() as f64)
+    }
+
+    /// The fraction of native contacts with a smooth switching function
+    /// `1 / (1 + exp(beta * (r - lambda * r0)))` (Best, Hummer and Eaton,
+    /// 2013); typically beta = 5 /angstrom and lambda = 1.8 for heavy atoms.
+    pub fn q_smooth<T: AtomData + Particle>(&self, frame: &[T], beta: f64, lambda: f64)
+        -> Result<f64, String> {
+        if self.contacts.is_empty() {
+            return Err("no native contact".to_owned());
+        }
+        let distances = try!(self.distances(frame));
+        let sum = distances.iter().zip(self.contacts.iter()).fold(0.0, |acc, (d, c)| {
+            acc + 1.0 / (1.0 + (beta * (d - lambda * c.distance)).exp())
+        });
+        Ok(sum / self.contacts.len() as f64)
+    }
+}
@@ -0,0 +1,267 @@
+//! module dcd: CHARMM/NAMD binary trajectories (.dcd).
+//!
+//! A DCD file has coordinates in angstroms and, optionally, a unit cell
+//! for each frame, but no atom names. Put a topology on the frames with
+//! `Structure::with_topology`. Both byte orders are read; files are written
+//! in little endian. Files with fixed atoms are not supported.
+use std::io::{self, BufWriter, Read, Write};
+use std::string::String;
+use std::vec::Vec;
+use nalgebra::Vector3;
+use super::pdb::{AtomBuilder, Model, Structure, UnitCell};
+
+/// Coordinates and the unit cell of a frame.
+#[derive(Clone, Debug)]
+pub struct Frame {
+    pub positions : Vec<Vector3<f64>>,
+    pub unit_cell : Option<UnitCell>,
+}
+
+/// Fortran unformatted records in either byte order.
+struct Records {
+    bytes  : Vec<u8>,
+    offset : usize,
+    big    : bool,
+}
+
+impl Records {
+    fn word(&self, at: usize) -> [u8; 4] {
+        let mut w = [self.bytes[at], self.bytes[at+1], self.bytes[at+2], self.bytes[at+3]];
+        if self.big {
+            w.reverse();
+        }
+        w
+    }
+
+    fn i32_at(&self, at: usize) -> i32 {
+        let w = self.word(at);
+        (w[0] as u32 | (w[1] as u32) << 8 | (w[2] as u32) << 16 | (w[3] as u32) << 24) as i32
+    }
+
+    fn f32_at(&self, at: usize) -> f32 {
+        f32::from_bits(self.i32_at(at) as u32)
+    }
+
+    fn f64_at(&self, at: usize) -> f64 {
+        let first  = self.i32_at(at) as u32 as u64;
+        let second = self.i32_at(at + 4) as u32 as u64;
+        if self.big {
+            f64::from_bits(first << 32 | second)
+        } else {
+            f64::from_bits(second << 32 | first)
+        }
+    }
+
+    fn is_end(&self) -> bool {
+        self.offset >= self.bytes.len()
+    }
+
+    /// The range of the next record, checking the markers on both sides.
+    fn next(&mut self) -> Result<(usize, usize), String> {
+        if self.offset + 4 > self.bytes.len() {
+            return Err("unexpected end of file".to_owned());
+        }
+        let size  = self.i32_at(self.offset);
+        let begin = self.offset + 4;
+        let end   = begin + size.max(0) as usize;
+        if size < 0 || end + 4 > self.bytes.len() {
+            return Err("unexpected end of file".to_owned());
+        }
+        if self.i32_at(end) != size {
+            return Err(format!("broken record at byte {}", self.offset));
+        }
+        self.offset = end + 4;
+        Ok((begin, end))
+    }
+}
+
+pub struct Reader<R> {
+    reader : R,
+}
+
+impl<R: Read> Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Reader{reader: inner}
+    }
+
+    /// Reads all the frames. An incomplete frame at the end is an error.
+    pub fn read_frames(&mut self) -> Result<Vec<Frame>, String> {
+        let mut bytes = Vec::new();
+        try!(self.reader.read_to_end(&mut bytes).map_err(|e| e.to_string()));
+        if bytes.len() < 8 {
+            return Err("not a DCD file".to_owned());
+        }
+        let big = match (&bytes[0..4], &bytes[4..8]) {
+            ([84, 0, 0, 0], b"CORD") => false,
+            ([0, 0, 0, 84], b"CORD") => true,
+            _ => return Err("not a DCD file".to_owned()),
+        };
+        let mut records = Records{bytes: bytes, offset: 0, big: big};
+
+        let (header, _) = try!(records.next());
+        let control = |i: usize| records.i32_at(header + 4 + 4 * i);
+        if control(8) != 0 {
+            return Err("DCD files with fixed atoms are not supported".to_owned());
+        }
+        let has_cell = control(19) != 0 && control(10) != 0;
+        let has_4d   = control(19) != 0 && control(11) != 0;
+        try!(records.next()); // titles
+        let (begin, end) = try!(records.next());
+        if end - begin != 4 {
+            return Err(format!("the record of the number of atoms has {} bytes", end - begin));
+        }
+        let natoms = records.i32_at(begin);
+        if natoms < 0 {
+            return Err(format!("invalid number of atoms: {}", natoms));
+        }
+        let natoms = natoms as usize;
+
+        let mut frames = Vec::new();
+        while !records.is_end() {
+            let unit_cell = if has_cell {
+                let (begin, end) = try!(records.next());
+                if end - begin != 48 {
+                    return Err(format!("the unit cell record has {} bytes", end - begin));
+                }
+                Some(read_cell(&records, begin))
+            } else {None};
+            let mut axes = Vec::with_capacity(3);
+            for _ in 0..3 {
+                let (begin, end) = try!(records.next());
+                if end - begin != 4 * natoms {
+                    return Err(format!("{} coordinates are given for {} atoms",
+                                       (end - begin) / 4, natoms));
+                }
+                axes.push(begin);
+            }
+            if has_4d {
+                try!(records.next());
+            }
+            let positions = (0..natoms).map(|i| {
+                Vector3::new(records.f32_at(axes[0] + 4 * i) as f64,
+                             records.f32_at(axes[1] + 4 * i) as f64,
+                             records.f32_at(axes[2] + 4 * i) as f64)
+            }).collect();
+            frames.push(Frame{positions: positions, unit_cell: unit_cell});
+        }
+        Ok(frames)
+    }
+
+    /// Reads all the frames as models of atoms named X, all in a residue
+    /// UNK 1 of chain A. The unit cell is that of the first frame.
+    pub fn read_structure(&mut self) -> Result<Structure, String> {
+        let frames = try!(self.read_frames());
+        let mut structure = Structure::new();
+        for (i, frame) in frames.into_iter().enumerate() {
+            if i == 0 {
+                structure.header.unit_cell = frame.unit_cell;
+            }
+            let mut model = Model::new(i as i32 + 1);
+            model.atoms = frame.positions.iter().enumerate().map(|(j, p)| {
+                AtomBuilder::new()
+                    .atom_number(j as i32 + 1)
+                    .atom_residue_chain("X", "UNK", 'A')
+                    .pos(p[0], p[1], p[2])
+                    .occupancy(1.0)
+                    .temperature_factor(0.0)
+                    .finalize()
+            }).collect();
+            structure.models.push(model);
+        }
+        Ok(structure)
+    }
+}
+
+/// The cell is stored as A, gamma, B, beta, alpha, C. Angles are written
+/// as cosines by newer CHARMM and NAMD, and in degrees by older programs.
+fn read_cell(records: &Records, at: usize) -> UnitCell {
+    let v: Vec<f64> = (0..6).map(|i| records.f64_at(at + 8 * i)).collect();
+    let angle = |x: f64| if -1.0 <= x && x <= 1.0 {x.acos().to_degrees()} else {x};
+    UnitCell{
+        a: v[0], b: v[2], c: v[5], alpha: angle(v[4]), beta: angle(v[3]), gamma: angle(v[1]),
+        space_group: "P 1".to_owned(), z: 1,
+    }
+}
+
+pub struct Writer<W: Write> {
+    writer : BufWriter<W>,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn new(inner: W) -> Self {
+        Writer{writer: BufWriter::new(inner)}
+    }
+
+    fn record(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let size = le_i32(bytes.len() as i32);
+        try!(self.writer.write_all(&size));
+        try!(self.writer.write_all(bytes));
+        self.writer.write_all(&size)
+    }
+
+    /// Writes each model as a frame in the CHARMM format. The unit cell of
+    /// the header is written to every frame if it is given. All the models
+    /// must have the same number of atoms.
+    pub fn write_structure(&mut self, structure: &Structure) -> io::Result<()> {
+        let natoms = structure.models.first().map_or(0, |m| m.len());
+        if let Some(m) = structure.models.iter().find(|m| m.len() != natoms) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!(
+                "model {} has {} atoms while the first one has {}", m.serial, m.len(), natoms)));
+        }
+        let cell = structure.header.unit_cell.as_ref();
+
+        let mut control = [0i32; 20];
+        control[0]  = structure.models.len() as i32;
+        control[2]  = 1;
+        control[3]  = structure.models.len() as i32;
+        control[9]  = 1.0f32.to_bits() as i32;
+        control[10] = if cell.is_some() {1} else {0};
+        control[19] = 24;
+        let mut header = b"CORD".to_vec();
+        for c in control.iter() {
+            header.extend_from_slice(&le_i32(*c));
+        }
+        try!(self.record(&header));
+
+        let mut title = le_i32(1).to_vec();
+        let text = if structure.header.title.is_empty() {
+            "written by mill"
+        } else {
+            structure.header.title.as_str()
+        };
+        title.extend(format!("{:<80.80}", text).bytes());
+        try!(self.record(&title));
+        try!(self.record(&le_i32(natoms as i32)));
+
+        for model in structure.models.iter() {
+            if let Some(cell) = cell {
+                let cos = |x: f64| x.to_radians().cos();
+                let mut bytes = Vec::with_capacity(48);
+                for v in [cell.a, cos(cell.gamma), cell.b, cos(cell.beta), cos(cell.alpha),
+                          cell.c].iter() {
+                    bytes.extend_from_slice(&le_u64(v.to_bits()));
+                }
+                try!(self.record(&bytes));
+            }
+            let positions = model.positions();
+            for k in 0..3 {
+                let mut bytes = Vec::with_capacity(4 * natoms);
+                for p in positions.iter() {
+                    bytes.extend_from_slice(&le_i32((p[k] as f32).to_bits() as i32));
+                }
+                try!(self.record(&bytes));
+            }
+        }
+        self.writer.flush()
+    }
+}
+
+fn le_i32(x: i32) -> [u8; 4] {
+    let x = x as u32;
+    [x as u8, (x >> 8) as u8, (x >> 16) as u8, (x >> 24) as u8]
+}
+
+fn le_u64(x: u64) -> [u8; 8] {
+    let (lo, hi) = (le_i32(x as u32 as i32), le_i32((x >> 32) as u32 as i32));
+    [lo[0], lo[1], lo[2], lo[3], hi[0], hi[1], hi[2], hi[3]]
+}
@@ -0,0 +1,132 @@
+//! module dihedral: dihedral angles and backbone/side-chain torsions.
+use std::string::String;
+use std::vec::Vec;
+use super::Particle;
+use super::pdb::{AtomData, ResidueData, Model, Structure};
+use super::pdb::{ResidueId, ResidueIterator, ResidueSlice};
+
+/// Residues are regarded as connected if C(i-1)-N(i) is shorter than this.
+pub const PEPTIDE_BOND_CUTOFF: f64 = 2.0;
+
+/// The dihedral angle a-b-c-d in degrees, in (-180, 180]. NaN if three of
+/// the points are collinear.
+pub fn dihedral<P, Q, R, S>(a: &P, b: &Q, c: &R, d: &S) -> f64
+    where P: Particle, Q: Particle, R: Particle, S: Particle {
+    let b1 = b.vec() - a.vec();
+    let b2 = c.vec() - b.vec();
+    let b3 = d.vec() - c.vec();
+    let n1 = b1.cross(&b2);
+    let n2 = b2.cross(&b3);
+    if n1.norm_squared() == 0.0 || n2.norm_squared() == 0.0 {
+        return ::std::f64::NAN;
+    }
+    let y = b2.norm() * b1.dot(&n2);
+    let x = n1.dot(&n2);
+    let angle = y.atan2(x).to_degrees();
+    if angle <= -180.0 {angle + 360.0} else {angle}
+}
+
+/// Atoms that define the side-chain dihedrals of a residue.
+pub fn chi_atoms(residue: &str) -> &'static [[&'static str; 4]] {
+    const N_CA_CB_CG: [&'static str; 4] = ["N", "CA", "CB", "CG"];
+    match residue {
+        "ARG" => &[N_CA_CB_CG, ["CA", "CB", "CG", "CD"], ["CB", "CG", "CD", "NE"],
+                   ["CG", "CD", "NE", "CZ"]],
+        "ASN" | "ASP" | "ASH" => &[N_CA_CB_CG, ["CA", "CB", "CG", "OD1"]],
+        "CYS" | "CYX" => &[["N", "CA", "CB", "SG"]],
+        "GLN" | "GLU" | "GLH" => &[N_CA_CB_CG, ["CA", "CB", "CG", "CD"],
+                                   ["CB", "CG", "CD", "OE1"]],
+        "HIS" | "HID" | "HIE" | "HIP" | "HSD" | "HSE" | "HSP" =>
+                 &[N_CA_CB_CG, ["CA", "CB", "CG", "ND1"]],
+        "ILE" => &[["N", "CA", "CB", "CG1"], ["CA", "CB", "CG1", "CD1"]],
+        "LEU" => &[N_CA_CB_CG, ["CA", "CB", "CG", "CD1"]],
+        "LYS" | "LYN" => &[N_CA_CB_CG, ["CA", "CB", "CG", "CD"], ["CB", "CG", "CD", "CE"],
+                           ["CG", "CD", "CE", "NZ"]],
+        "MET" => &[N_CA_CB_CG, ["CA", "CB", "CG", "SD"], ["CB", "CG", "SD", "CE"]],
+        "MSE" => &[N_CA_CB_CG, ["CA", "CB", "CG", "SE"], ["CB", "CG", "SE", "CE"]],
+        "PHE" | "TRP" | "TYR" => &[N_CA_CB_CG, ["CA", "CB", "CG", "CD1"]],
+        "PRO" => &[N_CA_CB_CG, ["CA", "CB", "CG", "CD"]],
+        "SER" => &[["N", "CA", "CB", "OG"]],
+        "THR" => &[["N", "CA", "CB", "OG1"]],
+        "VAL" => &[["N", "CA", "CB", "CG1"]],
+        _ => &[],
+    }
+}
+
+/// Dihedral angles of a residue in degrees. An angle is `None` if an atom
+/// is missing, at the chain termini, or across a chain break.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dihedrals {
+    pub residue : ResidueId,
+    /// C(i-1)-N-CA-C
+    pub phi     : Option<f64>,
+    /// N-CA-C-N(i+1)
+    pub psi     : Option<f64>,
+    /// CA-C-N(i+1)-CA(i+1)
+    pub omega   : Option<f64>,
+    /// chi1 to chi4; the residue defines how many.
+    pub chi     : Vec<Option<f64>>,
+}
+
+fn find<'a, T: AtomData>(residue: &'a ResidueSlice<T>, name: &str) -> Option<&'a T> {
+    residue.atoms.iter().find(|a| a.atom_name() == name)
+}
+
+fn connected<T: AtomData + Particle>(prev: &ResidueSlice<T>, next: &ResidueSlice<T>) -> bool {
+    if prev.chain_id() != next.chain_id() {
+        return false;
+    }
+    match (find(prev, "C"), find(next, "N")) {
+        (Some(c), Some(n)) => (c.vec() - n.vec()).norm() < PEPTIDE_BOND_CUTOFF,
+        _ => false,
+    }
+}
+
+fn angle<T: AtomData + Particle>(atoms: [Option<&T>; 4]) -> Option<f64> {
+    match atoms {
+        [Some(a), Some(b), Some(c), Some(d)] => Some(dihedral(a, b, c, d)),
+        _ => None,
+    }
+}
+
+/// Dihedral angles of each residue. Residues are consecutive if they are
+/// in the same chain and bonded by C-N; see `PEPTIDE_BOND_CUTOFF`.
+pub fn dihedrals<T: AtomData + Particle>(atoms: &[T]) -> Vec<Dihedrals> {
+    let residues: Vec<ResidueSlice<T>> = ResidueIterator::from_slice(atoms).collect();
+    let mut result = Vec::with_capacity(residues.len());
+    for (i, residue) in residues.iter().enumerate() {
+        let prev = if i > 0 && connected(&residues[i-1], residue) {
+            Some(&residues[i-1])
+        } else {None};
+        let next = if i + 1 < residues.len() && connected(residue, &residues[i+1]) {
+            Some(&residues[i+1])
+        } else {None};
+        let here = |name: &str| find(residue, name);
+        let prev_c  = prev.and_then(|r| find(r, "C"));
+        let next_n  = next.and_then(|r| find(r, "N"));
+        let next_ca = next.and_then(|r| find(r, "CA"));
+        result.push(Dihedrals{
+            residue : residue.residue_id(),
+            phi     : angle([prev_c, here("N"), here("CA"), here("C")]),
+            psi     : angle([here("N"), here("CA"), here("C"), next_n]),
+            omega   : angle([here("CA"), here("C"), next_n, next_ca]),
+            chi     : chi_atoms(residue.residue_name()).iter().map(|names| {
+                angle([here(names[0]), here(names[1]), here(names[2]), here(names[3])])
+            }).collect(),
+        });
+    }
+    result
+}
+
+/// Dihedral angles of ATOM records in a model.
+pub fn of_model(model: &Model) -> Vec<Dihedrals> {
+    dihedrals(&model.atoms)
+}
+
+/// Dihedral angles in each model, e.g. each frame of a trajectory.
+pub fn per_model(structure: &Structure) -> Result<Vec<Vec<Dihedrals>>, String> {
+    if structure.models.is_empty() {
+        return Err("no model found".to_owned());
+    }
+    Ok(structure.models.iter().map(of_model).collect())
+}
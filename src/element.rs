@@ -0,0 +1,245 @@
+//! module element: the periodic table and element inference from atom names.
+use std::string::String;
+use std::vec::Vec;
+use super::pdb::{AtomData, classify};
+
+/// Properties of an element. Radii are in angstroms.
+///
+/// Covalent radii are from Cordero et al. (2008) and van der Waals radii are
+/// from Bondi (1964), supplemented by Alvarez (2013). For elements heavier
+/// than Cm, 1.50 and 2.00 are used as placeholders.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Element {
+    pub atomic_number   : u8,
+    pub symbol          : &'static str,
+    pub name            : &'static str,
+    pub mass            : f64,
+    pub covalent_radius : f64,
+    pub vdw_radius      : f64,
+}
+
+pub const ELEMENTS: [Element; 118] = [
+    Element{atomic_number:   1, symbol: "H",  name: "Hydrogen",      mass:    1.008, covalent_radius: 0.31, vdw_radius: 1.20},
+    Element{atomic_number:   2, symbol: "He", name: "Helium",        mass:   4.0026, covalent_radius: 0.28, vdw_radius: 1.40},
+    Element{atomic_number:   3, symbol: "Li", name: "Lithium",       mass:     6.94, covalent_radius: 1.28, vdw_radius: 1.82},
+    Element{atomic_number:   4, symbol: "Be", name: "Beryllium",     mass:   9.0122, covalent_radius: 0.96, vdw_radius: 1.53},
+    Element{atomic_number:   5, symbol: "B",  name: "Boron",         mass:    10.81, covalent_radius: 0.84, vdw_radius: 1.92},
+    Element{atomic_number:   6, symbol: "C",  name: "Carbon",        mass:   12.011, covalent_radius: 0.76, vdw_radius: 1.70},
+    Element{atomic_number:   7, symbol: "N",  name: "Nitrogen",      mass:   14.007, covalent_radius: 0.71, vdw_radius: 1.55},
+    Element{atomic_number:   8, symbol: "O",  name: "Oxygen",        mass:   15.999, covalent_radius: 0.66, vdw_radius: 1.52},
+    Element{atomic_number:   9, symbol: "F",  name: "Fluorine",      mass:   18.998, covalent_radius: 0.57, vdw_radius: 1.47},
+    Element{atomic_number:  10, symbol: "Ne", name: "Neon",          mass:   20.180, covalent_radius: 0.58, vdw_radius: 1.54},
+    Element{atomic_number:  11, symbol: "Na", name: "Sodium",        mass:   22.990, covalent_radius: 1.66, vdw_radius: 2.27},
+    Element{atomic_number:  12, symbol: "Mg", name: "Magnesium",     mass:   24.305, covalent_radius: 1.41, vdw_radius: 1.73},
+    Element{atomic_number:  13, symbol: "Al", name: "Aluminium",     mass:   26.982, covalent_radius: 1.21, vdw_radius: 1.84},
+    Element{atomic_number:  14, symbol: "Si", name: "Silicon",       mass:   28.085, covalent_radius: 1.11, vdw_radius: 2.10},
+    Element{atomic_number:  15, symbol: "P",  name: "Phosphorus",    mass:   30.974, covalent_radius: 1.07, vdw_radius: 1.80},
+    Element{atomic_number:  16, symbol: "S",  name: "Sulfur",        mass:    32.06, covalent_radius: 1.05, vdw_radius: 1.80},
+    Element{atomic_number:  17, symbol: "Cl", name: "Chlorine",      mass:    35.45, covalent_radius: 1.02, vdw_radius: 1.75},
+    Element{atomic_number:  18, symbol: "Ar", name: "Argon",         mass:   39.948, covalent_radius: 1.06, vdw_radius: 1.88},
+    Element{atomic_number:  19, symbol: "K",  name: "Potassium",     mass:   39.098, covalent_radius: 2.03, vdw_radius: 2.75},
+    Element{atomic_number:  20, symbol: "Ca", name: "Calcium",       mass:   40.078, covalent_radius: 1.76, vdw_radius: 2.31},
+    Element{atomic_number:  21, symbol: "Sc", name: "Scandium",      mass:   44.956, covalent_radius: 1.70, vdw_radius: 2.15},
+    Element{atomic_number:  22, symbol: "Ti", name: "Titanium",      mass:   47.867, covalent_radius: 1.60, vdw_radius: 2.11},
+    Element{atomic_number:  23, symbol: "V",  name: "Vanadium",      mass:   50.942, covalent_radius: 1.53, vdw_radius: 2.07},
+    Element{atomic_number:  24, symbol: "Cr", name: "Chromium",      mass:   51.996, covalent_radius: 1.39, vdw_radius: 2.06},
+    Element{atomic_number:  25, symbol: "Mn", name: "Manganese",     mass:   54.938, covalent_radius: 1.39, vdw_radius: 2.05},
+    Element{atomic_number:  26, symbol: "Fe", name: "Iron",          mass:   55.845, covalent_radius: 1.32, vdw_radius: 2.04},
+    Element{atomic_number:  27, symbol: "Co", name: "Cobalt",        mass:   58.933, covalent_radius: 1.26, vdw_radius: 2.00},
+    Element{atomic_number:  28, symbol: "Ni", name: "Nickel",        mass:   58.693, covalent_radius: 1.24, vdw_radius: 1.63},
+    Element{atomic_number:  29, symbol: "Cu", name: "Copper",        mass:   63.546, covalent_radius: 1.32, vdw_radius: 1.40},
+    Element{atomic_number:  30, symbol: "Zn", name: "Zinc",          mass:    65.38, covalent_radius: 1.22, vdw_radius: 1.39},
+    Element{atomic_number:  31, symbol: "Ga", name: "Gallium",       mass:   69.723, covalent_radius: 1.22, vdw_radius: 1.87},
+    Element{atomic_number:  32, symbol: "Ge", name: "Germanium",     mass:   72.630, covalent_radius: 1.20, vdw_radius: 2.11},
+    Element{atomic_number:  33, symbol: "As", name: "Arsenic",       mass:   74.922, covalent_radius: 1.19, vdw_radius: 1.85},
+    Element{atomic_number:  34, symbol: "Se", name: "Selenium",      mass:   78.971, covalent_radius: 1.20, vdw_radius: 1.90},
+    Element{atomic_number:  35, symbol: "Br", name: "Bromine",       mass:   79.904, covalent_radius: 1.20, vdw_radius: 1.85},
+    Element{atomic_number:  36, symbol: "Kr", name: "Krypton",       mass:   83.798, covalent_radius: 1.16, vdw_radius: 2.02},
+    Element{atomic_number:  37, symbol: "Rb", name: "Rubidium",      mass:   85.468, covalent_radius: 2.20, vdw_radius: 3.03},
+    Element{atomic_number:  38, symbol: "Sr", name: "Strontium",     mass:    87.62, covalent_radius: 1.95, vdw_radius: 2.49},
+    Element{atomic_number:  39, symbol: "Y",  name: "Yttrium",       mass:   88.906, covalent_radius: 1.90, vdw_radius: 2.32},
+    Element{atomic_number:  40, symbol: "Zr", name: "Zirconium",     mass:   91.224, covalent_radius: 1.75, vdw_radius: 2.23},
+    Element{atomic_number:  41, symbol: "Nb", name: "Niobium",       mass:   92.906, covalent_radius: 1.64, vdw_radius: 2.18},
+    Element{atomic_number:  42, symbol: "Mo", name: "Molybdenum",    mass:    95.95, covalent_radius: 1.54, vdw_radius: 2.17},
+    Element{atomic_number:  43, symbol: "Tc", name: "Technetium",    mass:     98.0, covalent_radius: 1.47, vdw_radius: 2.16},
+    Element{atomic_number:  44, symbol: "Ru", name: "Ruthenium",     mass:   101.07, covalent_radius: 1.46, vdw_radius: 2.13},
+    Element{atomic_number:  45, symbol: "Rh", name: "Rhodium",       mass:   102.91, covalent_radius: 1.42, vdw_radius: 2.10},
+    Element{atomic_number:  46, symbol: "Pd", name: "Palladium",     mass:   106.42, covalent_radius: 1.39, vdw_radius: 1.63},
+    Element{atomic_number:  47, symbol: "Ag", name: "Silver",        mass:   107.87, covalent_radius: 1.45, vdw_radius: 1.72},
+    Element{atomic_number:  48, symbol: "Cd", name: "Cadmium",       mass:   112.41, covalent_radius: 1.44, vdw_radius: 1.58},
+    Element{atomic_number:  49, symbol: "In", name: "Indium",        mass:   114.82, covalent_radius: 1.42, vdw_radius: 1.93},
+    Element{atomic_number:  50, symbol: "Sn", name: "Tin",           mass:   118.71, covalent_radius: 1.39, vdw_radius: 2.17},
+    Element{atomic_number:  51, symbol: "Sb", name: "Antimony",      mass:   121.76, covalent_radius: 1.39, vdw_radius: 2.06},
+    Element{atomic_number:  52, symbol: "Te", name: "Tellurium",     mass:   127.60, covalent_radius: 1.38, vdw_radius: 2.06},
+    Element{atomic_number:  53, symbol: "I",  name: "Iodine",        mass:   126.90, covalent_radius: 1.39, vdw_radius: 1.98},
+    Element{atomic_number:  54, symbol: "Xe", name: "Xenon",         mass:   131.29, covalent_radius: 1.40, vdw_radius: 2.16},
+    Element{atomic_number:  55, symbol: "Cs", name: "Caesium",       mass:   132.91, covalent_radius: 2.44, vdw_radius: 3.43},
+    Element{atomic_number:  56, symbol: "Ba", name: "Barium",        mass:   137.33, covalent_radius: 2.15, vdw_radius: 2.68},
+    Element{atomic_number:  57, symbol: "La", name: "Lanthanum",     mass:   138.91, covalent_radius: 2.07, vdw_radius: 2.43},
+    Element{atomic_number:  58, symbol: "Ce", name: "Cerium",        mass:   140.12, covalent_radius: 2.04, vdw_radius: 2.42},
+    Element{atomic_number:  59, symbol: "Pr", name: "Praseodymium",  mass:   140.91, covalent_radius: 2.03, vdw_radius: 2.40},
+    Element{atomic_number:  60, symbol: "Nd", name: "Neodymium",     mass:   144.24, covalent_radius: 2.01, vdw_radius: 2.39},
+    Element{atomic_number:  61, symbol: "Pm", name: "Promethium",    mass:    145.0, covalent_radius: 1.99, vdw_radius: 2.38},
+    Element{atomic_number:  62, symbol: "Sm", name: "Samarium",      mass:   150.36, covalent_radius: 1.98, vdw_radius: 2.36},
+    Element{atomic_number:  63, symbol: "Eu", name: "Europium",      mass:   151.96, covalent_radius: 1.98, vdw_radius: 2.35},
+    Element{atomic_number:  64, symbol: "Gd", name: "Gadolinium",    mass:   157.25, covalent_radius: 1.96, vdw_radius: 2.34},
+    Element{atomic_number:  65, symbol: "Tb", name: "Terbium",       mass:   158.93, covalent_radius: 1.94, vdw_radius: 2.33},
+    Element{atomic_number:  66, symbol: "Dy", name: "Dysprosium",    mass:   162.50, covalent_radius: 1.92, vdw_radius: 2.31},
+    Element{atomic_number:  67, symbol: "Ho", name: "Holmium",       mass:   164.93, covalent_radius: 1.92, vdw_radius: 2.30},
+    Element{atomic_number:  68, symbol: "Er", name: "Erbium",        mass:   167.26, covalent_radius: 1.89, vdw_radius: 2.29},
+    Element{atomic_number:  69, symbol: "Tm", name: "Thulium",       mass:   168.93, covalent_radius: 1.90, vdw_radius: 2.27},
+    Element{atomic_number:  70, symbol: "Yb", name: "Ytterbium",     mass:   173.05, covalent_radius: 1.87, vdw_radius: 2.26},
+    Element{atomic_number:  71, symbol: "Lu", name: "Lutetium",      mass:   174.97, covalent_radius: 1.87, vdw_radius: 2.24},
+    Element{atomic_number:  72, symbol: "Hf", name: "Hafnium",       mass:   178.49, covalent_radius: 1.75, vdw_radius: 2.23},
+    Element{atomic_number:  73, symbol: "Ta", name: "Tantalum",      mass:   180.95, covalent_radius: 1.70, vdw_radius: 2.22},
+    Element{atomic_number:  74, symbol: "W",  name: "Tungsten",      mass:   183.84, covalent_radius: 1.62, vdw_radius: 2.18},
+    Element{atomic_number:  75, symbol: "Re", name: "Rhenium",       mass:   186.21, covalent_radius: 1.51, vdw_radius: 2.16},
+    Element{atomic_number:  76, symbol: "Os", name: "Osmium",        mass:   190.23, covalent_radius: 1.44, vdw_radius: 2.16},
+    Element{atomic_number:  77, symbol: "Ir", name: "Iridium",       mass:   192.22, covalent_radius: 1.41, vdw_radius: 2.13},
+    Element{atomic_number:  78, symbol: "Pt", name: "Platinum",      mass:   195.08, covalent_radius: 1.36, vdw_radius: 1.75},
+    Element{atomic_number:  79, symbol: "Au", name: "Gold",          mass:   196.97, covalent_radius: 1.36, vdw_radius: 1.66},
+    Element{atomic_number:  80, symbol: "Hg", name: "Mercury",       mass:   200.59, covalent_radius: 1.32, vdw_radius: 1.55},
+    Element{atomic_number:  81, symbol: "Tl", name: "Thallium",      mass:   204.38, covalent_radius: 1.45, vdw_radius: 1.96},
+    Element{atomic_number:  82, symbol: "Pb", name: "Lead",          mass:    207.2, covalent_radius: 1.46, vdw_radius: 2.02},
+    Element{atomic_number:  83, symbol: "Bi", name: "Bismuth",       mass:   208.98, covalent_radius: 1.48, vdw_radius: 2.07},
+    Element{atomic_number:  84, symbol: "Po", name: "Polonium",      mass:    209.0, covalent_radius: 1.40, vdw_radius: 1.97},
+    Element{atomic_number:  85, symbol: "At", name: "Astatine",      mass:    210.0, covalent_radius: 1.50, vdw_radius: 2.02},
+    Element{atomic_number:  86, symbol: "Rn", name: "Radon",         mass:    222.0, covalent_radius: 1.50, vdw_radius: 2.20},
+    Element{atomic_number:  87, symbol: "Fr", name: "Francium",      mass:    223.0, covalent_radius: 2.60, vdw_radius: 3.48},
+    Element{atomic_number:  88, symbol: "Ra", name: "Radium",        mass:    226.0, covalent_radius: 2.21, vdw_radius: 2.83},
+    Element{atomic_number:  89, symbol: "Ac", name: "Actinium",      mass:    227.0, covalent_radius: 2.15, vdw_radius: 2.47},
+    Element{atomic_number:  90, symbol: "Th", name: "Thorium",       mass:   232.04, covalent_radius: 2.06, vdw_radius: 2.45},
+    Element{atomic_number:  91, symbol: "Pa", name: "Protactinium",  mass:   231.04, covalent_radius: 2.00, vdw_radius: 2.43},
+    Element{atomic_number:  92, symbol: "U",  name: "Uranium",       mass:   238.03, covalent_radius: 1.96, vdw_radius: 1.86},
+    Element{atomic_number:  93, symbol: "Np", name: "Neptunium",     mass:    237.0, covalent_radius: 1.90, vdw_radius: 2.39},
+    Element{atomic_number:  94, symbol: "Pu", name: "Plutonium",     mass:    244.0, covalent_radius: 1.87, vdw_radius: 2.43},
+    Element{atomic_number:  95, symbol: "Am", name: "Americium",     mass:    243.0, covalent_radius: 1.80, vdw_radius: 2.44},
+    Element{atomic_number:  96, symbol: "Cm", name: "Curium",        mass:    247.0, covalent_radius: 1.69, vdw_radius: 2.45},
+    Element{atomic_number:  97, symbol: "Bk", name: "Berkelium",     mass:    247.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number:  98, symbol: "Cf", name: "Californium",   mass:    251.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number:  99, symbol: "Es", name: "Einsteinium",   mass:    252.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number: 100, symbol: "Fm", name: "Fermium",       mass:    257.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number: 101, symbol: "Md", name: "Mendelevium",   mass:    258.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number: 102, symbol: "No", name: "Nobelium",      mass:    259.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number: 103, symbol: "Lr", name: "Lawrencium",    mass:    266.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number: 104, symbol: "Rf", name: "Rutherfordium", mass:    267.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number: 105, symbol: "Db", name: "Dubnium",       mass:    268.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number: 106, symbol: "Sg", name: "Seaborgium",    mass:    269.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number: 107, symbol: "Bh", name: "Bohrium",       mass:    270.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number: 108, symbol: "Hs", name: "Hassium",       mass:    277.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number: 109, symbol: "Mt", name: "Meitnerium",    mass:    278.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number: 110, symbol: "Ds", name: "Darmstadtium",  mass:    281.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number: 111, symbol: "Rg", name: "Roentgenium",   mass:    282.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number: 112, symbol: "Cn", name: "Copernicium",   mass:    285.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number: 113, symbol: "Nh", name: "Nihonium",      mass:    286.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number: 114, symbol: "Fl", name: "Flerovium",     mass:    289.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number: 115, symbol: "Mc", name: "Moscovium",     mass:    290.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number: 116, symbol: "Lv", name: "Livermorium",   mass:    293.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number: 117, symbol: "Ts", name: "Tennessine",    mass:    294.0, covalent_radius: 1.50, vdw_radius: 2.00},
+    Element{atomic_number: 118, symbol: "Og", name: "Oganesson",     mass:    294.0, covalent_radius: 1.50, vdw_radius: 2.00},
+];
+
+/// Deuterium, often written as `D` in neutron structures.
+pub const DEUTERIUM: Element = Element{
+    atomic_number: 1, symbol: "D", name: "Deuterium", mass: 2.014,
+    covalent_radius: 0.31, vdw_radius: 1.20
+};
+
+/// Finds an element by symbol. The symbol is case-insensitive.
+pub fn by_symbol(symbol: &str) -> Option<&'static Element> {
+    let symbol = symbol.trim();
+    if symbol.eq_ignore_ascii_case("D") {
+        return Some(&DEUTERIUM);
+    }
+    ELEMENTS.iter().find(|e| e.symbol.eq_ignore_ascii_case(symbol))
+}
+
+pub fn by_number(atomic_number: u8) -> Option<&'static Element> {
+    if atomic_number == 0 || atomic_number as usize > ELEMENTS.len() {
+        None
+    } else {
+        Some(&ELEMENTS[atomic_number as usize - 1])
+    }
+}
+
+/// The mass of an element. The symbol is case-insensitive.
+pub fn mass(symbol: &str) -> Option<f64> {
+    by_symbol(symbol).map(|e| e.mass)
+}
+
+/// Residues that consist of one atom of an element, i.e. ions.
+/// In these residues, the atom name is the element symbol (CA is calcium,
+/// HG is mercury, and so on).
+const IONS: &'static [&'static str] = &[
+    "LI", "NA", "K", "RB", "CS", "MG", "CA", "SR", "BA", "MN", "FE", "CO",
+    "NI", "CU", "ZN", "CD", "HG", "PT", "AU", "AG", "PB", "AL", "GA", "CL",
+    "BR", "IOD", "F", "YB", "SM", "EU", "GD", "TB", "LA", "TL", "CR", "V",
+];
+
+/// Whether a residue is a monatomic ion, e.g. NA, CL or ZN.
+pub fn is_ion(residue_name: &str) -> bool {
+    IONS.contains(&residue_name.trim().to_uppercase().as_str())
+}
+
+/// Two-letter elements recognized at the beginning of the atom name of
+/// a ligand, if the rest of the name is digits (e.g. CL1, BR, FE).
+/// Other elements are not guessed because names like CA1 or NA are
+/// carbon or nitrogen in most ligands.
+const LIGAND_ELEMENTS: &'static [&'static str] = &[
+    "CL", "BR", "FE", "ZN", "MG", "MN", "CU", "SE", "CO", "NI", "HG", "PT",
+];
+
+/// Guesses the element from atom and residue names. Used when the element
+/// columns (77-78) are blank.
+///
+/// - In ion residues like CA, ZN or HG, the atom is that element.
+/// - Selenomethionine (MSE) has a selenium named SE.
+/// - In ligands, atom names like CL1, BR or FE are halogens or metals.
+/// - Otherwise, the first letter after leading digits is the element, so
+///   that CA is an alpha carbon and HG1 or 1HG is a hydrogen.
+pub fn infer(atom_name: &str, residue_name: &str) -> Option<&'static Element> {
+    let name = atom_name.trim().to_uppercase();
+    let res  = residue_name.trim().to_uppercase();
+    if IONS.contains(&res.as_str()) {
+        let stripped = name.trim_end_matches(|c: char| c.is_digit(10) || c == '+' || c == '-');
+        if res == "IOD" && stripped == "I" {
+            return by_symbol("I");
+        }
+        if stripped == res {
+            return by_symbol(&res);
+        }
+    }
+    if res == "MSE" && name == "SE" {
+        return by_symbol("SE");
+    }
+    let stripped = name.trim_start_matches(|c: char| c.is_digit(10));
+    if !classify(&res).is_polymer() && stripped.len() >= 2 {
+        let (head, tail) = stripped.split_at(2);
+        if LIGAND_ELEMENTS.contains(&head) && tail.chars().all(|c| c.is_digit(10)) {
+            return by_symbol(head);
+        }
+    }
+    stripped.chars().next().and_then(|c| {
+        let mut buf = [0u8; 4];
+        by_symbol(c.encode_utf8(&mut buf))
+    })
+}
+
+/// The element of an atom. The element symbol is used if it is written,
+/// otherwise it is inferred from atom and residue names.
+pub fn of<T: AtomData>(atom: &T) -> Option<&'static Element> {
+    if !atom.element_symbol().trim().is_empty() {
+        by_symbol(atom.element_symbol())
+    } else {
+        infer(atom.atom_name(), atom.residue_name())
+    }
+}
+
+/// Masses of atoms. See `of` for how the elements are determined.
+pub fn masses<T: AtomData>(atoms: &[T]) -> Result<Vec<f64>, String> {
+    atoms.iter().map(|a| {
+        of(a).map(|e| e.mass).ok_or(
+            format!("unknown element `{}` of atom {} {}", a.element_symbol(),
+                    a.atom_number(), a.atom_name()))
+    }).collect()
+}
@@ -0,0 +1,217 @@
+//! module enm: normal modes of elastic network models.
+//!
+//! Particles, usually CA atoms, within a cutoff are connected by springs of
+//! the same constant gamma. The anisotropic network model (ANM; Atilgan et
+//! al., 2001) has a 3N x 3N Hessian and modes with directions, while the
+//! Gaussian network model (GNM; Bahar et al., 1997) has an N x N Kirchhoff
+//! matrix and isotropic modes. Fluctuations are in units of kT/gamma.
+use std::cmp::Ordering;
+use std::f64::consts::PI;
+use std::str::FromStr;
+use std::string::String;
+use std::vec::Vec;
+use nalgebra::{DMatrix, DVector, Vector3};
+use super::Particle;
+use super::neighbor::CellList;
+
+/// Eigenvalues smaller than this times the largest are those of the rigid
+/// body motions, or of disconnected parts of the network.
+pub const ZERO_EIGENVALUE: f64 = 1e-8;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Network {
+    Anisotropic,
+    Gaussian,
+}
+
+impl FromStr for Network {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Network, String> {
+        match s {
+            "anm" | "ANM" => Ok(Network::Anisotropic),
+            "gnm" | "GNM" => Ok(Network::Gaussian),
+            _ => Err(format!("unknown network model `{}`; use anm or gnm", s)),
+        }
+    }
+}
+
+impl Network {
+    /// Typical cutoffs for CA atoms: 15 angstroms for ANM and 7.3 for GNM.
+    pub fn default_cutoff(&self) -> f64 {
+        match *self {
+            Network::Anisotropic => 15.0,
+            Network::Gaussian    => 7.3,
+        }
+    }
+
+    /// Degrees of freedom per particle.
+    pub fn dimension(&self) -> usize {
+        match *self {
+            Network::Anisotropic => 3,
+            Network::Gaussian    => 1,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ElasticNetwork {
+    pub network : Network,
+    pub cutoff  : f64,
+    /// The spring constant.
+    pub gamma   : f64,
+}
+
+/// A normal mode. The vector is normalized and has `dimension()` elements
+/// per particle.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mode {
+    pub eigenvalue : f64,
+    pub vector     : DVector<f64>,
+}
+
+impl Mode {
+    /// The angular frequency for unit masses, in units of sqrt(gamma).
+    pub fn frequency(&self) -> f64 {
+        self.eigenvalue.sqrt()
+    }
+}
+
+/// Nonzero modes in ascending order of the eigenvalues.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Modes {
+    pub network : Network,
+    pub modes   : Vec<Mode>,
+}
+
+impl ElasticNetwork {
+    /// A network with the default cutoff and gamma = 1.
+    pub fn new(network: Network) -> ElasticNetwork {
+        ElasticNetwork{network: network, cutoff: network.default_cutoff(), gamma: 1.0}
+    }
+
+    /// The Hessian of ANM or the Kirchhoff matrix of GNM. Particles at the
+    /// same position are not connected; their spring has no direction.
+    pub fn matrix<P: Particle>(&self, particles: &[P]) -> DMatrix<f64> {
+        let positions: Vec<Vector3<f64>> = particles.iter().map(|p| p.vec()).collect();
+        let d = self.network.dimension();
+        let mut matrix = DMatrix::zeros(d * positions.len(), d * positions.len());
+        let cells = CellList::new(&positions, self.cutoff);
+        for (i, j, r) in cells.pairs(self.cutoff) {
+            if r == 0.0 {
+                continue;
+            }
+            match self.network {
+                Network::Gaussian => {
+                    matrix[(i, j)] -= self.gamma;
+                    matrix[(j, i)] -= self.gamma;
+                    matrix[(i, i)] += self.gamma;
+                    matrix[(j, j)] += self.gamma;
+                }
+                Network::Anisotropic => {
+                    let v = positions[j] - positions[i];
+                    for a in 0..3 {
+                        for b in 0..3 {
+                            let k = -self.gamma * v[a] * v[b] / (r * r);
+                            matrix[(3 * i + a, 3 * j + b)] += k;
+                            matrix[(3 * j + a, 3 * i + b)] += k;
+                            matrix[(3 * i + a, 3 * i + b)] -= k;
+                            matrix[(3 * j + a, 3 * j + b)] -= k;
+                        }
+                    }
+                }
+            }
+        }
+        matrix
+    }
+
+    /// Diagonalizes the matrix and drops the zero modes; six for ANM and
+    /// one for GNM if the network is connected.
+    pub fn modes<P: Particle>(&self, particles: &[P]) -> Result<Modes, String> {
+        if particles.len() < 2 {
+            return Err("at least two particles are needed".to_owned());
+        }
+        let eigen = self.matrix(particles).symmetric_eigen();
+        let largest = eigen.eigenvalues.iter().fold(0.0f64, |m, x| m.max(*x));
+        if largest <= 0.0 {
+            return Err(format!("no particles are within {} angstroms", self.cutoff));
+        }
+        let mut modes: Vec<Mode> = (0..eigen.eigenvalues.len())
+            .filter(|k| eigen.eigenvalues[*k] > ZERO_EIGENVALUE * largest)
+            .map(|k| Mode{eigenvalue: eigen.eigenvalues[k],
+                          vector: eigen.eigenvectors.column(k).into_owned()})
+            .collect();
+        modes.sort_by(|a, b| a.eigenvalue.partial_cmp(&b.eigenvalue).unwrap_or(Ordering::Equal));
+        Ok(Modes{network: self.network, modes: modes})
+    }
+}
+
+impl Modes {
+    /// Mean square fluctuations of the particles by the slowest `count`
+    /// modes, or all of them. Multiply by kT/gamma for angstroms squared.
+    pub fn fluctuations(&self, count: Option<usize>) -> Vec<f64> {
+        let d = self.network.dimension();
+        let n = self.modes.first().map_or(0, |m| m.vector.len() / d);
+        let count = count.unwrap_or(self.modes.len()).min(self.modes.len());
+        let mut fluctuations = vec![0.0; n];
+        for mode in self.modes[..count].iter() {
+            for i in 0..n {
+                let square = (0..d).map(|a| mode.vector[d * i + a].powi(2)).sum::<f64>();
+                fluctuations[i] += square / mode.eigenvalue;
+            }
+        }
+        if self.network == Network::Gaussian {
+            // the fluctuations of x, y and z are the same
+            for f in fluctuations.iter_mut() {
+                *f *= 3.0;
+            }
+        }
+        fluctuations
+    }
+
+    /// Positions along an ANM mode, counted from 0, for a cycle of `frames`
+    /// frames; the largest RMSD from the original is `amplitude`.
+    pub fn animate<P: Particle>(&self, particles: &[P], mode: usize, amplitude: f64,
+                                frames: usize) -> Result<Vec<Vec<Vector3<f64>>>, String> {
+        if self.network != Network::Anisotropic {
+            return Err("GNM modes have no directions".to_owned());
+        }
+        let vector = match self.modes.get(mode) {
+            Some(m) => &m.vector,
+            None => return Err(format!("mode {} is not in the {} modes", mode, self.modes.len())),
+        };
+        if vector.len() != 3 * particles.len() {
+            return Err(format!("the mode is of {} particles", vector.len() / 3));
+        }
+        // the vector is normalized, so its RMSD per particle is 1/sqrt(N)
+        let scale = amplitude * (particles.len() as f64).sqrt();
+        Ok((0..frames).map(|t| {
+            let s = scale * (2.0 * PI * t as f64 / frames as f64).sin();
+            particles.iter().enumerate().map(|(i, p)| {
+                p.vec() + Vector3::new(vector[3 * i], vector[3 * i + 1], vector[3 * i + 2]) * s
+            }).collect()
+        }).collect())
+    }
+}
+
+/// B-factors from mean square fluctuations, 8 pi^2 / 3 <dR^2>.
+pub fn b_factors(fluctuations: &[f64]) -> Vec<f64> {
+    fluctuations.iter().map(|f| 8.0 * PI * PI / 3.0 * f).collect()
+}
+
+/// The Pearson correlation coefficient; None if either has no variance.
+pub fn correlation(lhs: &[f64], rhs: &[f64]) -> Option<f64> {
+    let n = lhs.len().min(rhs.len());
+    if n == 0 {
+        return None;
+    }
+    let mean = |x: &[f64]| x[..n].iter().sum::<f64>() / n as f64;
+    let (ml, mr) = (mean(lhs), mean(rhs));
+    let (mut sll, mut srr, mut slr) = (0.0, 0.0, 0.0);
+    for (l, r) in lhs[..n].iter().zip(rhs[..n].iter()) {
+        sll += (l - ml) * (l - ml);
+        srr += (r - mr) * (r - mr);
+        slr += (l - ml) * (r - mr);
+    }
+    if sll == 0.0 || srr == 0.0 {None} else {Some(slr / (sll * srr).sqrt())}
+}
@@ -0,0 +1,142 @@
+//! module geometry: geometric properties of a set of particles.
+use nalgebra::{Matrix3, Vector3};
+use std::cmp::Ordering;
+use std::string::String;
+use std::vec::Vec;
+use super::Particle;
+use super::pdb::{AtomData, Structure};
+use super::element;
+
+/// The average position. `None` if there is no particle.
+pub fn center_of_geometry<P, I>(particles: I) -> Option<Vector3<f64>>
+    where P: Particle, I: IntoIterator<Item = P> {
+    let mut sum = Vector3::zeros();
+    let mut n   = 0;
+    for p in particles {
+        sum += p.vec();
+        n   += 1;
+    }
+    if n == 0 {None} else {Some(sum / n as f64)}
+}
+
+/// The weighted average position. `None` if the total weight is zero.
+pub fn center_of_mass<P, I, W>(particles: I, masses: W) -> Option<Vector3<f64>>
+    where P: Particle, I: IntoIterator<Item = P>, W: IntoIterator<Item = f64> {
+    let mut sum   = Vector3::zeros();
+    let mut total = 0.0;
+    for (p, m) in particles.into_iter().zip(masses.into_iter()) {
+        sum   += p.vec() * m;
+        total += m;
+    }
+    if total == 0.0 {None} else {Some(sum / total)}
+}
+
+/// Radius of gyration, optionally weighted by masses.
+pub fn radius_of_gyration<P: Particle>(particles: &[P], masses: Option<&[f64]>)
+    -> Option<f64> {
+    let ones = vec![1.0; particles.len()];
+    let ms = masses.unwrap_or(&ones);
+    let center = match center_of_mass(particles, ms.iter().cloned()) {
+        Some(c) => c, None => return None
+    };
+    let total = ms.iter().fold(0.0, |acc, m| acc + m);
+    let sum = particles.iter().zip(ms.iter())
+        .fold(0.0, |acc, (p, m)| acc + (p.vec() - center).norm_squared() * m);
+    Some((sum / total).sqrt())
+}
+
+/// The inertia tensor around the center of mass.
+pub fn inertia_tensor<P: Particle>(particles: &[P], masses: &[f64]) -> Option<Matrix3<f64>> {
+    let center = match center_of_mass(particles, masses.iter().cloned()) {
+        Some(c) => c, None => return None
+    };
+    let mut tensor = Matrix3::zeros();
+    for (p, m) in particles.iter().zip(masses.iter()) {
+        let r = p.vec() - center;
+        tensor += (Matrix3::identity() * r.norm_squared() - r * r.transpose()) * *m;
+    }
+    Some(tensor)
+}
+
+/// Principal moments of inertia in ascending order and the corresponding
+/// axes as columns of the matrix.
+pub fn principal_axes<P: Particle>(particles: &[P], masses: &[f64])
+    -> Option<(Vector3<f64>, Matrix3<f64>)> {
+    let tensor = match inertia_tensor(particles, masses) {
+        Some(t) => t, None => return None
+    };
+    let eigen = tensor.symmetric_eigen();
+    let mut order = [0, 1, 2];
+    order.sort_by(|i, j| {
+        eigen.eigenvalues[*i].partial_cmp(&eigen.eigenvalues[*j]).unwrap_or(Ordering::Equal)
+    });
+    let moments = Vector3::new(eigen.eigenvalues[order[0]],
+                               eigen.eigenvalues[order[1]],
+                               eigen.eigenvalues[order[2]]);
+    let axes = Matrix3::from_columns(&[eigen.eigenvectors.column(order[0]),
+                                       eigen.eigenvectors.column(order[1]),
+                                       eigen.eigenvectors.column(order[2])]);
+    Some((moments, axes))
+}
+
+/// The lower and upper corners of the axis-aligned bounding box.
+pub fn bounding_box<P, I>(particles: I) -> Option<(Vector3<f64>, Vector3<f64>)>
+    where P: Particle, I: IntoIterator<Item = P> {
+    let mut iter = particles.into_iter();
+    let first = match iter.next() {Some(p) => p.vec(), None => return None};
+    let (mut lower, mut upper) = (first, first);
+    for p in iter {
+        let v = p.vec();
+        for i in 0..3 {
+            if v[i] < lower[i] {lower[i] = v[i];}
+            if v[i] > upper[i] {upper[i] = v[i];}
+        }
+    }
+    Some((lower, upper))
+}
+
+/// The distance between the first and the last particles.
+pub fn end_to_end_distance<P: Particle>(particles: &[P]) -> Option<f64> {
+    match (particles.first(), particles.last()) {
+        (Some(f), Some(l)) => Some((l.vec() - f.vec()).norm()),
+        _ => None,
+    }
+}
+
+/// Geometric properties of a set of atoms.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Properties {
+    pub center_of_geometry : Vector3<f64>,
+    pub center_of_mass     : Vector3<f64>,
+    pub radius_of_gyration : f64,
+    pub principal_moments  : Vector3<f64>,
+    pub principal_axes     : Matrix3<f64>,
+    pub bounding_box       : (Vector3<f64>, Vector3<f64>),
+    pub end_to_end         : f64,
+}
+
+impl Properties {
+    /// Computes all the properties. Masses are taken from element symbols.
+    pub fn of<T: AtomData + Particle>(atoms: &[T]) -> Result<Properties, String> {
+        if atoms.is_empty() {
+            return Err("no atom to compute properties".to_owned());
+        }
+        let masses = try!(element::masses(atoms));
+        let (moments, axes) = try!(principal_axes(atoms, &masses)
+                                   .ok_or("total mass is zero".to_owned()));
+        Ok(Properties{
+            center_of_geometry : center_of_geometry(atoms).unwrap(),
+            center_of_mass     : center_of_mass(atoms, masses.iter().cloned()).unwrap(),
+            radius_of_gyration : radius_of_gyration(atoms, Some(&masses)).unwrap(),
+            principal_moments  : moments,
+            principal_axes     : axes,
+            bounding_box       : bounding_box(atoms).unwrap(),
+            end_to_end         : end_to_end_distance(atoms).unwrap(),
+        })
+    }
+}
+
+/// Properties of ATOM records in each model (frame).
+pub fn per_model(structure: &Structure) -> Result<Vec<Properties>, String> {
+    structure.models.iter().map(|m| Properties::of(&m.atoms)).collect()
+}
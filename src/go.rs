@@ -0,0 +1,232 @@
+//! module go: structure-based (Go-like) models with a bead at each CA atom.
+//!
+//! A `GoModel` has the native geometry of a structure: bond lengths, bond
+//! angles and dihedrals along the chains, and the distances of native
+//! contacts. Lengths are in angstroms and angles in degrees. The energy
+//! function is given by `ForceConstants` when the model is written, as TOML
+//! or as a GROMACS topology in the style of SMOG (Clementi et al., 2000):
+//!
+//! ```text
+//! V = sum k_b / 2 (r - r0)^2 + sum k_a / 2 (theta - theta0)^2
+//!   + sum k_d [(1 - cos(phi - phi0)) + (1 - cos 3(phi - phi0)) / 2]
+//!   + sum epsilon [(r0 / r)^12 - 2 (r0 / r)^6] + repulsion of the others
+//! ```
+use std::io::{self, Write};
+use std::string::String;
+use std::vec::Vec;
+use nalgebra::Vector3;
+use super::Particle;
+use super::contact::{self, Definition};
+use super::dihedral::dihedral;
+use super::pdb::{AtomData, ResidueData, ResidueId, ResidueIterator, ResidueKind, classify};
+
+/// Consecutive CA atoms farther than this are not bonded; 3.8 angstroms in
+/// a chain, or 2.9 for a cis peptide.
+pub const CHAIN_BREAK: f64 = 4.3;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bead {
+    pub residue  : ResidueId,
+    pub position : Vector3<f64>,
+}
+
+/// A bonded term or a native contact between beads, indexed from 0, with
+/// the native value of its length or angle.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Term<I> {
+    pub beads  : I,
+    pub native : f64,
+}
+
+pub type Bond     = Term<[usize; 2]>;
+pub type Angle    = Term<[usize; 3]>;
+pub type Dihedral = Term<[usize; 4]>;
+pub type Pair     = Term<[usize; 2]>;
+
+/// Strengths of the terms, in units of `epsilon`, and in angstroms and
+/// radians. The defaults are those of SMOG for CA models.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ForceConstants {
+    /// The unit of energy in kJ/mol.
+    pub epsilon   : f64,
+    pub bond      : f64,
+    pub angle     : f64,
+    /// Of the first harmonic; the third one has the half.
+    pub dihedral  : f64,
+    pub contact   : f64,
+    /// The excluded volume of beads not in contact, `(sigma / r)^12`.
+    pub sigma     : f64,
+}
+
+impl Default for ForceConstants {
+    fn default() -> ForceConstants {
+        ForceConstants{epsilon: 1.0, bond: 200.0, angle: 40.0, dihedral: 1.0, contact: 1.0,
+                       sigma: 4.0}
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GoModel {
+    pub beads     : Vec<Bead>,
+    pub bonds     : Vec<Bond>,
+    pub angles    : Vec<Angle>,
+    pub dihedrals : Vec<Dihedral>,
+    pub contacts  : Vec<Pair>,
+}
+
+fn angle(a: &Vector3<f64>, b: &Vector3<f64>, c: &Vector3<f64>) -> f64 {
+    let (u, v) = (a - b, c - b);
+    (u.dot(&v) / (u.norm() * v.norm())).max(-1.0).min(1.0).acos().to_degrees()
+}
+
+impl GoModel {
+    /// Makes a model of the amino acids with a CA atom. Native contacts are
+    /// found by the definition, but their distances are between CA atoms.
+    pub fn new<T: AtomData + Particle>(atoms: &[T], definition: Definition)
+        -> Result<GoModel, String> {
+        let beads: Vec<Bead> = ResidueIterator::from_slice(atoms).filter_map(|residue| {
+            if classify(residue.residue_name()) != ResidueKind::AminoAcid {
+                return None;
+            }
+            residue.atoms.iter().find(|a| a.atom_name() == "CA").map(|ca| {
+                Bead{residue: residue.residue_id(), position: ca.vec()}
+            })
+        }).collect();
+        if beads.is_empty() {
+            return Err("no CA atom of amino acids".to_owned());
+        }
+
+        let bonded = |i: usize| {
+            beads[i].residue.chain_id() == beads[i + 1].residue.chain_id() &&
+                (beads[i + 1].position - beads[i].position).norm() <= CHAIN_BREAK
+        };
+        let p = |i: usize| &beads[i].position;
+        let mut model = GoModel{beads: Vec::new(), bonds: Vec::new(), angles: Vec::new(),
+                                dihedrals: Vec::new(), contacts: Vec::new()};
+        let n = beads.len();
+        for i in 0..n - 1 {
+            if !bonded(i) {
+                continue;
+            }
+            model.bonds.push(Term{beads: [i, i + 1], native: (p(i + 1) - p(i)).norm()});
+            if i + 2 < n && bonded(i + 1) {
+                model.angles.push(Term{beads: [i, i + 1, i + 2],
+                                       native: angle(p(i), p(i + 1), p(i + 2))});
+                if i + 3 < n && bonded(i + 2) {
+                    let phi = dihedral(p(i), p(i + 1), p(i + 2), p(i + 3));
+                    if !phi.is_nan() {
+                        model.dihedrals.push(Term{beads: [i, i + 1, i + 2, i + 3],
+                                                  native: phi});
+                    }
+                }
+            }
+        }
+        for c in contact::contacts(atoms, definition) {
+            let find = |id: &ResidueId| beads.iter().position(|b| b.residue == *id);
+            if let (Some(i), Some(j)) = (find(&c.lhs), find(&c.rhs)) {
+                model.contacts.push(Term{beads: [i, j], native: (p(j) - p(i)).norm()});
+            }
+        }
+        model.beads = beads;
+        Ok(model)
+    }
+
+    /// Writes the model and the force constants in TOML. Beads are numbered
+    /// from 1 as in the GROMACS topology.
+    pub fn write_toml<W: Write>(&self, out: &mut W, constants: &ForceConstants)
+        -> io::Result<()> {
+        try!(writeln!(out, "# Go model written by mill"));
+        try!(writeln!(out, "# angstroms, degrees and epsilon; beads are numbered from 1"));
+        try!(writeln!(out, "[parameters]"));
+        try!(writeln!(out, "epsilon  = {:.6} # kJ/mol", constants.epsilon));
+        try!(writeln!(out, "bond     = {:.6} # epsilon/angstrom^2", constants.bond));
+        try!(writeln!(out, "angle    = {:.6} # epsilon/radian^2", constants.angle));
+        try!(writeln!(out, "dihedral = {:.6}", constants.dihedral));
+        try!(writeln!(out, "contact  = {:.6}", constants.contact));
+        try!(writeln!(out, "sigma    = {:.6} # angstroms", constants.sigma));
+        try!(writeln!(out, "\n[topology]"));
+        try!(writeln!(out, "beads = ["));
+        for bead in self.beads.iter() {
+            let r = &bead.residue;
+            try!(writeln!(out, "    {{chain = \"{}\", residue = \"{}\", number = {}, \
+                                position = [{:.3}, {:.3}, {:.3}]}},",
+                          r.chain_id(), r.residue_name(), r.residue_number(),
+                          bead.position[0], bead.position[1], bead.position[2]));
+        }
+        try!(writeln!(out, "]"));
+        try!(write_terms(out, "bonds", "length", &self.bonds));
+        try!(write_terms(out, "angles", "angle", &self.angles));
+        try!(write_terms(out, "dihedrals", "angle", &self.dihedrals));
+        write_terms(out, "contacts", "distance", &self.contacts)
+    }
+
+    /// Writes the molecule type of a GROMACS topology, in nm, degrees and
+    /// kJ/mol, to be included in a topology with the `CA` atom type. Native
+    /// contacts are 12-6 pairs excluded from the nonbonded interactions.
+    pub fn write_itp<W: Write>(&self, out: &mut W, constants: &ForceConstants, name: &str)
+        -> io::Result<()> {
+        let e = constants.epsilon;
+        try!(writeln!(out, "[ moleculetype ]\n; name nrexcl\n{} 3", name));
+        try!(writeln!(out, "\n[ atoms ]\n; nr type resnr residue atom cgnr charge mass"));
+        for (i, bead) in self.beads.iter().enumerate() {
+            try!(writeln!(out, "{:6} CA {:6} {:>4} CA {:6} 0.0 1.0", i + 1,
+                          bead.residue.residue_number(), bead.residue.residue_name(), i + 1));
+        }
+        try!(writeln!(out, "\n[ bonds ]\n; ai aj func r0 kb"));
+        for b in self.bonds.iter() {
+            try!(writeln!(out, "{:6} {:6} 1 {:.5} {:.6e}", b.beads[0] + 1, b.beads[1] + 1,
+                          b.native / 10.0, constants.bond * e * 100.0));
+        }
+        try!(writeln!(out, "\n[ angles ]\n; ai aj ak func theta0 k"));
+        for a in self.angles.iter() {
+            try!(writeln!(out, "{:6} {:6} {:6} 1 {:.3} {:.6e}", a.beads[0] + 1, a.beads[1] + 1,
+                          a.beads[2] + 1, a.native, constants.angle * e));
+        }
+        try!(writeln!(out, "\n[ dihedrals ]\n; ai aj ak al func phi k mult"));
+        for d in self.dihedrals.iter() {
+            for &(n, k) in [(1, constants.dihedral), (3, constants.dihedral / 2.0)].iter() {
+                // 1 - cos(n (phi - phi0)) = 1 + cos(n phi - (n phi0 + 180))
+                let phase = ((n as f64 * d.native + 180.0) % 360.0 + 360.0) % 360.0;
+                try!(writeln!(out, "{:6} {:6} {:6} {:6} 1 {:.3} {:.6e} {}", d.beads[0] + 1,
+                              d.beads[1] + 1, d.beads[2] + 1, d.beads[3] + 1, phase, k * e, n));
+            }
+        }
+        try!(writeln!(out, "\n[ pairs ]\n; ai aj func c6 c12"));
+        for c in self.contacts.iter() {
+            let r6 = (c.native / 10.0).powi(6);
+            let eps = constants.contact * e;
+            try!(writeln!(out, "{:6} {:6} 1 {:.6e} {:.6e}", c.beads[0] + 1, c.beads[1] + 1,
+                          2.0 * eps * r6, eps * r6 * r6));
+        }
+        try!(writeln!(out, "\n[ exclusions ]\n; ai aj"));
+        for c in self.contacts.iter() {
+            try!(writeln!(out, "{:6} {:6}", c.beads[0] + 1, c.beads[1] + 1));
+        }
+        Ok(())
+    }
+
+    /// Writes a GROMACS topology with one molecule of the model.
+    pub fn write_top<W: Write>(&self, out: &mut W, constants: &ForceConstants, name: &str)
+        -> io::Result<()> {
+        try!(writeln!(out, "; Go model written by mill\n"));
+        try!(writeln!(out, "[ defaults ]\n; nbfunc comb-rule gen-pairs\n1 1 no 1.0 1.0"));
+        try!(writeln!(out, "\n[ atomtypes ]\n; name mass charge ptype c6 c12"));
+        try!(writeln!(out, "CA 1.0 0.0 A 0.0 {:.6e}\n",
+                      constants.epsilon * (constants.sigma / 10.0).powi(12)));
+        try!(self.write_itp(out, constants, name));
+        try!(writeln!(out, "\n[ system ]\n; name\n{}", name));
+        writeln!(out, "\n[ molecules ]\n; name count\n{} 1", name)
+    }
+}
+
+fn write_terms<W: Write, I: AsRef<[usize]>>(out: &mut W, name: &str, value: &str,
+                                              terms: &[Term<I>]) -> io::Result<()> {
+    try!(writeln!(out, "{} = [", name));
+    for term in terms.iter() {
+        let beads: Vec<String> = term.beads.as_ref().iter().map(|i| (i + 1).to_string())
+                                                        .collect();
+        try!(writeln!(out, "    {{beads = [{}], {} = {:.3}}},", beads.join(", "), value,
+                      term.native));
+    }
+    writeln!(out, "]")
+}
@@ -0,0 +1,182 @@
+//! module gro: GROMACS coordinate files (.gro).
+//!
+//! Coordinates and box vectors are in nm in a file and in angstroms in a
+//! structure. Each frame in a file becomes a model. Atoms in GRO files have
+//! no chain IDs and are read as ATOM records of chain A.
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::string::String;
+use std::vec::Vec;
+use nalgebra::Vector3;
+use super::pdb::{Atom, AtomBuilder, AtomData, Model, Structure, UnitCell};
+
+/// Angstroms in a nm.
+const NM: f64 = 10.0;
+
+fn truncate(s: &str, len: usize) -> &str {
+    match s.char_indices().nth(len) {Some((i, _)) => &s[..i], None => s}
+}
+
+/// The unit cell of box vectors.
+pub fn unit_cell(v1: &Vector3<f64>, v2: &Vector3<f64>, v3: &Vector3<f64>) -> UnitCell {
+    let angle = |a: &Vector3<f64>, b: &Vector3<f64>| {
+        (a.dot(b) / (a.norm() * b.norm())).acos().to_degrees()
+    };
+    UnitCell{
+        a: v1.norm(), b: v2.norm(), c: v3.norm(),
+        alpha: angle(v2, v3), beta: angle(v1, v3), gamma: angle(v1, v2),
+        space_group: "P 1".to_owned(), z: 1,
+    }
+}
+
+/// Box vectors of a unit cell. v1 is along x and v2 is on the xy plane.
+pub fn box_vectors(cell: &UnitCell) -> [Vector3<f64>; 3] {
+    let m = cell.orthogonalization();
+    let column = |j: usize| Vector3::new(m[(0, j)], m[(1, j)], m[(2, j)]);
+    [column(0), column(1), column(2)]
+}
+
+pub struct Reader<R> {
+    reader : BufReader<R>,
+}
+
+impl<R: Read> Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Reader{reader: BufReader::new(inner)}
+    }
+
+    /// Reads all the frames up to the end of the file; titles may be blank.
+    /// The unit cell is the box of the first frame.
+    pub fn read_structure(&mut self) -> Result<Structure, String> {
+        let mut structure = Structure::new();
+        let mut lines = self.reader.by_ref().lines();
+        loop {
+            let title = match lines.next() {
+                Some(line) => try!(line.map_err(|e| e.to_string())),
+                None       => break,
+            };
+            let mut next = || -> Result<String, String> {
+                match lines.next() {
+                    Some(line) => line.map_err(|e| e.to_string()),
+                    None       => Err("unexpected end of file".to_owned()),
+                }
+            };
+            let count = try!(next());
+            let count = try!(count.trim().parse::<usize>().map_err(
+                |e| format!("{} at\n{}", e, count)));
+            let mut model = Model::new(structure.models.len() as i32 + 1);
+            for _ in 0..count {
+                model.atoms.push(try!(read_atom(&try!(next()))));
+            }
+            let cell = try!(read_box(&try!(next())));
+            if structure.models.is_empty() {
+                structure.header.title = title.trim().to_owned();
+                structure.header.unit_cell = cell;
+            }
+            structure.models.push(model);
+        }
+        Ok(structure)
+    }
+}
+
+fn read_atom(line: &str) -> Result<Atom, String> {
+    if line.len() < 44 || !line.is_ascii() {
+        return Err(format!("invalid atom line:\n{}", line));
+    }
+    // the width of coordinates is the distance between decimal points
+    let first = try!(line[20..].find('.').ok_or(format!("no coordinate at\n{}", line)));
+    let width = line[20 + first + 1..].find('.').map_or(8, |w| w + 1);
+    let field = |i: usize| -> Result<f64, String> {
+        let (b, e) = (20 + i * width, 20 + (i + 1) * width);
+        let text = try!(line.get(b..e.min(line.len())).ok_or(
+            format!("too short line:\n{}", line)));
+        text.trim().parse::<f64>().map_err(|e| format!("{} at\n{}", e, line))
+    };
+    let integer = |b: usize, e: usize| -> Result<i32, String> {
+        line[b..e].trim().parse::<i32>().map_err(|e| format!("{} at\n{}", e, line))
+    };
+    let (atom, residue) = (truncate(line[10..15].trim(), 4), truncate(line[5..10].trim(), 3));
+    Ok(AtomBuilder::new()
+        .atom_number(try!(integer(15, 20)))
+        .residue_number(try!(integer(0, 5)))
+        .atom_residue_chain(atom, residue, 'A')
+        .pos(try!(field(0)) * NM, try!(field(1)) * NM, try!(field(2)) * NM)
+        .occupancy(1.0)
+        .temperature_factor(0.0)
+        .finalize())
+}
+
+/// Reads a box line, `v1(x) v2(y) v3(z) [v1(y) v1(z) v2(x) v2(z) v3(x) v3(y)]`.
+/// A zero box means no unit cell.
+fn read_box(line: &str) -> Result<Option<UnitCell>, String> {
+    let values: Vec<f64> = try!(line.split_whitespace().map(|v| {
+        v.parse::<f64>().map(|x| x * NM).map_err(|e| format!("{} at\n{}", e, line))
+    }).collect());
+    let v = match values.len() {
+        3 => [Vector3::new(values[0], 0.0, 0.0), Vector3::new(0.0, values[1], 0.0),
+              Vector3::new(0.0, 0.0, values[2])],
+        9 => [Vector3::new(values[0], values[3], values[4]),
+              Vector3::new(values[5], values[1], values[6]),
+              Vector3::new(values[7], values[8], values[2])],
+        _ => return Err(format!("invalid box line:\n{}", line)),
+    };
+    if v.iter().any(|x| x.norm() == 0.0) {
+        return Ok(None);
+    }
+    Ok(Some(unit_cell(&v[0], &v[1], &v[2])))
+}
+
+pub struct Writer<W: Write> {
+    writer : BufWriter<W>,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn new(inner: W) -> Self {
+        Writer{writer: BufWriter::new(inner)}
+    }
+
+    /// Writes each model as a frame. Atom and residue numbers wrap around
+    /// at 100000 as GROMACS does.
+    pub fn write_structure(&mut self, structure: &Structure) -> io::Result<()> {
+        let title = if structure.header.title.is_empty() {
+            "written by mill"
+        } else {
+            structure.header.title.as_str()
+        };
+        for model in structure.models.iter() {
+            try!(writeln!(self.writer, "{}", title));
+            try!(writeln!(self.writer, "{:5}", model.len()));
+            for atom in model.atoms.iter() {
+                try!(self.write_atom(atom, atom.x, atom.y, atom.z));
+            }
+            for hetatm in model.hetatms.iter() {
+                try!(self.write_atom(hetatm, hetatm.x, hetatm.y, hetatm.z));
+            }
+            try!(self.write_box(structure.header.unit_cell.as_ref()));
+        }
+        self.writer.flush()
+    }
+
+    fn write_atom<T: AtomData>(&mut self, atom: &T, x: f64, y: f64, z: f64)
+        -> io::Result<()> {
+        writeln!(self.writer, "{:5}{:<5}{:>5}{:5}{:8.3}{:8.3}{:8.3}",
+                 atom.residue_number() % 100000, atom.residue_name(),
+                 atom.atom_name(), atom.atom_number() % 100000,
+                 x / NM, y / NM, z / NM)
+    }
+
+    fn write_box(&mut self, cell: Option<&UnitCell>) -> io::Result<()> {
+        let v = match cell {
+            Some(cell) => box_vectors(cell),
+            None => return writeln!(self.writer, "{:10.5}{:10.5}{:10.5}", 0.0, 0.0, 0.0),
+        };
+        let v: Vec<Vector3<f64>> = v.iter().map(|x| *x / NM).collect();
+        try!(write!(self.writer, "{:10.5}{:10.5}{:10.5}", v[0][0], v[1][1], v[2][2]));
+        let off = [v[0][1], v[0][2], v[1][0], v[1][2], v[2][0], v[2][1]];
+        if off.iter().any(|x| x.abs() >= 0.000005) {
+            for x in off.iter() {
+                try!(write!(self.writer, "{:10.5}", x));
+            }
+        }
+        writeln!(self.writer, "")
+    }
+}
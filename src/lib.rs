@@ -9,7 +9,49 @@ pub trait Particle {
     fn name(&self) -> Option<&str> {None}
 }
 
+pub trait ParticleMut : Particle {
+    fn set_vec(&mut self, v: nalgebra::Vector3<f64>);
+}
+
+impl<'a, P: Particle> Particle for &'a P {
+    fn x(&self)    -> f64 {(*self).x()}
+    fn y(&self)    -> f64 {(*self).y()}
+    fn z(&self)    -> f64 {(*self).z()}
+    fn vec(&self)  -> nalgebra::Vector3<f64> {(*self).vec()}
+    fn name(&self) -> Option<&str> {(*self).name()}
+}
+
+impl Particle for nalgebra::Vector3<f64> {
+    fn x(&self)   -> f64 {self[0]}
+    fn y(&self)   -> f64 {self[1]}
+    fn z(&self)   -> f64 {self[2]}
+    fn vec(&self) -> nalgebra::Vector3<f64> {*self}
+}
+
+impl ParticleMut for nalgebra::Vector3<f64> {
+    fn set_vec(&mut self, v: nalgebra::Vector3<f64>) {*self = v;}
+}
+
 pub mod pdb;
+pub mod gro;
+pub mod xyz;
+pub mod mmcif;
+pub mod dcd;
+pub mod element;
+pub mod geometry;
+pub mod select;
+pub mod superpose;
+pub mod neighbor;
+pub mod align;
+pub mod similarity;
+pub mod contact;
+pub mod dihedral;
+pub mod cg;
+pub mod nucleic;
+pub mod go;
+pub mod enm;
+pub mod backmap;
+pub mod complete;
 pub use pdb::atom::Atom;
 pub use pdb::atom::AtomBuilder;
 pub use pdb::hetatm::Hetatm;
@@ -0,0 +1,381 @@
+//! module mmcif: the `_atom_site` category of PDBx/mmCIF files.
+//!
+//! Author-defined names and numbers (`auth_*`) are preferred over label
+//! ones, as in PDB files. Chain IDs longer than one character are cut to the
+//! first, residue names to three and atom names to four characters. Other
+//! categories than `_atom_site`, `_cell`, `_symmetry` and `_struct.title`
+//! are skipped.
+use std::collections::HashMap;
+use std::io::{self, BufWriter, Read, Write};
+use std::string::String;
+use std::vec::Vec;
+use super::element;
+use super::pdb::{AtomBuilder, AtomData, HetatmBuilder, Model, Structure, UnitCell};
+
+/// A token of a CIF file. Quoted values are never keywords or tags.
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Bare(String),
+    Quoted(String),
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        if line.starts_with(';') {
+            let mut value = line[1..].to_owned();
+            while let Some(next) = lines.next() {
+                if next.starts_with(';') {
+                    break;
+                }
+                value.push('\n');
+                value.push_str(next);
+            }
+            tokens.push(Token::Quoted(value.trim().to_owned()));
+            continue;
+        }
+        let bytes = line.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b' ' | b'\t' => i += 1,
+                b'#' => break,
+                quote @ b'\'' | quote @ b'"' => {
+                    // a quote ends only if it is followed by a space
+                    let mut j = i + 1;
+                    while j < bytes.len() && !(bytes[j] == quote &&
+                            (j + 1 == bytes.len() || bytes[j+1] == b' ' || bytes[j+1] == b'\t')) {
+                        j += 1;
+                    }
+                    tokens.push(Token::Quoted(line[i+1..j.min(bytes.len())].to_owned()));
+                    i = j + 1;
+                }
+                _ => {
+                    let mut j = i;
+                    while j < bytes.len() && bytes[j] != b' ' && bytes[j] != b'\t' {
+                        j += 1;
+                    }
+                    tokens.push(Token::Bare(line[i..j].to_owned()));
+                    i = j;
+                }
+            }
+        }
+    }
+    tokens
+}
+
+/// Items and loops of a data block.
+struct Block {
+    name  : String,
+    items : HashMap<String, String>,
+    loops : Vec<(Vec<String>, Vec<String>)>,
+}
+
+fn is_tag(token: &Token) -> bool {
+    match *token {Token::Bare(ref s) => s.starts_with('_'), _ => false}
+}
+
+fn is_keyword(token: &Token) -> bool {
+    match *token {
+        Token::Bare(ref s) => {
+            let lower = s.to_lowercase();
+            lower == "loop_" || lower.starts_with("data_") || lower.starts_with("save_") ||
+            lower == "global_" || lower == "stop_"
+        }
+        _ => false,
+    }
+}
+
+fn value(token: Token) -> String {
+    match token {Token::Bare(s) => s, Token::Quoted(s) => s}
+}
+
+/// Parses the first data block.
+fn parse(text: &str) -> Result<Block, String> {
+    let mut block = Block{name: String::new(), items: HashMap::new(), loops: Vec::new()};
+    let mut tokens = tokenize(text).into_iter().peekable();
+    let mut started = false;
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Bare(ref s) if s.to_lowercase().starts_with("data_") => {
+                if started {
+                    break;
+                }
+                started = true;
+                block.name = s[5..].to_owned();
+            }
+            Token::Bare(ref s) if s.to_lowercase() == "loop_" => {
+                let mut tags = Vec::new();
+                while tokens.peek().map_or(false, is_tag) {
+                    tags.push(value(tokens.next().unwrap()));
+                }
+                let mut values = Vec::new();
+                while tokens.peek().map_or(false, |t| !is_tag(t) && !is_keyword(t)) {
+                    values.push(value(tokens.next().unwrap()));
+                }
+                if tags.is_empty() || values.len() % tags.len() != 0 {
+                    return Err(format!("invalid loop of {}", tags.join(" ")));
+                }
+                block.loops.push((tags, values));
+            }
+            Token::Bare(ref s) if s.starts_with('_') => {
+                match tokens.next() {
+                    Some(ref v) if !is_tag(v) && !is_keyword(v) => {
+                        block.items.insert(s.to_owned(), value(v.clone()));
+                    }
+                    _ => return Err(format!("no value for {}", s)),
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(block)
+}
+
+/// `.` and `?` are missing values.
+fn given(value: &str) -> Option<&str> {
+    if value == "." || value == "?" {None} else {Some(value)}
+}
+
+/// The first `len` bytes of a name, which must be ASCII as in PDB files.
+fn truncate(s: &str, len: usize) -> Result<&str, String> {
+    if !s.is_ascii() {
+        return Err(format!("`{}` is not of ASCII characters", s));
+    }
+    Ok(&s[..s.len().min(len)])
+}
+
+/// Reads the first data block into a structure.
+pub struct Reader<R> {
+    reader : R,
+}
+
+impl<R: Read> Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Reader{reader: inner}
+    }
+
+    /// Reads atoms, one model per `pdbx_PDB_model_num`.
+    pub fn read_structure(&mut self) -> Result<Structure, String> {
+        let mut text = String::new();
+        try!(self.reader.read_to_string(&mut text).map_err(|e| e.to_string()));
+        let block = try!(parse(&text));
+
+        let mut structure = Structure::new();
+        structure.header.id_code = block.name.clone();
+        if let Some(title) = block.items.get("_struct.title") {
+            structure.header.title = title.clone();
+        }
+        structure.header.unit_cell = try!(read_cell(&block.items));
+
+        let &(ref tags, ref values) = match block.loops.iter().find(|l| {
+                l.0.iter().any(|t| t.starts_with("_atom_site."))}) {
+            Some(l) => l,
+            None    => return Ok(structure),
+        };
+        let column = |names: &[&str]| -> Option<usize> {
+            names.iter().filter_map(|name| {
+                tags.iter().position(|t| t == &format!("_atom_site.{}", name))
+            }).next()
+        };
+        let required = |names: &[&str]| -> Result<usize, String> {
+            column(names).ok_or(format!("_atom_site.{} not found", names[0]))
+        };
+        let group   = column(&["group_PDB"]);
+        let serial  = try!(required(&["id"]));
+        let element = column(&["type_symbol"]);
+        let name    = try!(required(&["auth_atom_id", "label_atom_id"]));
+        let altloc  = column(&["label_alt_id"]);
+        let resname = try!(required(&["auth_comp_id", "label_comp_id"]));
+        let chain   = try!(required(&["auth_asym_id", "label_asym_id"]));
+        let resseq  = try!(required(&["auth_seq_id", "label_seq_id"]));
+        let icode   = column(&["pdbx_PDB_ins_code"]);
+        let xyz     = [try!(required(&["Cartn_x"])), try!(required(&["Cartn_y"])),
+                       try!(required(&["Cartn_z"]))];
+        let occ     = column(&["occupancy"]);
+        let bfactor = column(&["B_iso_or_equiv"]);
+        let charge  = column(&["pdbx_formal_charge"]);
+        let model   = column(&["pdbx_PDB_model_num"]);
+
+        let mut current = Model::new(1);
+        for row in values.chunks(tags.len()) {
+            let get = |col: Option<usize>| col.and_then(|c| given(&row[c]));
+            let number = |col: usize, what: &str| -> Result<f64, String> {
+                row[col].parse::<f64>().map_err(|e| {
+                    format!("{}: {} of atom {}", e, what, row[serial])
+                })
+            };
+            if let Some(m) = get(model).and_then(|m| m.parse::<i32>().ok()) {
+                if m != current.serial {
+                    if !current.is_empty() {
+                        structure.models.push(current);
+                    }
+                    current = Model::new(m);
+                }
+            }
+            let charge = match get(charge).and_then(|c| c.parse::<i32>().ok()) {
+                Some(c) if c != 0 => format!("{}{}", c.abs(), if c < 0 {'-'} else {'+'}),
+                _ => String::new(),
+            };
+            let ascii = |name: &str, len: usize| -> Result<String, String> {
+                truncate(name, len).map(|n| n.to_owned()).map_err(|e| {
+                    format!("{}: name of atom {}", e, row[serial])
+                })
+            };
+            let atom_name    = try!(ascii(&row[name], 4));
+            let residue_name = try!(ascii(&row[resname], 3));
+            let element_name = try!(ascii(&get(element).unwrap_or("").to_uppercase(), 2));
+            let resseq = match given(&row[resseq]) {
+                Some(_) => try!(number(resseq, "residue number")) as i32,
+                None    => 0,
+            };
+            let atom = AtomBuilder::new()
+                .atom_number(try!(number(serial, "serial")) as i32)
+                .atom_residue_chain(&atom_name, &residue_name,
+                                    given(&row[chain]).and_then(|c| c.chars().next())
+                                                      .unwrap_or(' '))
+                .residue_number(resseq)
+                .alternate_location(get(altloc).and_then(|a| a.chars().next()).unwrap_or(' '))
+                .insertion_code(get(icode).and_then(|i| i.chars().next()).unwrap_or(' '))
+                .pos(try!(number(xyz[0], "x")), try!(number(xyz[1], "y")),
+                     try!(number(xyz[2], "z")))
+                .occupancy(get(occ).and_then(|o| o.parse().ok()).unwrap_or(1.0))
+                .temperature_factor(get(bfactor).and_then(|b| b.parse().ok()).unwrap_or(0.0))
+                .element(&element_name)
+                .charge(&charge)
+                .finalize();
+            if get(group) == Some("HETATM") {
+                current.hetatms.push(HetatmBuilder::new()
+                    .atom_number(atom.atom_number())
+                    .atom_residue_chain(atom.atom_name(), atom.residue_name(), atom.chain_id())
+                    .residue_number(atom.residue_number())
+                    .alternate_location(atom.alternate_location())
+                    .insertion_code(atom.insertion_code())
+                    .pos(atom.x, atom.y, atom.z)
+                    .occupancy(atom.occupancy())
+                    .temperature_factor(atom.temperature_factor())
+                    .element(atom.element_symbol())
+                    .charge(atom.charge())
+                    .finalize());
+            } else {
+                current.atoms.push(atom);
+            }
+        }
+        if !current.is_empty() {
+            structure.models.push(current);
+        }
+        Ok(structure)
+    }
+}
+
+fn read_cell(items: &HashMap<String, String>) -> Result<Option<UnitCell>, String> {
+    let field = |name: &str| -> Result<Option<f64>, String> {
+        match items.get(&format!("_cell.{}", name)).and_then(|v| given(v)) {
+            Some(v) => v.parse::<f64>().map(Some).map_err(|e| format!("{}: _cell.{}", e, name)),
+            None    => Ok(None),
+        }
+    };
+    let lengths = [try!(field("length_a")), try!(field("length_b")), try!(field("length_c"))];
+    let angles  = [try!(field("angle_alpha")), try!(field("angle_beta")),
+                   try!(field("angle_gamma"))];
+    match (lengths, angles) {
+        ([Some(a), Some(b), Some(c)], [Some(alpha), Some(beta), Some(gamma)]) => {
+            let space_group = items.get("_symmetry.space_group_name_H-M")
+                .and_then(|v| given(v)).unwrap_or("P 1").to_owned();
+            let z = items.get("_cell.Z_PDB").and_then(|v| v.parse().ok()).unwrap_or(1);
+            Ok(Some(UnitCell{a: a, b: b, c: c, alpha: alpha, beta: beta, gamma: gamma,
+                             space_group: space_group, z: z}))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Quotes a value if it is empty, has spaces or quotes, or looks like a tag.
+fn quote(value: &str) -> String {
+    if value.is_empty() {
+        return "?".to_owned();
+    }
+    let special = value.starts_with('_') || value.starts_with('#') ||
+        value.starts_with('$') || value.starts_with('[') || value.starts_with(';') ||
+        value.to_lowercase().starts_with("data_") || value.to_lowercase() == "loop_";
+    if !special && !value.contains(|c: char| c == ' ' || c == '\'' || c == '"') {
+        value.to_owned()
+    } else if !value.contains('"') {
+        format!("\"{}\"", value)
+    } else {
+        format!("'{}'", value)
+    }
+}
+
+pub struct Writer<W: Write> {
+    writer : BufWriter<W>,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn new(inner: W) -> Self {
+        Writer{writer: BufWriter::new(inner)}
+    }
+
+    /// Writes a data block with the cell, symmetry and `_atom_site` of all
+    /// the models. Label and author fields have the same values.
+    pub fn write_structure(&mut self, structure: &Structure) -> io::Result<()> {
+        let name = if structure.header.id_code.trim().is_empty() {
+            "mill"
+        } else {
+            structure.header.id_code.trim()
+        };
+        try!(writeln!(self.writer, "data_{}\n#", name));
+        if !structure.header.title.is_empty() {
+            try!(writeln!(self.writer, "_struct.title {}\n#", quote(&structure.header.title)));
+        }
+        if let Some(ref cell) = structure.header.unit_cell {
+            try!(writeln!(self.writer, "_cell.length_a    {:.3}\n_cell.length_b    {:.3}\n\
+                                        _cell.length_c    {:.3}\n_cell.angle_alpha  {:.2}\n\
+                                        _cell.angle_beta   {:.2}\n_cell.angle_gamma  {:.2}\n\
+                                        _cell.Z_PDB       {}\n#",
+                          cell.a, cell.b, cell.c, cell.alpha, cell.beta, cell.gamma, cell.z));
+            try!(writeln!(self.writer, "_symmetry.space_group_name_H-M {}\n#",
+                          quote(&cell.space_group)));
+        }
+        try!(writeln!(self.writer, "loop_"));
+        for tag in ["group_PDB", "id", "type_symbol", "label_atom_id", "label_alt_id",
+                    "label_comp_id", "label_asym_id", "label_seq_id", "pdbx_PDB_ins_code",
+                    "Cartn_x", "Cartn_y", "Cartn_z", "occupancy", "B_iso_or_equiv",
+                    "pdbx_formal_charge", "auth_seq_id", "auth_comp_id", "auth_asym_id",
+                    "auth_atom_id", "pdbx_PDB_model_num"].iter() {
+            try!(writeln!(self.writer, "_atom_site.{}", tag));
+        }
+        for model in structure.models.iter() {
+            for atom in model.atoms.iter() {
+                try!(self.write_atom(atom, [atom.x, atom.y, atom.z], model.serial));
+            }
+            for hetatm in model.hetatms.iter() {
+                try!(self.write_atom(hetatm, [hetatm.x, hetatm.y, hetatm.z], model.serial));
+            }
+        }
+        try!(writeln!(self.writer, "#"));
+        self.writer.flush()
+    }
+
+    fn write_atom<T: AtomData>(&mut self, atom: &T, pos: [f64; 3], model: i32)
+        -> io::Result<()> {
+        let or_dot = |c: char| if c == ' ' {".".to_owned()} else {c.to_string()};
+        let or_question = |c: char| if c == ' ' {"?".to_owned()} else {c.to_string()};
+        let element = element::of(atom).map_or("X".to_owned(), |e| e.symbol.to_uppercase());
+        let charge  = atom.charge().trim();
+        let charge  = if charge.is_empty() {"?".to_owned()} else {
+            let sign = if charge.ends_with('-') {"-"} else {""};
+            format!("{}{}", sign, charge.trim_end_matches(|c| c == '+' || c == '-'))
+        };
+        let (name, resname) = (quote(atom.atom_name()), quote(atom.residue_name()));
+        writeln!(self.writer,
+                 "{:<6} {:<5} {:<2} {:<6} {} {:<3} {} {:<4} {} {:8.3} {:8.3} {:8.3} {:5.2} \
+                  {:6.2} {} {:<4} {:<3} {} {:<6} {}",
+                 atom.record_name(), atom.atom_number(), element, name,
+                 or_dot(atom.alternate_location()), resname, or_question(atom.chain_id()),
+                 atom.residue_number(), or_question(atom.insertion_code()),
+                 pos[0], pos[1], pos[2], atom.occupancy(), atom.temperature_factor(), charge,
+                 atom.residue_number(), resname, or_question(atom.chain_id()), name, model)
+    }
+}
@@ -0,0 +1,272 @@
+//! module neighbor: neighbor search with a cell list.
+//!
+//! Particles are binned into cubic cells at least as wide as the typical
+//! search radius, so that a query only visits the cells around a point.
+//! Building takes O(N) time and a radius query takes time proportional to
+//! the number of particles in the nearby cells.
+//!
+//! A periodic box is orthorhombic. Distances follow the minimum image
+//! convention, so a search radius must not exceed half the box length.
+
+use nalgebra::Vector3;
+use std::cmp::Ordering;
+use std::vec::Vec;
+use super::Particle;
+
+/// A spatial index of particle positions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CellList {
+    positions : Vec<Vector3<f64>>,
+    origin    : Vector3<f64>,
+    width     : Vector3<f64>,
+    dims      : [i64; 3],
+    periodic  : Option<Vector3<f64>>,
+    /// indices of particles sorted by cell, and where each cell begins
+    indices   : Vec<usize>,
+    starts    : Vec<usize>,
+}
+
+impl CellList {
+    /// Builds an index with cells of width `cell_width`. Queries are the
+    /// fastest when the radius is close to the width.
+    pub fn new<P: Particle>(particles: &[P], cell_width: f64) -> CellList {
+        let positions: Vec<Vector3<f64>> = particles.iter().map(|p| p.vec()).collect();
+        let mut lower = positions.first().cloned().unwrap_or(Vector3::zeros());
+        let mut upper = lower;
+        for p in positions.iter() {
+            for i in 0..3 {
+                lower[i] = lower[i].min(p[i]);
+                upper[i] = upper[i].max(p[i]);
+            }
+        }
+        // sparse systems get wider cells so that empty cells do not dominate
+        let max_cells = 8.0 * positions.len() as f64 + 64.0;
+        let mut width = cell_width.max(1e-3);
+        let mut dims  = [1; 3];
+        loop {
+            for i in 0..3 {
+                dims[i] = ((upper[i] - lower[i]) / width).floor() as i64 + 1;
+            }
+            if (dims[0] as f64) * (dims[1] as f64) * (dims[2] as f64) <= max_cells {
+                break;
+            }
+            width *= 1.25;
+        }
+        CellList::build(positions, lower, Vector3::new(width, width, width), dims, None)
+    }
+
+    /// Builds an index in an orthorhombic periodic box with edge lengths
+    /// `box_size`. Particles outside the box are wrapped into it.
+    pub fn periodic<P: Particle>(particles: &[P], cell_width: f64, box_size: Vector3<f64>)
+        -> CellList {
+        let positions: Vec<Vector3<f64>> = particles.iter().map(|p| {
+            let mut v = p.vec();
+            for i in 0..3 {
+                v[i] -= (v[i] / box_size[i]).floor() * box_size[i];
+            }
+            v
+        }).collect();
+        let max_cells = 8.0 * positions.len() as f64 + 64.0;
+        let mut cell_width = cell_width.max(1e-3);
+        let mut dims  = [1; 3];
+        loop {
+            for i in 0..3 {
+                dims[i] = ((box_size[i] / cell_width).floor() as i64).max(1);
+            }
+            if (dims[0] as f64) * (dims[1] as f64) * (dims[2] as f64) <= max_cells {
+                break;
+            }
+            cell_width *= 1.25;
+        }
+        let mut width = box_size;
+        for i in 0..3 {
+            width[i] = box_size[i] / dims[i] as f64;
+        }
+        CellList::build(positions, Vector3::zeros(), width, dims, Some(box_size))
+    }
+
+    fn build(positions: Vec<Vector3<f64>>, origin: Vector3<f64>, width: Vector3<f64>,
+             dims: [i64; 3], periodic: Option<Vector3<f64>>) -> CellList {
+        let mut list = CellList{
+            positions: Vec::new(), origin: origin, width: width, dims: dims,
+            periodic: periodic, indices: Vec::new(), starts: Vec::new(),
+        };
+        let ncells = (dims[0] * dims[1] * dims[2]) as usize;
+        let cells: Vec<usize> = positions.iter().map(|p| {
+            let c = list.cell_of(p);
+            let mut clamped = [0; 3];
+            for i in 0..3 {
+                clamped[i] = c[i].max(0).min(dims[i] - 1);
+            }
+            list.flatten(clamped)
+        }).collect();
+
+        // counting sort by cell
+        let mut starts = vec![0; ncells + 1];
+        for &c in cells.iter() {
+            starts[c + 1] += 1;
+        }
+        for c in 0..ncells {
+            starts[c + 1] += starts[c];
+        }
+        let mut filled = starts.clone();
+        let mut indices = vec![0; positions.len()];
+        for (i, &c) in cells.iter().enumerate() {
+            indices[filled[c]] = i;
+            filled[c] += 1;
+        }
+        list.positions = positions;
+        list.indices   = indices;
+        list.starts    = starts;
+        list
+    }
+
+    fn cell_of(&self, p: &Vector3<f64>) -> [i64; 3] {
+        let mut c = [0; 3];
+        for i in 0..3 {
+            c[i] = ((p[i] - self.origin[i]) / self.width[i]).floor() as i64;
+        }
+        c
+    }
+
+    fn flatten(&self, c: [i64; 3]) -> usize {
+        ((c[0] * self.dims[1] + c[1]) * self.dims[2] + c[2]) as usize
+    }
+
+    pub fn len(&self)      -> usize {self.positions.len()}
+    pub fn is_empty(&self) -> bool  {self.positions.is_empty()}
+
+    /// The box given to `periodic`.
+    pub fn box_size(&self) -> Option<Vector3<f64>> {self.periodic}
+
+    /// Displacement from `from` to `to`, the minimum image in a periodic box.
+    pub fn displacement(&self, from: &Vector3<f64>, to: &Vector3<f64>) -> Vector3<f64> {
+        let mut d = to - from;
+        if let Some(size) = self.periodic {
+            for i in 0..3 {
+                d[i] -= (d[i] / size[i]).round() * size[i];
+            }
+        }
+        d
+    }
+
+    /// Cell indices along one axis that cover `[lo, hi]`.
+    fn cell_range(&self, axis: usize, lo: f64, hi: f64) -> Vec<i64> {
+        let width = self.width[axis];
+        let first = ((lo - self.origin[axis]) / width).floor() as i64;
+        let last  = ((hi - self.origin[axis]) / width).floor() as i64;
+        let n = self.dims[axis];
+        if self.periodic.is_some() {
+            if last - first + 1 >= n {
+                (0..n).collect()
+            } else {
+                (first..last + 1).map(|c| ((c % n) + n) % n).collect()
+            }
+        } else {
+            (first.max(0)..last.min(n - 1) + 1).collect()
+        }
+    }
+
+    /// Calls `f` with the index and the squared distance of each particle
+    /// within `radius` of `point`, until `f` returns false.
+    fn visit<F>(&self, point: &Vector3<f64>, radius: f64, mut f: F)
+        where F: FnMut(usize, f64) -> bool {
+        let mut p = *point;
+        if let Some(size) = self.periodic {
+            for i in 0..3 {
+                p[i] -= (p[i] / size[i]).floor() * size[i];
+            }
+        }
+        let r2 = radius * radius;
+        let xs = self.cell_range(0, p[0] - radius, p[0] + radius);
+        let ys = self.cell_range(1, p[1] - radius, p[1] + radius);
+        let zs = self.cell_range(2, p[2] - radius, p[2] + radius);
+        for &x in xs.iter() {
+            for &y in ys.iter() {
+                for &z in zs.iter() {
+                    let c = self.flatten([x, y, z]);
+                    for &i in self.indices[self.starts[c]..self.starts[c + 1]].iter() {
+                        let d2 = self.displacement(&p, &self.positions[i]).norm_squared();
+                        if d2 <= r2 && !f(i, d2) {
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Indices of the particles within `radius` of `point`, in ascending order.
+    pub fn within(&self, point: &Vector3<f64>, radius: f64) -> Vec<usize> {
+        let mut found = Vec::new();
+        self.visit(point, radius, |i, _| {found.push(i); true});
+        found.sort();
+        found
+    }
+
+    /// Whether any particle is within `radius` of `point`.
+    pub fn any_within(&self, point: &Vector3<f64>, radius: f64) -> bool {
+        let mut found = false;
+        self.visit(point, radius, |_, _| {found = true; false});
+        found
+    }
+
+    /// The `k` particles closest to `point` as pairs of index and distance,
+    /// nearest first.
+    pub fn nearest(&self, point: &Vector3<f64>, k: usize) -> Vec<(usize, f64)> {
+        let k = k.min(self.len());
+        if k == 0 {
+            return Vec::new();
+        }
+        // a radius that covers all the particles
+        let limit = match self.periodic {
+            Some(size) => size.norm() * 0.5,
+            None => self.positions.iter().fold(0.0, |acc: f64, p| {
+                acc.max((p - point).norm())
+            }),
+        };
+        let mut radius = self.width.iter().fold(::std::f64::INFINITY, |a, &b| a.min(b));
+        loop {
+            radius = radius.min(limit);
+            let mut found = Vec::new();
+            self.visit(point, radius, |i, d2| {found.push((i, d2)); true});
+            if found.len() >= k || radius >= limit {
+                found.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal)
+                                      .then(a.0.cmp(&b.0)));
+                return found.into_iter().take(k).map(|(i, d2)| (i, d2.sqrt())).collect();
+            }
+            radius *= 2.0;
+        }
+    }
+
+    /// All pairs `(i, j, distance)` with `i < j` closer than `cutoff`,
+    /// sorted by `i` and then `j`.
+    pub fn pairs(&self, cutoff: f64) -> Vec<(usize, usize, f64)> {
+        let mut pairs = Vec::new();
+        for i in 0..self.len() {
+            let mut found = Vec::new();
+            self.visit(&self.positions[i], cutoff, |j, d2| {
+                if i < j {found.push((j, d2.sqrt()));}
+                true
+            });
+            found.sort_by(|a, b| a.0.cmp(&b.0));
+            pairs.extend(found.into_iter().map(|(j, d)| (i, j, d)));
+        }
+        pairs
+    }
+
+    /// Pairs `(i, j, distance)` of a particle `i` in this index and a
+    /// particle `j` in `others` closer than `cutoff`.
+    pub fn pairs_with<P: Particle>(&self, others: &[P], cutoff: f64)
+        -> Vec<(usize, usize, f64)> {
+        let mut pairs = Vec::new();
+        for (j, other) in others.iter().enumerate() {
+            self.visit(&other.vec(), cutoff, |i, d2| {
+                pairs.push((i, j, d2.sqrt()));
+                true
+            });
+        }
+        pairs.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
+        pairs
+    }
+}
@@ -0,0 +1,215 @@
+//! module nucleic: nucleotide atom groups, base pairs and base stacking.
+//!
+//! Base pairs are found by the distances of their hydrogen bonds between
+//! donors and acceptors, and stacking by the centers and planes of bases.
+//! Old atom names (`C1*`, `O1P`) are understood as the current ones.
+use std::fmt;
+use std::string::String;
+use std::vec::Vec;
+use nalgebra::Vector3;
+use super::Particle;
+use super::geometry::{center_of_geometry, principal_axes};
+use super::neighbor::CellList;
+use super::pdb::{AtomData, ResidueData, ResidueId, ResidueIterator, classify};
+use super::pdb::residue::one_letter_code;
+
+/// Donor-acceptor distance of hydrogen bonds in base pairs.
+pub const HBOND_CUTOFF: f64 = 3.5;
+/// Bases are stacked if their centers are closer than this,
+pub const STACK_DISTANCE: f64 = 4.5;
+/// their planes make an angle smaller than this in degrees,
+pub const STACK_ANGLE: f64 = 30.0;
+/// and the line between the centers is within this angle from the normal
+/// of either base.
+pub const STACK_OFFSET: f64 = 40.0;
+
+/// Parts of a nucleotide.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Group {
+    Phosphate,
+    Sugar,
+    Base,
+}
+
+const PHOSPHATE: &'static [&'static str] = &["P", "OP1", "OP2", "OP3", "O5'", "HOP2", "HOP3"];
+
+/// Converts old atom names, `C1*` or `O1P`, into current ones.
+pub fn normalize(atom_name: &str) -> String {
+    match atom_name {
+        "O1P" => "OP1".to_owned(),
+        "O2P" => "OP2".to_owned(),
+        "O3P" => "OP3".to_owned(),
+        _ => atom_name.replace('*', "'"),
+    }
+}
+
+/// The group of an atom of a nucleotide: atoms with primes other than
+/// those of the phosphate are of the sugar, and the rest are of the base.
+pub fn group(atom_name: &str) -> Group {
+    let name = normalize(atom_name);
+    if PHOSPHATE.contains(&name.as_str()) {
+        Group::Phosphate
+    } else if name.contains('\'') {
+        Group::Sugar
+    } else {
+        Group::Base
+    }
+}
+
+/// How two bases are paired.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PairKind {
+    /// A-T, A-U and G-C by the Watson-Crick edges.
+    WatsonCrick,
+    /// A-T, A-U and G-C+ with the purine flipped to its Hoogsteen edge.
+    Hoogsteen,
+}
+
+impl fmt::Display for PairKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PairKind::WatsonCrick => write!(f, "watson-crick"),
+            PairKind::Hoogsteen   => write!(f, "hoogsteen"),
+        }
+    }
+}
+
+/// Hydrogen bonds between a purine and a pyrimidine in each pair.
+fn hbonds(purine: char, pyrimidine: char, kind: PairKind)
+    -> &'static [(&'static str, &'static str)] {
+    match (purine, pyrimidine, kind) {
+        ('A', 'T', PairKind::WatsonCrick) | ('A', 'U', PairKind::WatsonCrick) =>
+            &[("N1", "N3"), ("N6", "O4")],
+        ('G', 'C', PairKind::WatsonCrick) => &[("N1", "N3"), ("N2", "O2"), ("O6", "N4")],
+        ('A', 'T', PairKind::Hoogsteen) | ('A', 'U', PairKind::Hoogsteen) =>
+            &[("N7", "N3"), ("N6", "O4")],
+        ('G', 'C', PairKind::Hoogsteen) => &[("N7", "N3"), ("O6", "N4")],
+        _ => &[],
+    }
+}
+
+/// A base of a nucleotide with the positions of its heavy atoms.
+struct Base {
+    residue : ResidueId,
+    code    : char,
+    atoms   : Vec<(String, Vector3<f64>)>,
+    center  : Vector3<f64>,
+    normal  : Vector3<f64>,
+}
+
+impl Base {
+    fn find(&self, name: &str) -> Option<Vector3<f64>> {
+        self.atoms.iter().find(|a| a.0 == name).map(|a| a.1)
+    }
+}
+
+fn bases<T: AtomData + Particle>(atoms: &[T]) -> Vec<Base> {
+    ResidueIterator::from_slice(atoms).filter_map(|residue| {
+        if !classify(residue.residue_name()).is_nucleotide() {
+            return None;
+        }
+        let code = match one_letter_code(residue.residue_name()) {
+            Some(c) => c,
+            None => return None,
+        };
+        let heavy: Vec<(String, Vector3<f64>)> = residue.atoms.iter().filter(|a| {
+            group(a.atom_name()) == Group::Base &&
+                !a.atom_name().trim_start_matches(|c: char| c.is_digit(10)).starts_with('H')
+        }).map(|a| (normalize(a.atom_name()), a.vec())).collect();
+        // three atoms at least to define the plane
+        if heavy.len() < 3 {
+            return None;
+        }
+        let positions: Vec<Vector3<f64>> = heavy.iter().map(|a| a.1).collect();
+        let center = center_of_geometry(positions.iter()).unwrap();
+        let (_, axes) = principal_axes(&positions, &vec![1.0; positions.len()]).unwrap();
+        let normal = Vector3::new(axes[(0, 2)], axes[(1, 2)], axes[(2, 2)]);
+        Some(Base{residue: residue.residue_id(), code: code, atoms: heavy,
+                  center: center, normal: normal})
+    }).collect()
+}
+
+/// A pair of bases. `lhs` is the purine.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BasePair {
+    pub lhs  : ResidueId,
+    pub rhs  : ResidueId,
+    pub kind : PairKind,
+    /// Distances of the hydrogen bonds.
+    pub hbonds : Vec<f64>,
+}
+
+/// Base pairs whose hydrogen bonds are all shorter than `cutoff`. A pair is
+/// tested for the Watson-Crick edges first. A base may be in several pairs,
+/// e.g. in a triplex.
+pub fn base_pairs<T: AtomData + Particle>(atoms: &[T], cutoff: f64) -> Vec<BasePair> {
+    let bases = bases(atoms);
+    let centers: Vec<Vector3<f64>> = bases.iter().map(|b| b.center).collect();
+    // centers of paired bases are about 6 A apart
+    let cells = CellList::new(&centers, 12.0);
+
+    let mut pairs = Vec::new();
+    for (i, j, _) in cells.pairs(12.0) {
+        let (purine, pyrimidine) = match (bases[i].code, bases[j].code) {
+            ('A', _) | ('G', _) => (&bases[i], &bases[j]),
+            (_, 'A') | (_, 'G') => (&bases[j], &bases[i]),
+            _ => continue,
+        };
+        for kind in [PairKind::WatsonCrick, PairKind::Hoogsteen].iter() {
+            let bonds = hbonds(purine.code, pyrimidine.code, *kind);
+            if bonds.is_empty() {
+                continue;
+            }
+            let distances: Option<Vec<f64>> = bonds.iter().map(|&(p, q)| {
+                match (purine.find(p), pyrimidine.find(q)) {
+                    (Some(a), Some(b)) => Some((a - b).norm()),
+                    _ => None,
+                }
+            }).collect();
+            if let Some(distances) = distances {
+                if distances.iter().all(|d| *d <= cutoff) {
+                    pairs.push(BasePair{lhs: purine.residue, rhs: pyrimidine.residue,
+                                        kind: *kind, hbonds: distances});
+                    break;
+                }
+            }
+        }
+    }
+    pairs.sort_by(|a, b| (a.lhs, a.rhs).cmp(&(b.lhs, b.rhs)));
+    pairs
+}
+
+/// A pair of stacked bases.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stacking {
+    pub lhs      : ResidueId,
+    pub rhs      : ResidueId,
+    /// Between the centers of the bases.
+    pub distance : f64,
+    /// Between the base planes in degrees.
+    pub angle    : f64,
+}
+
+/// Stacked bases; see `STACK_DISTANCE`, `STACK_ANGLE` and `STACK_OFFSET`.
+/// Each pair is ordered as in the atoms.
+pub fn stackings<T: AtomData + Particle>(atoms: &[T]) -> Vec<Stacking> {
+    let bases = bases(atoms);
+    let centers: Vec<Vector3<f64>> = bases.iter().map(|b| b.center).collect();
+    let cells = CellList::new(&centers, STACK_DISTANCE);
+    let angle = |a: &Vector3<f64>, b: &Vector3<f64>| {
+        // planes have no direction
+        (a.dot(b).abs() / (a.norm() * b.norm())).min(1.0).acos().to_degrees()
+    };
+    let mut stacks = Vec::new();
+    for (i, j, distance) in cells.pairs(STACK_DISTANCE) {
+        let (lhs, rhs) = (&bases[i], &bases[j]);
+        let planes = angle(&lhs.normal, &rhs.normal);
+        let offset = rhs.center - lhs.center;
+        if planes <= STACK_ANGLE && (angle(&lhs.normal, &offset) <= STACK_OFFSET ||
+                                     angle(&rhs.normal, &offset) <= STACK_OFFSET) {
+            stacks.push(Stacking{lhs: lhs.residue, rhs: rhs.residue,
+                                 distance: distance, angle: planes});
+        }
+    }
+    stacks
+}
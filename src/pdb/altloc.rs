@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::vec::Vec;
+use super::{Model, Structure};
+use super::{AtomData, AtomDataMut};
+
+// ---------------------------------- ALTLOC -----------------------------------
+
+/// How to resolve alternate locations.
+///
+/// A conformer is chosen for each residue (chain ID, residue number and
+/// insertion code), so that atoms in one residue never come from different
+/// conformers. Atoms without alternate location are always kept.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AltlocPolicy {
+    /// The conformer that has the highest occupancy. The first one wins a tie.
+    HighestOccupancy,
+    /// The conformer that appears first.
+    First,
+    /// The conformer with this ID. If a residue does not have it, the first
+    /// conformer is kept instead.
+    Keep(char),
+    /// All the conformers, each as a separate set of atoms.
+    All,
+}
+
+impl FromStr for AltlocPolicy {
+    type Err = String;
+
+    /// Parses `highest`, `first`, `all` or a one-letter altloc ID.
+    fn from_str(s: &str) -> Result<AltlocPolicy, String> {
+        match s {
+            "highest" => Ok(AltlocPolicy::HighestOccupancy),
+            "first"   => Ok(AltlocPolicy::First),
+            "all"     => Ok(AltlocPolicy::All),
+            _ if s.len() == 1 => Ok(AltlocPolicy::Keep(s.chars().next().unwrap())),
+            _ => Err(format!("unknown altloc policy: {}", s)),
+        }
+    }
+}
+
+type ResidueKey = (char, i32, char);
+
+fn residue_key<T: AtomData>(atom: &T) -> ResidueKey {
+    (atom.chain_id(), atom.residue_number(), atom.insertion_code())
+}
+
+/// Altloc IDs that appear in the atoms, in order of appearance.
+pub fn altloc_ids<T: AtomData>(atoms: &[T]) -> Vec<char> {
+    let mut ids = Vec::new();
+    for atom in atoms.iter() {
+        let alt = atom.alternate_location();
+        if alt != ' ' && !ids.contains(&alt) {
+            ids.push(alt);
+        }
+    }
+    ids
+}
+
+fn choose<T: AtomData>(atoms: &[T], policy: AltlocPolicy) -> HashMap<ResidueKey, char> {
+    // altloc IDs in order of appearance and their highest occupancy
+    let mut found: HashMap<ResidueKey, Vec<(char, f64)>> = HashMap::new();
+    for atom in atoms.iter().filter(|a| a.alternate_location() != ' ') {
+        let alts = found.entry(residue_key(atom)).or_insert(Vec::new());
+        let alt  = atom.alternate_location();
+        match alts.iter().position(|&(id, _)| id == alt) {
+            Some(idx) => if alts[idx].1 < atom.occupancy() {
+                alts[idx].1 = atom.occupancy();
+            },
+            None => alts.push((alt, atom.occupancy())),
+        }
+    }
+    found.into_iter().map(|(key, alts)| {
+        let chosen = match policy {
+            AltlocPolicy::HighestOccupancy => {
+                let mut best = alts[0];
+                for alt in alts.iter().skip(1) {
+                    if alt.1 > best.1 {
+                        best = *alt;
+                    }
+                }
+                best.0
+            }
+            AltlocPolicy::Keep(id) if alts.iter().any(|a| a.0 == id) => id,
+            _ => alts[0].0,
+        };
+        (key, chosen)
+    }).collect()
+}
+
+/// Keeps one conformer per residue as `policy` says and clears altloc IDs
+/// of the kept atoms. `AltlocPolicy::All` is treated as `First`;
+/// use `conformers` to get all of them.
+pub fn resolve_altlocs<T: AtomDataMut + Clone>(atoms: &[T], policy: AltlocPolicy)
+    -> Vec<T> {
+    let chosen = choose(atoms, policy);
+    atoms.iter().filter(|a| {
+        let alt = a.alternate_location();
+        alt == ' ' || chosen.get(&residue_key(*a)) == Some(&alt)
+    }).map(|a| {
+        let mut atom = a.clone();
+        atom.set_alternate_location(' ');
+        atom
+    }).collect()
+}
+
+/// Splits atoms into conformer sets, one for each altloc ID. Each set has
+/// all the atoms without altloc. If there is no altloc, one set with ' '
+/// is returned.
+pub fn conformers<T: AtomDataMut + Clone>(atoms: &[T]) -> Vec<(char, Vec<T>)> {
+    let ids = altloc_ids(atoms);
+    if ids.is_empty() {
+        return vec![(' ', atoms.to_vec())];
+    }
+    ids.into_iter()
+       .map(|id| (id, resolve_altlocs(atoms, AltlocPolicy::Keep(id))))
+       .collect()
+}
+
+fn resolve_model(model: &Model, policy: AltlocPolicy) -> Model {
+    Model{
+        serial  : model.serial,
+        atoms   : resolve_altlocs(&model.atoms,   policy),
+        hetatms : resolve_altlocs(&model.hetatms, policy),
+        ters    : model.ters.clone(),
+    }
+}
+
+/// Applies `policy` to all the models. With `AltlocPolicy::All`, each model
+/// is replaced by one model per conformer, numbered sequentially.
+pub fn resolve_structure(structure: &Structure, policy: AltlocPolicy) -> Structure {
+    let mut resolved = Structure{
+        header: structure.header.clone(), models: Vec::new(), bonds: structure.bonds.clone()
+    };
+    for model in structure.models.iter() {
+        if policy != AltlocPolicy::All {
+            resolved.models.push(resolve_model(model, policy));
+            continue;
+        }
+        let mut ids = altloc_ids(&model.atoms);
+        for id in altloc_ids(&model.hetatms) {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        if ids.is_empty() {
+            ids.push(' ');
+        }
+        for id in ids {
+            let mut conformer = resolve_model(model, AltlocPolicy::Keep(id));
+            conformer.serial = resolved.models.len() as i32 + 1;
+            resolved.models.push(conformer);
+        }
+    }
+    resolved
+}
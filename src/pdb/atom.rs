@@ -6,7 +6,9 @@ use std::str::FromStr;
 use std::str;
 use std::fmt;
 use super::Particle;
+use super::ParticleMut;
 use super::AtomData;
+use super::AtomDataMut;
 
 // ------------------------------------ ATOM -----------------------------------
 
@@ -44,6 +46,20 @@ impl AtomData for Atom {
     fn charge(&self)             -> &str {self.charge.as_str()}
 }
 
+impl AtomDataMut for Atom {
+    fn set_atom_number(&mut self, an: i32)          {self.serial = an;}
+    fn set_atom_name(&mut self, atm: &str)          {self.name = ArrayString::from(atm).unwrap();}
+    fn set_alternate_location(&mut self, alt: char) {self.altloc = alt as u8;}
+    fn set_residue_name(&mut self, res: &str)       {self.resname = ArrayString::from(res).unwrap();}
+    fn set_chain_id(&mut self, ch: char)            {self.chainid = ch as u8;}
+    fn set_residue_number(&mut self, rn: i32)       {self.resseq = rn;}
+    fn set_insertion_code(&mut self, icd: char)     {self.icode = icd as u8;}
+    fn set_occupancy(&mut self, occ: f64)           {self.occupancy = occ;}
+    fn set_temperature_factor(&mut self, tfc: f64)  {self.tempfactor = tfc;}
+    fn set_element_symbol(&mut self, elm: &str)     {self.element = ArrayString::from(elm).unwrap();}
+    fn set_charge(&mut self, chg: &str)             {self.charge = ArrayString::from(chg).unwrap();}
+}
+
 impl Particle for Atom {
     fn x(&self) -> f64 {self.x}
     fn y(&self) -> f64 {self.y}
@@ -56,6 +72,14 @@ impl Particle for Atom {
     }
 }
 
+impl ParticleMut for Atom {
+    fn set_vec(&mut self, v: Vector3<f64>) {
+        self.x = v[0];
+        self.y = v[1];
+        self.z = v[2];
+    }
+}
+
 impl FromStr for Atom {
     type Err = String; // TODO!!!
     fn from_str(line: &str) -> Result<Self, Self::Err> {
@@ -0,0 +1,328 @@
+use nalgebra::Vector3;
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::collections::btree_set;
+use std::vec::Vec;
+use std::fmt;
+use super::super::Particle;
+use super::super::element;
+use super::super::neighbor::CellList;
+use super::{AtomData, Model};
+
+// ----------------------------------- BOND ------------------------------------
+
+/// Covalent bonds between atoms identified by their serial numbers,
+/// as written in CONECT records.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct BondGraph {
+    bonds    : BTreeSet<(i32, i32)>,
+    /// The bonds with the larger serial number first, to find neighbors.
+    reversed : BTreeSet<(i32, i32)>,
+}
+
+impl BondGraph {
+    pub fn new() -> BondGraph {
+        BondGraph{bonds: BTreeSet::new(), reversed: BTreeSet::new()}
+    }
+
+    /// Adds a bond. The order of atoms does not matter.
+    pub fn add(&mut self, lhs: i32, rhs: i32) {
+        if lhs != rhs {
+            self.bonds.insert((lhs.min(rhs), lhs.max(rhs)));
+            self.reversed.insert((lhs.max(rhs), lhs.min(rhs)));
+        }
+    }
+
+    pub fn contains(&self, lhs: i32, rhs: i32) -> bool {
+        if lhs < rhs {
+            self.bonds.contains(&(lhs, rhs))
+        } else {
+            self.bonds.contains(&(rhs, lhs))
+        }
+    }
+
+    pub fn len(&self)      -> usize {self.bonds.len()}
+    pub fn is_empty(&self) -> bool  {self.bonds.is_empty()}
+
+    /// Bonds as pairs of serial numbers; the smaller one comes first.
+    pub fn iter(&self) -> btree_set::Iter<(i32, i32)> {
+        self.bonds.iter()
+    }
+
+    /// Serial numbers of the atoms bonded to `serial`, in ascending order.
+    pub fn neighbors(&self, serial: i32) -> Vec<i32> {
+        let range = (serial, i32::MIN)..=(serial, i32::MAX);
+        self.reversed.range(range.clone()).chain(self.bonds.range(range)).map(|b| b.1).collect()
+    }
+
+    /// Bonds with serial numbers replaced as `serials` says. Bonds of atoms
+    /// not in `serials` are removed.
+    pub fn renumbered(&self, serials: &HashMap<i32, i32>) -> BondGraph {
+        let mut graph = BondGraph::new();
+        for &(l, r) in self.bonds.iter() {
+            if let (Some(l), Some(r)) = (serials.get(&l), serials.get(&r)) {
+                graph.add(*l, *r);
+            }
+        }
+        graph
+    }
+
+    /// Keeps bonds between atoms for which `f` returns true.
+    pub fn retain<F: Fn(i32) -> bool>(&mut self, f: F) {
+        self.bonds = self.bonds.iter().filter(|&&(l, r)| f(l) && f(r)).cloned().collect();
+        self.reversed = self.bonds.iter().map(|&(l, r)| (r, l)).collect();
+    }
+}
+
+impl fmt::Display for BondGraph {
+    /// Writes CONECT records. Each atom lists all of its partners, four per
+    /// line. Each line is terminated by a newline.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut partners: HashMap<i32, Vec<i32>> = HashMap::new();
+        for &(l, r) in self.bonds.iter() {
+            partners.entry(l).or_insert(Vec::new()).push(r);
+            partners.entry(r).or_insert(Vec::new()).push(l);
+        }
+        let mut serials: Vec<i32> = partners.keys().cloned().collect();
+        serials.sort();
+        for serial in serials {
+            let mut bonded = partners[&serial].clone();
+            bonded.sort();
+            for chunk in bonded.chunks(4) {
+                try!(write!(f, "CONECT{:5}", serial));
+                for b in chunk {
+                    try!(write!(f, "{:5}", b));
+                }
+                try!(writeln!(f, "{:width$}", "", width = 69 - 5 * chunk.len()));
+            }
+        }
+        Ok(())
+    }
+}
+
+// --------------------------------- templates ---------------------------------
+
+const BACKBONE: &'static [(&'static str, &'static str)] = &[
+    ("N", "CA"), ("CA", "C"), ("C", "O"), ("C", "OXT"),
+];
+
+const NUCLEOTIDE_BACKBONE: &'static [(&'static str, &'static str)] = &[
+    ("P", "OP1"), ("P", "OP2"), ("P", "O1P"), ("P", "O2P"), ("P", "O5'"),
+    ("O5'", "C5'"), ("C5'", "C4'"), ("C4'", "O4'"), ("C4'", "C3'"),
+    ("C3'", "O3'"), ("C3'", "C2'"), ("C2'", "C1'"), ("C1'", "O4'"),
+    ("C2'", "O2'"),
+];
+
+const PURINE: &'static [(&'static str, &'static str)] = &[
+    ("C1'", "N9"), ("N9", "C8"), ("C8", "N7"), ("N7", "C5"), ("C5", "C6"),
+    ("C6", "N1"), ("N1", "C2"), ("C2", "N3"), ("N3", "C4"), ("C4", "C5"),
+    ("C4", "N9"),
+];
+
+const PYRIMIDINE: &'static [(&'static str, &'static str)] = &[
+    ("C1'", "N1"), ("N1", "C2"), ("C2", "O2"), ("C2", "N3"), ("N3", "C4"),
+    ("C4", "C5"), ("C5", "C6"), ("C6", "N1"),
+];
+
+/// Bonds between heavy atoms in a standard residue, except the backbone.
+fn side_chain(residue: &str) -> Option<&'static [(&'static str, &'static str)]> {
+    Some(match residue {
+        "ALA" => &[("CA", "CB")],
+        "ARG" => &[("CA", "CB"), ("CB", "CG"), ("CG", "CD"), ("CD", "NE"),
+                   ("NE", "CZ"), ("CZ", "NH1"), ("CZ", "NH2")],
+        "ASN" => &[("CA", "CB"), ("CB", "CG"), ("CG", "OD1"), ("CG", "ND2")],
+        "ASP" | "ASH" => &[("CA", "CB"), ("CB", "CG"), ("CG", "OD1"), ("CG", "OD2")],
+        "CYS" | "CYX" => &[("CA", "CB"), ("CB", "SG")],
+        "GLN" => &[("CA", "CB"), ("CB", "CG"), ("CG", "CD"), ("CD", "OE1"), ("CD", "NE2")],
+        "GLU" | "GLH" => &[("CA", "CB"), ("CB", "CG"), ("CG", "CD"), ("CD", "OE1"),
+                           ("CD", "OE2")],
+        "GLY" => &[],
+        "HIS" | "HID" | "HIE" | "HIP" | "HSD" | "HSE" | "HSP" =>
+                 &[("CA", "CB"), ("CB", "CG"), ("CG", "ND1"), ("CG", "CD2"),
+                   ("ND1", "CE1"), ("CD2", "NE2"), ("CE1", "NE2")],
+        "ILE" => &[("CA", "CB"), ("CB", "CG1"), ("CB", "CG2"), ("CG1", "CD1")],
+        "LEU" => &[("CA", "CB"), ("CB", "CG"), ("CG", "CD1"), ("CG", "CD2")],
+        "LYS" | "LYN" => &[("CA", "CB"), ("CB", "CG"), ("CG", "CD"), ("CD", "CE"),
+                           ("CE", "NZ")],
+        "MET" => &[("CA", "CB"), ("CB", "CG"), ("CG", "SD"), ("SD", "CE")],
+        "MSE" => &[("CA", "CB"), ("CB", "CG"), ("CG", "SE"), ("SE", "CE")],
+        "PHE" => &[("CA", "CB"), ("CB", "CG"), ("CG", "CD1"), ("CG", "CD2"),
+                   ("CD1", "CE1"), ("CD2", "CE2"), ("CE1", "CZ"), ("CE2", "CZ")],
+        "PRO" => &[("CA", "CB"), ("CB", "CG"), ("CG", "CD"), ("CD", "N")],
+        "SER" => &[("CA", "CB"), ("CB", "OG")],
+        "THR" => &[("CA", "CB"), ("CB", "OG1"), ("CB", "CG2")],
+        "TRP" => &[("CA", "CB"), ("CB", "CG"), ("CG", "CD1"), ("CG", "CD2"),
+                   ("CD1", "NE1"), ("NE1", "CE2"), ("CD2", "CE2"), ("CD2", "CE3"),
+                   ("CE2", "CZ2"), ("CE3", "CZ3"), ("CZ2", "CH2"), ("CZ3", "CH2")],
+        "TYR" => &[("CA", "CB"), ("CB", "CG"), ("CG", "CD1"), ("CG", "CD2"),
+                   ("CD1", "CE1"), ("CD2", "CE2"), ("CE1", "CZ"), ("CE2", "CZ"),
+                   ("CZ", "OH")],
+        "VAL" => &[("CA", "CB"), ("CB", "CG1"), ("CB", "CG2")],
+        _ => return None,
+    })
+}
+
+/// Bonds of a standard residue. `None` if the residue is not known.
+pub fn template(residue: &str) -> Option<Vec<(&'static str, &'static str)>> {
+    if let Some(side) = side_chain(residue) {
+        return Some(BACKBONE.iter().chain(side.iter()).cloned().collect());
+    }
+    let base: &[(&'static str, &'static str)] = match residue {
+        "A" | "DA" => &[("C6", "N6")],
+        "G" | "DG" => &[("C6", "O6"), ("C2", "N2")],
+        "I" | "DI" => &[("C6", "O6")],
+        "C" | "DC" => &[("C4", "N4")],
+        "U" | "DU" => &[("C4", "O4")],
+        "DT"       => &[("C4", "O4"), ("C5", "C7"), ("C5", "C5M")],
+        _ => return None,
+    };
+    let ring = match residue {
+        "A" | "DA" | "G" | "DG" | "I" | "DI" => PURINE,
+        _ => PYRIMIDINE,
+    };
+    Some(NUCLEOTIDE_BACKBONE.iter().chain(ring.iter()).chain(base.iter()).cloned().collect())
+}
+
+// --------------------------------- inference ---------------------------------
+
+/// Tolerance added to the sum of covalent radii.
+pub const TOLERANCE: f64 = 0.45;
+
+/// Pairs closer than this are not bonded but overlapping.
+const MIN_DISTANCE: f64 = 0.4;
+
+struct AtomInfo {
+    serial   : i32,
+    name     : String,
+    residue  : (char, i32, char, String),
+    hetero   : bool,
+    hydrogen : bool,
+    radius   : f64,
+    pos      : Vector3<f64>,
+}
+
+fn info<T: AtomData + Particle>(atom: &T) -> AtomInfo {
+    let elem = element::of(atom);
+    AtomInfo{
+        serial   : atom.atom_number(),
+        name     : atom.atom_name().to_owned(),
+        residue  : (atom.chain_id(), atom.residue_number(), atom.insertion_code(),
+                    atom.residue_name().to_owned()),
+        hetero   : atom.record_name() == "HETATM",
+        hydrogen : elem.map_or(false, |e| e.atomic_number == 1),
+        radius   : elem.map_or(0.76, |e| e.covalent_radius),
+        pos      : atom.vec(),
+    }
+}
+
+fn is_bonded(lhs: &AtomInfo, rhs: &AtomInfo) -> bool {
+    let d2 = (lhs.pos - rhs.pos).norm_squared();
+    let max = lhs.radius + rhs.radius + TOLERANCE;
+    MIN_DISTANCE * MIN_DISTANCE < d2 && d2 < max * max
+}
+
+/// Infers covalent bonds in a model.
+///
+/// - Standard residues in ATOM records are connected by templates, and
+///   adjacent residues by a peptide (C-N) or phosphodiester (O3'-P) bond
+///   if the atoms are close enough.
+/// - A hydrogen in ATOM records is bonded to the closest heavy atom in the
+///   residue, if it is within the covalent distance.
+/// - HETATMs, atoms in unknown residues or atoms not in templates, and
+///   sulfur atoms (for disulfide bonds), are bonded to any atom within the
+///   sum of covalent radii plus `TOLERANCE`, except hydrogen pairs.
+///
+/// Neighbors are searched using a cell list so that it scales linearly.
+pub fn infer_bonds(model: &Model) -> BondGraph {
+    let atoms: Vec<AtomInfo> = model.atoms.iter().map(|a| info(a))
+        .chain(model.hetatms.iter().map(|a| info(a))).collect();
+    let mut graph = BondGraph::new();
+    if atoms.is_empty() {
+        return graph;
+    }
+    let max_radius = atoms.iter().fold(0.0, |acc: f64, a| acc.max(a.radius));
+    let cutoff = 2.0 * max_radius + TOLERANCE;
+    let positions: Vec<Vector3<f64>> = atoms.iter().map(|a| a.pos).collect();
+    let cells = CellList::new(&positions, cutoff);
+
+    // residues as ranges of contiguous atoms
+    let mut residues: Vec<(usize, usize)> = Vec::new();
+    for i in 0..atoms.len() {
+        if i == 0 || atoms[i].residue != atoms[i-1].residue || atoms[i].hetero != atoms[i-1].hetero {
+            residues.push((i, i + 1));
+        } else {
+            residues.last_mut().unwrap().1 = i + 1;
+        }
+    }
+
+    let mut by_distance = vec![false; atoms.len()];
+    for (r, &(first, last)) in residues.iter().enumerate() {
+        let find = |name: &str| (first..last).find(|i| atoms[*i].name == name);
+        let tmpl = if atoms[first].hetero {None} else {template(&atoms[first].residue.3)};
+        let tmpl = match tmpl {
+            Some(t) => t,
+            None => {
+                for i in first..last {by_distance[i] = true;}
+                continue;
+            }
+        };
+        let mut covered = vec![false; last - first];
+        for &(lhs, rhs) in tmpl.iter() {
+            if let (Some(i), Some(j)) = (find(lhs), find(rhs)) {
+                if is_bonded(&atoms[i], &atoms[j]) || !atoms[i].hydrogen {
+                    graph.add(atoms[i].serial, atoms[j].serial);
+                }
+                covered[i - first] = true;
+                covered[j - first] = true;
+            }
+        }
+        for i in first..last {
+            if atoms[i].hydrogen {
+                let closest = (first..last).filter(|j| !atoms[*j].hydrogen)
+                    .min_by(|j, k| {
+                        let dj = (atoms[*j].pos - atoms[i].pos).norm_squared();
+                        let dk = (atoms[*k].pos - atoms[i].pos).norm_squared();
+                        dj.partial_cmp(&dk).unwrap_or(Ordering::Equal)
+                    });
+                if let Some(j) = closest {
+                    if is_bonded(&atoms[i], &atoms[j]) {
+                        graph.add(atoms[i].serial, atoms[j].serial);
+                    }
+                }
+            } else if !covered[i - first] || atoms[i].name.starts_with('S') {
+                by_distance[i] = true;
+            }
+        }
+
+        // link to the next residue in the same chain
+        if let Some(&(next_first, next_last)) = residues.get(r + 1) {
+            if atoms[next_first].hetero || atoms[next_first].residue.0 != atoms[first].residue.0 {
+                continue;
+            }
+            let find_next = |name: &str| (next_first..next_last).find(|i| atoms[*i].name == name);
+            for &(lhs, rhs) in [("C", "N"), ("O3'", "P")].iter() {
+                if let (Some(i), Some(j)) = (find(lhs), find_next(rhs)) {
+                    if is_bonded(&atoms[i], &atoms[j]) {
+                        graph.add(atoms[i].serial, atoms[j].serial);
+                    }
+                }
+            }
+        }
+    }
+
+    for i in (0..atoms.len()).filter(|i| by_distance[*i]) {
+        for j in cells.within(&atoms[i].pos, cutoff) {
+            if i == j || (atoms[i].hydrogen && atoms[j].hydrogen) {
+                continue;
+            }
+            // a hydrogen in a standard residue already has its partner
+            if atoms[j].hydrogen && !by_distance[j] {
+                continue;
+            }
+            if is_bonded(&atoms[i], &atoms[j]) {
+                graph.add(atoms[i].serial, atoms[j].serial);
+            }
+        }
+    }
+    graph
+}
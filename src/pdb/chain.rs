@@ -0,0 +1,126 @@
+use std::iter::{IntoIterator, FromIterator};
+use std::vec::Vec;
+use super::{AtomData, ChainData, Ter};
+use super::residue::ResidueIterator;
+
+// ----------------------------------- CHAIN -----------------------------------
+
+pub struct Chain<T: AtomData> {
+    pub atoms : Vec<T>,
+    chain_id  : char,
+}
+
+impl<T: AtomData> Chain<T> {
+    pub fn new() -> Self {
+        Chain{atoms: Vec::new(), chain_id: 'A'}
+    }
+
+    /// Collects atoms that have the same chain ID as the first one.
+    pub fn from(v: Vec<T>) -> Option<Self> {
+        if v.is_empty() {
+            None
+        } else {
+            let chid = v.first().unwrap().chain_id();
+            let atoms = v.into_iter().filter(|elem| elem.chain_id() == chid).collect();
+            Some(Chain{atoms: atoms, chain_id: chid})
+        }
+    }
+
+    pub fn residues(&self) -> ResidueIterator<T> {
+        ResidueIterator::from_slice(&self.atoms)
+    }
+}
+
+impl<T: AtomData> ChainData for Chain<T> {
+    fn chain_id(&self) -> char {self.chain_id}
+}
+
+impl<T: AtomData> IntoIterator for Chain<T> {
+    type Item = T;
+    type IntoIter = ::std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.atoms.into_iter()
+    }
+}
+
+impl<T: AtomData> FromIterator<T> for Chain<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let atms = Vec::<T>::from_iter(iter);
+        let chid = atms.first().map_or('A', |a| a.chain_id());
+        Chain{atoms: atms, chain_id: chid}
+    }
+}
+
+pub struct ChainSlice<'a, T: AtomData + 'a> {
+    pub atoms : &'a [T],
+    chain_id  : char,
+}
+
+impl<'a, T: AtomData + 'a> ChainSlice<'a, T> {
+    pub fn residues(&self) -> ResidueIterator<'a, T> {
+        ResidueIterator::from_slice(self.atoms)
+    }
+
+    pub fn to_chain(&self) -> Chain<T> where T: Clone {
+        Chain{atoms: self.atoms.to_vec(), chain_id: self.chain_id}
+    }
+}
+
+impl<'a, T: AtomData + 'a> ChainData for ChainSlice<'a, T> {
+    fn chain_id(&self) -> char {self.chain_id}
+}
+
+/// Iterates chains in a sequence of atoms. A chain ends where the chain ID
+/// changes or where a TER record is placed.
+pub struct ChainIterator<'a, T: AtomData + 'a> {
+    first : usize,
+    ends  : Vec<usize>,
+    atoms : &'a [T],
+}
+
+impl<'a, T: AtomData + 'a> ChainIterator<'a, T> {
+    pub fn new(atoms: &[T]) -> Option<ChainIterator<T>> {
+        ChainIterator::with_ters(atoms, &[])
+    }
+
+    /// A TER splits the atoms before and after it, as determined by the
+    /// serial numbers of the atoms and the TER. Atoms are in ascending order
+    /// of serial numbers.
+    pub fn with_ters(atoms: &'a [T], ters: &[Ter]) -> Option<ChainIterator<'a, T>> {
+        if atoms.is_empty() {
+            return None;
+        }
+        let mut serials: Vec<i32> = ters.iter().map(|t| t.atom_number()).collect();
+        serials.sort();
+        let mut k = 0;
+        let mut ends = Vec::new();
+        for i in 1..atoms.len() {
+            let (prev, next) = (&atoms[i-1], &atoms[i]);
+            while k < serials.len() && serials[k] < prev.atom_number() {
+                k += 1;
+            }
+            let ter = k < serials.len() && serials[k] < next.atom_number();
+            if prev.chain_id() != next.chain_id() || ter {
+                ends.push(i);
+            }
+        }
+        ends.push(atoms.len());
+        ends.reverse();
+        Some(ChainIterator{first: 0, ends: ends, atoms: atoms})
+    }
+}
+
+impl<'a, T: AtomData + 'a> Iterator for ChainIterator<'a, T> {
+    type Item = ChainSlice<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let fst = self.first;
+        let lst = match self.ends.pop() {Some(e) => e, None => return None};
+        self.first = lst;
+        Some(ChainSlice{
+            atoms    : &self.atoms[fst .. lst],
+            chain_id : self.atoms[fst].chain_id(),
+        })
+    }
+}
@@ -0,0 +1,292 @@
+use std::string::String;
+use std::vec::Vec;
+use std::fmt;
+use super::symmetry::{Biomolecule, AssemblyGroup, NcsOperator, Transform, UnitCell};
+use super::symmetry::{read_operator_row, push_operator_row};
+
+// ----------------------------------- HEADER ----------------------------------
+
+/// A residue listed in REMARK 465 (missing residues).
+#[derive(Clone, Debug, PartialEq)]
+pub struct MissingResidue {
+    pub model          : Option<i32>,
+    pub residue_name   : String,
+    pub chain_id       : char,
+    pub residue_number : i32,
+    pub insertion_code : char,
+}
+
+/// Metadata collected from the title and crystallographic sections of a
+/// PDB file.
+///
+/// HEADER, TITLE, COMPND, SOURCE and EXPDTA are stored as text (continuation
+/// lines are joined), REMARK 2/3/290/350/465, CRYST1 and MTRIX are parsed
+/// into typed fields. All the REMARK lines are kept verbatim in `remarks` so
+/// that the writer can put them back as they were.
+#[derive(Clone, Debug)]
+pub struct Header {
+    pub classification      : String,
+    pub deposition_date     : String,
+    pub id_code             : String,
+    pub title               : String,
+    pub compound            : String,
+    pub source              : String,
+    pub experimental_method : String,
+    pub resolution          : Option<f64>,
+    pub r_work              : Option<f64>,
+    pub r_free              : Option<f64>,
+    pub missing_residues    : Vec<MissingResidue>,
+    pub crystal_symmetry    : Vec<Transform>,
+    pub biomolecules        : Vec<Biomolecule>,
+    pub remarks             : Vec<String>,
+    pub unit_cell           : Option<UnitCell>,
+    pub ncs_operators       : Vec<NcsOperator>,
+}
+
+impl Header {
+    pub fn new() -> Header {
+        Header{
+            classification      : String::new(),
+            deposition_date     : String::new(),
+            id_code             : String::new(),
+            title               : String::new(),
+            compound            : String::new(),
+            source              : String::new(),
+            experimental_method : String::new(),
+            resolution          : None,
+            r_work              : None,
+            r_free              : None,
+            missing_residues    : Vec::new(),
+            crystal_symmetry    : Vec::new(),
+            biomolecules        : Vec::new(),
+            remarks             : Vec::new(),
+            unit_cell           : None,
+            ncs_operators       : Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.classification.is_empty() && self.id_code.is_empty() &&
+        self.title.is_empty() && self.compound.is_empty() &&
+        self.source.is_empty() && self.experimental_method.is_empty() &&
+        self.remarks.is_empty() && self.unit_cell.is_none() &&
+        self.ncs_operators.is_empty()
+    }
+
+    /// Reads one line of the title section. Returns false if the line is not
+    /// a record handled by `Header`.
+    pub fn read_line(&mut self, line: &str) -> bool {
+        if !line.is_ascii() {
+            return false;
+        }
+        let record = if line.len() >= 6 {&line[0..6]} else {line};
+        match record.trim_end() {
+            "HEADER" => {
+                self.classification  = columns(line, 10, 50).to_owned();
+                self.deposition_date = columns(line, 50, 59).to_owned();
+                self.id_code         = columns(line, 62, 66).to_owned();
+            }
+            "TITLE"  => append_continued(&mut self.title,    columns(line, 10, 80)),
+            "COMPND" => append_continued(&mut self.compound, columns(line, 10, 80)),
+            "SOURCE" => append_continued(&mut self.source,   columns(line, 10, 80)),
+            "EXPDTA" => append_continued(&mut self.experimental_method,
+                                         columns(line, 10, 79)),
+            "REMARK" => {
+                self.read_remark(line);
+                self.remarks.push(line.trim_end().to_owned());
+            }
+            "CRYST1" => {
+                self.unit_cell = UnitCell::from_line(line).ok();
+            }
+            "MTRIX1" | "MTRIX2" | "MTRIX3" => {
+                if let Some((row, serial, values)) = read_operator_row(line, "MTRIX") {
+                    if row == 0 {
+                        self.ncs_operators.push(NcsOperator{
+                            serial: serial, transform: Transform::identity(), given: false
+                        });
+                    }
+                    if let Some(ncs) = self.ncs_operators.last_mut() {
+                        ncs.transform.set_row(row, &values);
+                        ncs.given = columns(line, 59, 60) == "1";
+                    }
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn read_remark(&mut self, line: &str) {
+        let number = match columns(line, 7, 10).parse::<i32>() {
+            Ok(n) => n, Err(_) => return
+        };
+        let text = columns(line, 10, 80);
+        match number {
+            2 => {
+                if text.starts_with("RESOLUTION.") {
+                    self.resolution = text["RESOLUTION.".len()..]
+                        .split_whitespace().next()
+                        .and_then(|s| s.parse::<f64>().ok());
+                }
+            }
+            3 => {
+                if let Some(colon) = text.find(':') {
+                    let key   = text[..colon].split_whitespace()
+                                    .collect::<Vec<_>>().join(" ");
+                    let value = text[colon+1..].trim().parse::<f64>().ok();
+                    if key == "R VALUE (WORKING SET)" {
+                        self.r_work = value;
+                    } else if key == "FREE R VALUE" {
+                        self.r_free = value;
+                    }
+                }
+            }
+            290 => {
+                if let Some((row, _, values)) = read_operator_row(text, "SMTRY") {
+                    push_operator_row(&mut self.crystal_symmetry, row, &values);
+                }
+            }
+            350 => self.read_remark350(text),
+            465 => {
+                if let Some(res) = missing_residue(text) {
+                    self.missing_residues.push(res);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn read_remark350(&mut self, text: &str) {
+        if text.starts_with("BIOMOLECULE:") {
+            if let Ok(id) = text["BIOMOLECULE:".len()..].trim().parse::<i32>() {
+                self.biomolecules.push(Biomolecule{id: id, groups: Vec::new()});
+            }
+            return;
+        }
+        let biomolecule = match self.biomolecules.last_mut() {
+            Some(b) => b, None => return
+        };
+        if let Some(colon) = text.find("CHAINS:") {
+            if text.starts_with("APPLY THE FOLLOWING TO") {
+                biomolecule.groups.push(AssemblyGroup{
+                    chains: Vec::new(), operators: Vec::new()
+                });
+            }
+            if let Some(group) = biomolecule.groups.last_mut() {
+                group.chains.extend(text[colon + "CHAINS:".len()..].split(',')
+                    .map(|c| c.trim()).filter(|c| c.len() == 1)
+                    .map(|c| c.chars().next().unwrap()));
+            }
+        } else if let Some((row, _, values)) = read_operator_row(text, "BIOMT") {
+            if let Some(group) = biomolecule.groups.last_mut() {
+                push_operator_row(&mut group.operators, row, &values);
+            }
+        }
+    }
+}
+
+/// Parses a data line of REMARK 465, like `  MET A     1` or `1 MET A 1A`.
+fn missing_residue(text: &str) -> Option<MissingResidue> {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let (model, rest) = match tokens.len() {
+        3 => (None, &tokens[..]),
+        4 => match tokens[0].parse::<i32>() {
+            Ok(m) => (Some(m), &tokens[1..]), Err(_) => return None
+        },
+        _ => return None,
+    };
+    if rest[0].len() > 3 || rest[1].len() != 1 {
+        return None;
+    }
+    let seq = rest[2];
+    let (number, icode) = match seq.chars().last() {
+        Some(c) if c.is_alphabetic() => (&seq[..seq.len()-1], c),
+        Some(_) => (seq, ' '),
+        None    => return None,
+    };
+    let number = match number.parse::<i32>() {Ok(n) => n, Err(_) => return None};
+    Some(MissingResidue{
+        model          : model,
+        residue_name   : rest[0].to_owned(),
+        chain_id       : rest[1].chars().next().unwrap(),
+        residue_number : number,
+        insertion_code : icode,
+    })
+}
+
+fn columns(line: &str, first: usize, last: usize) -> &str {
+    if line.len() <= first {
+        ""
+    } else if line.len() < last {
+        line[first..].trim()
+    } else {
+        line[first..last].trim()
+    }
+}
+
+fn append_continued(text: &mut String, part: &str) {
+    if !text.is_empty() && !part.is_empty() {
+        text.push(' ');
+    }
+    text.push_str(part);
+}
+
+/// Splits `text` at whitespaces into chunks not longer than `width`.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line  = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.len() + 1 + word.len() > width {
+            lines.push(line);
+            line = String::new();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn write_continued(f: &mut fmt::Formatter, record: &str, text: &str)
+    -> fmt::Result {
+    for (i, line) in wrap(text, 69).iter().enumerate() {
+        if i == 0 {
+            try!(writeln!(f, "{:<6}    {:<70}", record, line));
+        } else {
+            try!(writeln!(f, "{:<6}{:>4} {:<69}", record, i + 1, line));
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Display for Header {
+    /// Writes the title section. Each line is terminated by a newline.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.classification.is_empty() || !self.deposition_date.is_empty() ||
+           !self.id_code.is_empty() {
+            try!(writeln!(f, "HEADER    {:<40}{:<9}   {:<4}              ",
+                          self.classification, self.deposition_date,
+                          self.id_code));
+        }
+        try!(write_continued(f, "TITLE",  &self.title));
+        try!(write_continued(f, "COMPND", &self.compound));
+        try!(write_continued(f, "SOURCE", &self.source));
+        if !self.experimental_method.is_empty() {
+            try!(writeln!(f, "EXPDTA    {:<70}", self.experimental_method));
+        }
+        for remark in self.remarks.iter() {
+            try!(writeln!(f, "{:<80}", remark));
+        }
+        if let Some(ref cell) = self.unit_cell {
+            try!(writeln!(f, "{}", cell));
+        }
+        for ncs in self.ncs_operators.iter() {
+            try!(writeln!(f, "{}", ncs));
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 /// module pdb: read pdb file
-use super::Particle;
+use super::{Particle, ParticleMut};
 // use super::{Particle, Snapshot, Trajectory};
 
 pub trait AtomData {
@@ -17,9 +17,24 @@ pub trait AtomData {
     fn charge(&self)             -> &str;
 }
 
+pub trait AtomDataMut : AtomData {
+    fn set_atom_number(&mut self, an: i32);
+    fn set_atom_name(&mut self, atm: &str);
+    fn set_alternate_location(&mut self, alt: char);
+    fn set_residue_name(&mut self, res: &str);
+    fn set_chain_id(&mut self, ch: char);
+    fn set_residue_number(&mut self, rn: i32);
+    fn set_insertion_code(&mut self, icd: char);
+    fn set_occupancy(&mut self, occ: f64);
+    fn set_temperature_factor(&mut self, tfc: f64);
+    fn set_element_symbol(&mut self, elm: &str);
+    fn set_charge(&mut self, chg: &str);
+}
+
 pub trait ResidueData {
     fn residue_name(&self)   -> &str;
     fn residue_number(&self) -> i32;
+    fn insertion_code(&self) -> char;
     fn chain_id(&self)       -> char;
 }
 
@@ -46,12 +61,50 @@ pub mod reader;
 pub use self::reader::Record;
 pub use self::reader::Reader;
 
+pub mod header;
+pub use self::header::Header;
+pub use self::header::MissingResidue;
+
+pub mod symmetry;
+pub use self::symmetry::Transform;
+pub use self::symmetry::UnitCell;
+pub use self::symmetry::Biomolecule;
+pub use self::symmetry::biological_assembly;
+pub use self::symmetry::expand_ncs;
+pub use self::symmetry::symmetry_mates;
+
+pub mod structure;
+pub use self::structure::Model;
+pub use self::structure::Structure;
+
+pub mod writer;
+pub use self::writer::Writer;
+
+pub mod altloc;
+pub use self::altloc::AltlocPolicy;
+pub use self::altloc::resolve_altlocs;
+pub use self::altloc::conformers;
+
 pub mod residue;
 pub use self::residue::Residue;
+pub use self::residue::ResidueId;
 pub use self::residue::ResidueSlice;
 pub use self::residue::ResidueIterator;
-// pub mod chain;
-// pub use self::chain::Chain;
+pub use self::residue::ResidueKind;
+pub use self::residue::classify;
+
+pub mod chain;
+pub use self::chain::Chain;
+pub use self::chain::ChainSlice;
+pub use self::chain::ChainIterator;
+
+pub mod bond;
+pub use self::bond::BondGraph;
+pub use self::bond::infer_bonds;
+
+pub mod summary;
+pub use self::summary::Summary;
+pub use self::summary::ChainSummary;
 
 // /// pdb::Residue
 // /// pdb::Chain
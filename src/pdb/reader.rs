@@ -1,7 +1,8 @@
 use std::io::{BufRead, BufReader, Read};
 use std::string::String;
 use std::vec::Vec;
-use super::{Atom, Hetatm, Ter};
+use super::{Atom, Hetatm, Ter, Model, Structure};
+use super::altloc::{AltlocPolicy, resolve_structure};
 
 pub enum Record {
     MODEL{serial : i32},
@@ -9,6 +10,7 @@ pub enum Record {
     HETATM(Hetatm),
     TER(Ter),
     ENDMDL,
+    CONECT{serial : i32, bonded : Vec<i32>},
     Other(String),
 }
 
@@ -20,11 +22,22 @@ impl Record {
         else if let Ok(ter) = line.parse::<Ter>()    { Record::TER(ter) }
         else if length >= 5 && &line[0..5] == "MODEL" {
             let serial: i32 = if length >= 14 {
-                (&line[10..14]).parse().unwrap_or(0)
+                line[10..14].trim().parse().unwrap_or(0)
             } else {0};
             Record::MODEL{serial}
         }
         else if length >= 6 && &line[0..6] == "ENDMDL" { Record::ENDMDL }
+        else if length >= 11 && line.is_ascii() && &line[0..6] == "CONECT" {
+            match line[6..11].trim().parse::<i32>() {
+                Ok(serial) => {
+                    let bonded = (11..length).step_by(5)
+                        .filter_map(|i| line[i..(i+5).min(length)].trim().parse().ok())
+                        .collect();
+                    Record::CONECT{serial, bonded}
+                }
+                Err(_) => Record::Other(line),
+            }
+        }
         else {Record::Other(line)}
     }
 }
@@ -53,9 +66,62 @@ impl<R : Read> Reader<R> {
                 Record::TER(_)        => println!("chain terminated."),
                 Record::ATOM(atm)     => {println!("ATOM found"); chain.push(atm)},
                 Record::HETATM(_)     => println!("heterogen found."),
+                Record::CONECT{..}    => println!("connectivity found."),
                 Record::Other(_)      => println!("unrecognizable line found."),
             }
         }
         chain
     }
+
+    /// Reads the whole file. ATOM and HETATM lines that cannot be parsed are
+    /// errors; other lines that are not understood are skipped.
+    pub fn read_structure(&mut self) -> Result<Structure, String> {
+        let reader = &mut self.reader;
+        let mut structure = Structure::new();
+        let mut model = Model::new(1);
+        for (number, result) in reader.lines().enumerate() {
+            let line = try!(result.map_err(|e| e.to_string()));
+            match Record::new(line) {
+                Record::MODEL{serial} => {
+                    if !model.is_empty() {
+                        structure.models.push(model);
+                    }
+                    model = Model::new(serial);
+                }
+                Record::ENDMDL => {
+                    let serial = model.serial + 1;
+                    structure.models.push(model);
+                    model = Model::new(serial);
+                }
+                Record::ATOM(atm)   => model.atoms.push(atm),
+                Record::HETATM(htm) => model.hetatms.push(htm),
+                Record::TER(ter)    => model.ters.push(ter),
+                Record::CONECT{serial, bonded} => for b in bonded {
+                    structure.bonds.add(serial, b);
+                },
+                Record::Other(line) => {
+                    let error = if line.starts_with("ATOM  ") {
+                        line.parse::<Atom>().err()
+                    } else if line.starts_with("HETATM") {
+                        line.parse::<Hetatm>().err()
+                    } else {None};
+                    if let Some(e) = error {
+                        return Err(format!("line {}: {}", number + 1, e));
+                    }
+                    structure.header.read_line(&line);
+                }
+            }
+        }
+        if !model.is_empty() {
+            structure.models.push(model);
+        }
+        Ok(structure)
+    }
+
+    /// Reads the whole file and resolves alternate locations.
+    pub fn read_structure_with(&mut self, policy: AltlocPolicy)
+        -> Result<Structure, String> {
+        let structure = try!(self.read_structure());
+        Ok(resolve_structure(&structure, policy))
+    }
 }
@@ -4,46 +4,180 @@ use std::iter::{Extend, IntoIterator, FromIterator};
 use std::vec::Vec;
 use std::fmt;
 use super::{AtomData, ResidueData};
+use super::super::element;
+
+/// Identity of a residue: chain ID, residue number, insertion code and
+/// residue name. Atoms that have the same `ResidueId` belong to one residue.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ResidueId {
+    chain_id : char,
+    resseq   : i32,
+    icode    : char,
+    name     : ArrayString<[u8;3]>,
+}
+
+impl ResidueId {
+    /// A name longer than 3 bytes, the width of the column in PDB files, is
+    /// cut at the last character that fits.
+    pub fn new(chain_id: char, resseq: i32, icode: char, name: &str) -> ResidueId {
+        let mut end = name.len().min(3);
+        while !name.is_char_boundary(end) {
+            end -= 1;
+        }
+        ResidueId{
+            chain_id: chain_id, resseq: resseq, icode: icode,
+            name: ArrayString::from(&name[..end]).unwrap()
+        }
+    }
+
+    pub fn of<T: AtomData>(atom: &T) -> ResidueId {
+        ResidueId::new(atom.chain_id(), atom.residue_number(),
+                       atom.insertion_code(), atom.residue_name())
+    }
+}
+
+impl ResidueData for ResidueId {
+    fn residue_name(&self)   -> &str {self.name.as_str()}
+    fn residue_number(&self) -> i32  {self.resseq}
+    fn insertion_code(&self) -> char {self.icode}
+    fn chain_id(&self)       -> char {self.chain_id}
+}
+
+impl fmt::Display for ResidueId {
+    /// `A:ALA10` or `A:ALA10B` if the residue has an insertion code.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f, "{}:{}{}", self.chain_id, self.name, self.resseq));
+        if self.icode != ' ' {
+            try!(write!(f, "{}", self.icode));
+        }
+        Ok(())
+    }
+}
+
+/// One-letter code of an amino acid or a nucleotide. Protonation variants
+/// and common modified residues (MSE, SEP, ...) map to their parents, as do
+/// AMBER names of nucleotides (RA, DA5, RU3, ...).
+pub fn one_letter_code(residue_name: &str) -> Option<char> {
+    let name = residue_name.trim();
+    Some(match name {
+        "ALA" => 'A', "ARG" => 'R', "ASN" => 'N', "ASP" | "ASH" => 'D',
+        "CYS" | "CYX" => 'C', "GLN" => 'Q', "GLU" | "GLH" => 'E', "GLY" => 'G',
+        "HIS" | "HID" | "HIE" | "HIP" | "HSD" | "HSE" | "HSP" => 'H',
+        "ILE" => 'I', "LEU" => 'L', "LYS" | "LYN" => 'K', "MET" | "MSE" => 'M',
+        "PHE" => 'F', "PRO" => 'P', "SER" | "SEP" => 'S', "THR" | "TPO" => 'T',
+        "TRP" => 'W', "TYR" | "PTR" => 'Y', "VAL" => 'V', "SEC" => 'U', "PYL" => 'O',
+        "A" | "DA" | "RA" => 'A', "C" | "DC" | "RC" => 'C', "G" | "DG" | "RG" => 'G',
+        "U" | "DU" | "RU" => 'U', "DT" => 'T', "I" | "DI" => 'I',
+        // AMBER names of 5' and 3' termini
+        _ if DNA.contains(&name) || RNA.contains(&name) => {
+            return one_letter_code(&name[..name.len() - 1]);
+        }
+        _ => return None,
+    })
+}
+
+const AMINO_ACIDS: &'static [&'static str] = &[
+    "ALA", "ARG", "ASN", "ASP", "CYS", "GLN", "GLU", "GLY", "HIS", "ILE",
+    "LEU", "LYS", "MET", "PHE", "PRO", "SER", "THR", "TRP", "TYR", "VAL",
+    "SEC", "PYL", "MSE", "HID", "HIE", "HIP", "HSD", "HSE", "HSP", "CYX",
+    "ASH", "GLH", "LYN", "SEP", "TPO", "PTR",
+];
+/// Deoxyribonucleotides, including AMBER names of 5' and 3' termini.
+const DNA: &'static [&'static str] = &[
+    "DA", "DC", "DG", "DT", "DU", "DI", "DA5", "DC5", "DG5", "DT5", "DA3", "DC3", "DG3", "DT3",
+];
+/// Ribonucleotides, including AMBER names.
+const RNA: &'static [&'static str] = &[
+    "A", "C", "G", "U", "I", "RA", "RC", "RG", "RU", "RA5", "RC5", "RG5", "RU5",
+    "RA3", "RC3", "RG3", "RU3", "A5", "C5", "G5", "U5", "A3", "C3", "G3", "U3",
+];
+const WATER: &'static [&'static str] = &["HOH", "WAT", "H2O", "SOL", "TIP", "DOD"];
+
+/// What a residue is, judged from its name.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ResidueKind {
+    AminoAcid,
+    Dna,
+    Rna,
+    Water,
+    /// A monatomic ion; see `element::is_ion`.
+    Ion,
+    /// Anything else, e.g. ligands, cofactors and modified nucleotides.
+    Ligand,
+}
+
+impl ResidueKind {
+    pub fn is_nucleotide(&self) -> bool {
+        *self == ResidueKind::Dna || *self == ResidueKind::Rna
+    }
+
+    /// Amino acids and nucleotides.
+    pub fn is_polymer(&self) -> bool {
+        *self == ResidueKind::AminoAcid || self.is_nucleotide()
+    }
+}
+
+impl fmt::Display for ResidueKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            ResidueKind::AminoAcid => "amino acid",
+            ResidueKind::Dna       => "DNA",
+            ResidueKind::Rna       => "RNA",
+            ResidueKind::Water     => "water",
+            ResidueKind::Ion       => "ion",
+            ResidueKind::Ligand    => "ligand",
+        })
+    }
+}
+
+/// Classifies a residue by name. Names are case-sensitive, as in PDB files.
+pub fn classify(residue_name: &str) -> ResidueKind {
+    let name = residue_name.trim();
+    if AMINO_ACIDS.contains(&name) {
+        ResidueKind::AminoAcid
+    } else if DNA.contains(&name) {
+        ResidueKind::Dna
+    } else if RNA.contains(&name) {
+        ResidueKind::Rna
+    } else if WATER.contains(&name) {
+        ResidueKind::Water
+    } else if element::is_ion(name) {
+        ResidueKind::Ion
+    } else {
+        ResidueKind::Ligand
+    }
+}
 
 pub struct Residue<T: AtomData> {
     pub atoms : Vec<T>,
-    name      : ArrayString<[u8;3]>,
-    resseq    : i32,
-    chain_id  : char,
+    id        : ResidueId,
 }
 
 impl<T: AtomData> Residue<T> {
     pub fn new() -> Self {
-        Residue{
-            atoms: Vec::new(), name: ArrayString::new(), resseq: 1, chain_id: 'A'
-        }
+        Residue{atoms: Vec::new(), id: ResidueId::new('A', 1, ' ', "")}
     }
 
     pub fn from(v: Vec<T>) -> Option<Self> {
         if v.is_empty() {
             None
         } else {
-            let resn = ArrayString::<[u8;3]>::from(
-                v.first().unwrap().residue_name()).unwrap();
-            let resi = v.first().unwrap().residue_number();
-            let chid = v.first().unwrap().chain_id();
-            let mut res = Vec::<T>::new();
-            for elem in v {
-                if elem.residue_name()   == resn.as_str() &&
-                   elem.residue_number() == resi &&
-                   elem.chain_id()       == chid {
-                    res.push(elem);
-                }
-            }
-            Some(Residue{atoms: res, name: resn, resseq: resi, chain_id: chid})
+            let id = ResidueId::of(v.first().unwrap());
+            let res = v.into_iter().filter(|elem| ResidueId::of(elem) == id).collect();
+            Some(Residue{atoms: res, id: id})
         }
     }
 }
 
+impl<T: AtomData> Residue<T> {
+    pub fn residue_id(&self) -> ResidueId {self.id}
+}
+
 impl<T: AtomData> ResidueData for Residue<T> {
-    fn residue_name(&self)   -> &str {self.name.as_str()}
-    fn residue_number(&self) -> i32  {self.resseq}
-    fn chain_id(&self)       -> char {self.chain_id}
+    fn residue_name(&self)   -> &str {self.id.residue_name()}
+    fn residue_number(&self) -> i32  {self.id.residue_number()}
+    fn insertion_code(&self) -> char {self.id.insertion_code()}
+    fn chain_id(&self)       -> char {self.id.chain_id()}
 }
 
 impl<T: AtomData> IntoIterator for Residue<T> {
@@ -59,17 +193,10 @@ impl<T: AtomData> FromIterator<T> for Residue<T> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         let atms = ::std::vec::Vec::<T>::from_iter(iter);
         if atms.is_empty() {
-            Residue{
-                atoms: atms, name: ArrayString::new(), resseq: 1, chain_id: 'A'
-            }
+            Residue{atoms: atms, id: ResidueId::new('A', 1, ' ', "")}
         } else {
-            let resn = ArrayString::<[u8;3]>::from(
-                atms.first().unwrap().residue_name()).unwrap();
-            let resi = atms.first().unwrap().residue_number();
-            let chid = atms.first().unwrap().chain_id();
-            Residue{
-                atoms: atms, name: resn, resseq: resi, chain_id: chid
-            }
+            let id = ResidueId::of(atms.first().unwrap());
+            Residue{atoms: atms, id: id}
         }
     }
 }
@@ -77,8 +204,10 @@ impl<T: AtomData> FromIterator<T> for Residue<T> {
 impl<T: AtomData> Extend<T> for Residue<T> {
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
         for elem in iter {
-            if (elem.residue_name() == self.residue_name() &&
-                elem.chain_id() == self.chain_id()) || self.atoms.is_empty() {
+            if self.atoms.is_empty() {
+                self.id = ResidueId::of(&elem);
+                self.atoms.push(elem);
+            } else if ResidueId::of(&elem) == self.id {
                 self.atoms.push(elem);
             }
         }
@@ -87,34 +216,39 @@ impl<T: AtomData> Extend<T> for Residue<T> {
 
 pub struct ResidueSlice<'a, T: AtomData + 'a> {
     pub atoms : &'a [T],
-    name      : ArrayString<[u8;3]>,
-    resseq    : i32,
-    chain_id  : char,
+    id        : ResidueId,
+}
+
+impl<'a, T:AtomData + 'a> ResidueSlice<'a, T> {
+    pub fn residue_id(&self) -> ResidueId {self.id}
 }
 
 impl<'a, T:AtomData + 'a> ResidueData for ResidueSlice<'a, T> {
-    fn residue_name(&self)   -> &str {self.name.as_str()}
-    fn residue_number(&self) -> i32  {self.resseq}
-    fn chain_id(&self)       -> char {self.chain_id}
+    fn residue_name(&self)   -> &str {self.id.residue_name()}
+    fn residue_number(&self) -> i32  {self.id.residue_number()}
+    fn insertion_code(&self) -> char {self.id.insertion_code()}
+    fn chain_id(&self)       -> char {self.id.chain_id()}
 }
 
+/// Iterates residues in a sequence of atoms. A residue ends where the
+/// `ResidueId` (chain ID, residue number, insertion code and residue name)
+/// changes.
 pub struct ResidueIterator<'a, T:AtomData + 'a> {
     first    : usize,
-    last     : usize,
-    chain    : &'a Vec<T>,
+    chain    : &'a [T],
 }
 
 impl<'a, T:AtomData + 'a> ResidueIterator<'a, T> {
-    pub fn new(atoms : &Vec<T>) -> Option<ResidueIterator<T>> {
+    pub fn new(atoms : &[T]) -> Option<ResidueIterator<T>> {
         if atoms.is_empty() { None } else {
-            let mut idx: usize = 0;
-            let resn = &atoms[0].residue_name();
-            while idx < atoms.len() && &atoms[idx].residue_name() == resn {
-                idx += 1;
-            }
-            Some(ResidueIterator{first : 0, last : idx, chain : &atoms})
+            Some(ResidueIterator{first : 0, chain : atoms})
         }
     }
+
+    /// Same as `new`, but an empty slice gives an iterator that yields nothing.
+    pub fn from_slice(atoms : &[T]) -> ResidueIterator<T> {
+        ResidueIterator{first : 0, chain : atoms}
+    }
 }
 
 impl<'a, T:AtomData + 'a> Iterator for ResidueIterator<'a, T> {
@@ -126,28 +260,13 @@ impl<'a, T:AtomData + 'a> Iterator for ResidueIterator<'a, T> {
         }
 
         let fst = self.first;
-        let lst = self.last;
-        let rep = &(self.chain[self.first]);
-
-        if lst != self.chain.len() {
-            let mut idx = lst;
-            let next_resn = &(self.chain[self.last]).residue_name();
-            let next_chid = &(self.chain[self.last]).chain_id();
-
-            while idx < self.chain.len() &&
-                &self.chain[idx].chain_id() == next_chid &&
-                &self.chain[idx].residue_name() == next_resn {
-                idx += 1;
-            }
-            self.first = lst;
-            self.last = idx;
+        let id  = ResidueId::of(&self.chain[fst]);
+        let mut lst = fst + 1;
+        while lst < self.chain.len() && ResidueId::of(&self.chain[lst]) == id {
+            lst += 1;
         }
+        self.first = lst;
 
-        Some(ResidueSlice{
-            atoms    : &self.chain[fst .. lst],
-            name     : ArrayString::from(rep.residue_name()).unwrap(),
-            resseq   : rep.residue_number(),
-            chain_id : rep.chain_id(),
-        })
+        Some(ResidueSlice{atoms : &self.chain[fst .. lst], id : id})
     }
 }
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::string::String;
+use std::vec::Vec;
+use nalgebra::Vector3;
+use super::super::{Particle, ParticleMut};
+use super::{Atom, Hetatm, Ter, Header, BondGraph};
+use super::{AtomData, AtomDataMut};
+use super::chain::ChainIterator;
+
+/// A set of ATOM, HETATM and TER records between MODEL and ENDMDL.
+/// If a file has no MODEL record, all the records belong to one model.
+#[derive(Clone, Debug)]
+pub struct Model {
+    pub serial  : i32,
+    pub atoms   : Vec<Atom>,
+    pub hetatms : Vec<Hetatm>,
+    pub ters    : Vec<Ter>,
+}
+
+impl Model {
+    pub fn new(serial: i32) -> Model {
+        Model{
+            serial: serial, atoms: Vec::new(), hetatms: Vec::new(), ters: Vec::new()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.atoms.is_empty() && self.hetatms.is_empty() && self.ters.is_empty()
+    }
+
+    /// Chains of ATOM records, split by chain IDs and TERs.
+    pub fn chains(&self) -> Option<ChainIterator<Atom>> {
+        ChainIterator::with_ters(&self.atoms, &self.ters)
+    }
+
+    /// The number of ATOMs and HETATMs.
+    pub fn len(&self) -> usize {
+        self.atoms.len() + self.hetatms.len()
+    }
+
+    /// Positions of ATOMs and then HETATMs.
+    pub fn positions(&self) -> Vec<Vector3<f64>> {
+        self.atoms.iter().map(|a| a.vec())
+            .chain(self.hetatms.iter().map(|h| h.vec())).collect()
+    }
+
+    /// A copy of the model whose ATOMs and then HETATMs are moved to
+    /// `positions`, e.g. a frame of a trajectory.
+    pub fn with_positions(&self, serial: i32, positions: &[Vector3<f64>])
+        -> Result<Model, String> {
+        if positions.len() != self.len() {
+            return Err(format!("{} positions are given for {} atoms",
+                               positions.len(), self.len()));
+        }
+        let mut model = self.clone();
+        model.serial = serial;
+        let (atoms, hetatms) = positions.split_at(self.atoms.len());
+        for (atom, pos) in model.atoms.iter_mut().zip(atoms.iter()) {
+            atom.set_vec(*pos);
+        }
+        for (hetatm, pos) in model.hetatms.iter_mut().zip(hetatms.iter()) {
+            hetatm.set_vec(*pos);
+        }
+        Ok(model)
+    }
+
+    /// Renumbers ATOMs and then HETATMs from 1. Each TER is placed right
+    /// after the last atom of its residue. Returns the old and new serial
+    /// numbers.
+    pub fn renumber(&mut self) -> HashMap<i32, i32> {
+        let mut pending = ::std::mem::replace(&mut self.ters, Vec::new());
+        let ends: Vec<bool> = (0..self.atoms.len()).map(|i| {
+            i + 1 == self.atoms.len() ||
+            self.atoms[i].chain_id()       != self.atoms[i+1].chain_id() ||
+            self.atoms[i].residue_number() != self.atoms[i+1].residue_number()
+        }).collect();
+
+        let mut serials = HashMap::new();
+        let mut serial = 1;
+        for (atom, end) in self.atoms.iter_mut().zip(ends.into_iter()) {
+            serials.insert(atom.atom_number(), serial);
+            atom.set_atom_number(serial);
+            serial += 1;
+            if !end {
+                continue;
+            }
+            if let Some(idx) = pending.iter().position(|t| {
+                    t.chain_id() == atom.chain_id() &&
+                    t.residue_number() == atom.residue_number()}) {
+                pending.remove(idx);
+                self.ters.push(Ter::new(atom));
+            }
+        }
+        for hetatm in self.hetatms.iter_mut() {
+            serials.insert(hetatm.atom_number(), serial);
+            hetatm.set_atom_number(serial);
+            serial += 1;
+        }
+        serials
+    }
+
+    /// Renumbers residues of each chain of ATOMs sequentially from `first`
+    /// and clears insertion codes. HETATMs are left as they are.
+    pub fn renumber_residues(&mut self, first: i32) {
+        // the last atom before each TER, to put the TER after renumbering
+        let before: Vec<Option<usize>> = self.ters.iter().map(|t| {
+            self.atoms.iter().rposition(|a| {
+                a.atom_number() <= t.atom_number() && a.chain_id() == t.chain_id()
+            })
+        }).collect();
+
+        let mut number = first;
+        let mut prev: Option<(char, i32, char)> = None;
+        for atom in self.atoms.iter_mut() {
+            let key = (atom.chain_id(), atom.residue_number(), atom.insertion_code());
+            match prev {
+                Some((chain, _, _)) if chain != key.0 => number = first,
+                Some(p) if p != key => number += 1,
+                _ => {}
+            }
+            prev = Some(key);
+            atom.set_residue_number(number);
+            atom.set_insertion_code(' ');
+        }
+        for (ter, idx) in self.ters.iter_mut().zip(before.into_iter()) {
+            if let Some(i) = idx {
+                *ter = Ter::new(&self.atoms[i]);
+            }
+        }
+    }
+}
+
+/// The whole content of a PDB file: metadata, models and CONECT records.
+#[derive(Clone, Debug)]
+pub struct Structure {
+    pub header : Header,
+    pub models : Vec<Model>,
+    pub bonds  : BondGraph,
+}
+
+impl Structure {
+    pub fn new() -> Structure {
+        Structure{header: Header::new(), models: Vec::new(), bonds: BondGraph::new()}
+    }
+
+    pub fn first_model(&self) -> Option<&Model> {
+        self.models.first()
+    }
+
+    /// Renumbers atoms in all the models. Bonds follow the first model;
+    /// bonds of atoms not in it are removed.
+    pub fn renumber(&mut self) {
+        let mut serials = None;
+        for model in self.models.iter_mut() {
+            let renumbered = model.renumber();
+            if serials.is_none() {
+                serials = Some(renumbered);
+            }
+        }
+        let serials = serials.unwrap_or(HashMap::new());
+        self.bonds = self.bonds.renumbered(&serials);
+    }
+
+    /// Puts the atoms of `topology` at the positions of each model, for
+    /// trajectories without atom names (DCD) or residues (XYZ). Header and
+    /// bonds are those of the topology, except that the cell dimensions of
+    /// the trajectory are kept if it has them.
+    pub fn with_topology(&self, topology: &Structure) -> Result<Structure, String> {
+        let template = try!(topology.first_model().ok_or(
+            "no atom found in the topology".to_owned()));
+        let mut structure = Structure{
+            header : topology.header.clone(),
+            models : Vec::with_capacity(self.models.len()),
+            bonds  : topology.bonds.clone(),
+        };
+        if let Some(ref cell) = self.header.unit_cell {
+            let mut cell = cell.clone();
+            if let Some(ref given) = topology.header.unit_cell {
+                cell.space_group = given.space_group.clone();
+                cell.z = given.z;
+            }
+            structure.header.unit_cell = Some(cell);
+        }
+        for model in self.models.iter() {
+            let moved = try!(template.with_positions(model.serial, &model.positions())
+                                     .map_err(|e| format!("model {}: {}", model.serial, e)));
+            structure.models.push(moved);
+        }
+        Ok(structure)
+    }
+}
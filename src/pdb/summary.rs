@@ -0,0 +1,112 @@
+use std::string::String;
+use std::vec::Vec;
+use super::super::Particle;
+use super::{AtomData, ChainData, ResidueData, Structure};
+use super::{ResidueId, ResidueIterator, ResidueKind, ResidueSlice, classify};
+use super::altloc::altloc_ids;
+use super::residue::one_letter_code;
+
+/// C(i)-N(i+1) or O3'(i)-P(i+1) longer than this is a chain break.
+pub const BOND_CUTOFF: f64 = 2.0;
+/// CA(i)-CA(i+1) longer than this is a chain break if C or N is missing.
+pub const CA_CUTOFF: f64 = 4.2;
+
+/// Counts and sequence of a chain of ATOM records.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChainSummary {
+    pub chain_id : char,
+    pub residues : usize,
+    pub atoms    : usize,
+    /// One-letter codes; X for unknown residues.
+    pub sequence : String,
+    /// Pairs of consecutive residues that are not bonded.
+    pub breaks   : Vec<(ResidueId, ResidueId)>,
+}
+
+/// What a structure contains. Chains and counts are of the first model.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Summary {
+    pub models    : usize,
+    pub chains    : Vec<ChainSummary>,
+    pub atoms     : usize,
+    pub hetatms   : usize,
+    /// Residues in HETATM records that are neither water nor ions.
+    pub ligands   : Vec<ResidueId>,
+    pub waters    : usize,
+    pub ions      : usize,
+    /// Altloc IDs in order of appearance.
+    pub altlocs   : Vec<char>,
+    pub bonds     : usize,
+}
+
+fn find<'a, T: AtomData>(residue: &'a ResidueSlice<T>, name: &str) -> Option<&'a T> {
+    residue.atoms.iter().find(|a| a.atom_name() == name)
+}
+
+fn distance<P: Particle, Q: Particle>(lhs: Option<&P>, rhs: Option<&Q>) -> Option<f64> {
+    match (lhs, rhs) {
+        (Some(l), Some(r)) => Some((l.vec() - r.vec()).norm()),
+        _ => None,
+    }
+}
+
+/// Whether two consecutive residues are bonded. Peptide and phosphodiester
+/// bonds are checked first, then CA-CA distance. If none of the atoms are
+/// found, residues with consecutive residue numbers are regarded as bonded.
+pub fn is_bonded<T: AtomData + Particle>(prev: &ResidueSlice<T>, next: &ResidueSlice<T>)
+    -> bool {
+    if let Some(d) = distance(find(prev, "C"), find(next, "N")) {
+        return d <= BOND_CUTOFF;
+    }
+    if let Some(d) = distance(find(prev, "O3'"), find(next, "P")) {
+        return d <= BOND_CUTOFF;
+    }
+    if let Some(d) = distance(find(prev, "CA"), find(next, "CA")) {
+        return d <= CA_CUTOFF;
+    }
+    next.residue_number() - prev.residue_number() <= 1
+}
+
+impl Summary {
+    pub fn of(structure: &Structure) -> Summary {
+        let mut summary = Summary{
+            models: structure.models.len(), chains: Vec::new(), atoms: 0, hetatms: 0,
+            ligands: Vec::new(), waters: 0, ions: 0, altlocs: Vec::new(),
+            bonds: structure.bonds.len(),
+        };
+        let model = match structure.models.first() {Some(m) => m, None => return summary};
+        summary.atoms   = model.atoms.len();
+        summary.hetatms = model.hetatms.len();
+
+        if let Some(chains) = model.chains() {
+            for chain in chains {
+                let residues: Vec<_> = chain.residues().collect();
+                let breaks = residues.windows(2).filter(|w| !is_bonded(&w[0], &w[1]))
+                    .map(|w| (w[0].residue_id(), w[1].residue_id())).collect();
+                summary.chains.push(ChainSummary{
+                    chain_id : chain.chain_id(),
+                    residues : residues.len(),
+                    atoms    : chain.atoms.len(),
+                    sequence : residues.iter().map(|r| {
+                        one_letter_code(r.residue_name()).unwrap_or('X')
+                    }).collect(),
+                    breaks   : breaks,
+                });
+            }
+        }
+        for residue in ResidueIterator::from_slice(&model.hetatms) {
+            match classify(residue.residue_name()) {
+                ResidueKind::Water => summary.waters += 1,
+                ResidueKind::Ion   => summary.ions += 1,
+                _ => summary.ligands.push(residue.residue_id()),
+            }
+        }
+        summary.altlocs = altloc_ids(&model.atoms);
+        for id in altloc_ids(&model.hetatms) {
+            if !summary.altlocs.contains(&id) {
+                summary.altlocs.push(id);
+            }
+        }
+        summary
+    }
+}
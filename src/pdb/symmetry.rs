@@ -0,0 +1,396 @@
+use nalgebra::{Matrix3, Vector3};
+use std::collections::HashSet;
+use std::string::String;
+use std::vec::Vec;
+use std::fmt;
+use super::super::Particle;
+use super::super::neighbor::CellList;
+use super::{Atom, Ter, Model, Structure};
+use super::{AtomData, AtomDataMut};
+
+// ---------------------------------- SYMMETRY ---------------------------------
+
+/// A rotation followed by a translation, as written in BIOMT/SMTRY/MTRIX.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform {
+    pub rotation    : Matrix3<f64>,
+    pub translation : Vector3<f64>,
+}
+
+impl Transform {
+    pub fn identity() -> Transform {
+        Transform{rotation: Matrix3::identity(), translation: Vector3::zeros()}
+    }
+
+    pub fn apply(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.rotation * v + self.translation
+    }
+
+    pub fn is_identity(&self) -> bool {
+        (self.rotation - Matrix3::identity()).iter().all(|x| x.abs() < 1e-6) &&
+        self.translation.iter().all(|x| x.abs() < 1e-6)
+    }
+
+    pub fn set_row(&mut self, row: usize, values: &[f64; 4]) {
+        for i in 0..3 {
+            self.rotation[(row, i)] = values[i];
+        }
+        self.translation[row] = values[3];
+    }
+}
+
+/// Reads a row of BIOMT/SMTRY/MTRIX operators, like
+/// `BIOMT1   1  1.000000  0.000000  0.000000        0.00000`.
+/// Returns (row index, serial, values).
+pub fn read_operator_row(text: &str, tag: &str) -> Option<(usize, i32, [f64; 4])> {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    if tokens.len() < 6 || tokens[0].len() != tag.len() + 1 ||
+       !tokens[0].starts_with(tag) {
+        return None;
+    }
+    let row = match &tokens[0][tag.len()..] {
+        "1" => 0, "2" => 1, "3" => 2, _ => return None
+    };
+    let serial = match tokens[1].parse::<i32>() {Ok(n) => n, Err(_) => return None};
+    let mut values = [0.0; 4];
+    for i in 0..4 {
+        values[i] = match tokens[i+2].parse::<f64>() {Ok(x) => x, Err(_) => return None};
+    }
+    Some((row, serial, values))
+}
+
+/// Appends a row to the list of operators. The first row starts a new one.
+pub fn push_operator_row(operators: &mut Vec<Transform>, row: usize,
+                         values: &[f64; 4]) {
+    if row == 0 || operators.is_empty() {
+        operators.push(Transform::identity());
+    }
+    operators.last_mut().unwrap().set_row(row, values);
+}
+
+/// A set of chains and the operators applied to them (REMARK 350).
+#[derive(Clone, Debug, PartialEq)]
+pub struct AssemblyGroup {
+    pub chains    : Vec<char>,
+    pub operators : Vec<Transform>,
+}
+
+/// A biological assembly described in REMARK 350.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Biomolecule {
+    pub id     : i32,
+    pub groups : Vec<AssemblyGroup>,
+}
+
+/// The MTRIX record (non-crystallographic symmetry).
+/// If `given` is true, the copy is already in the file.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NcsOperator {
+    pub serial    : i32,
+    pub transform : Transform,
+    pub given     : bool,
+}
+
+/// The CRYST1 record.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnitCell {
+    pub a           : f64,
+    pub b           : f64,
+    pub c           : f64,
+    pub alpha       : f64,
+    pub beta        : f64,
+    pub gamma       : f64,
+    pub space_group : String,
+    pub z           : i32,
+}
+
+impl UnitCell {
+    pub fn from_line(line: &str) -> Result<UnitCell, String> {
+        if !line.is_ascii() || line.len() < 54 || &line[0..6] != "CRYST1" {
+            return Err(format!("the line is not CRYST1 line. \n{}", line).to_owned())
+        }
+        let field = |first: usize, last: usize| -> Result<f64, String> {
+            line[first..last].trim().parse::<f64>().map_err(
+                |e| [e.to_string(), format!("at\n{}", line)].concat())
+        };
+        // the space group and Z may be omitted with the trailing spaces
+        let spg = line.get(55..66).or(line.get(55..)).unwrap_or("").trim();
+        let z   = line.get(66..70).map_or(1, |z| z.trim().parse().unwrap_or(1));
+        Ok(UnitCell{
+            a           : try!(field( 6, 15)),
+            b           : try!(field(15, 24)),
+            c           : try!(field(24, 33)),
+            alpha       : try!(field(33, 40)),
+            beta        : try!(field(40, 47)),
+            gamma       : try!(field(47, 54)),
+            space_group : spg.to_owned(),
+            z           : z,
+        })
+    }
+
+    /// The matrix converting fractional coordinates into cartesian ones.
+    /// The a axis is along x and the b axis is on the xy plane.
+    pub fn orthogonalization(&self) -> Matrix3<f64> {
+        let (ca, cb, cg) = (self.alpha.to_radians().cos(),
+                            self.beta.to_radians().cos(),
+                            self.gamma.to_radians().cos());
+        let sg = self.gamma.to_radians().sin();
+        let cy = (ca - cb * cg) / sg;
+        let cz = (1.0 - cb * cb - cy * cy).sqrt();
+        Matrix3::new(self.a, self.b * cg, self.c * cb,
+                     0.0,    self.b * sg, self.c * cy,
+                     0.0,    0.0,         self.c * cz)
+    }
+
+    /// Symmetry operators of the space group in cartesian coordinates.
+    /// Only a few common space groups are known; use REMARK 290 otherwise.
+    pub fn symmetry_operators(&self) -> Option<Vec<Transform>> {
+        let ops: &[[[f64; 4]; 3]] = match self.space_group.as_str() {
+            "P 1" => &[
+                [[1.,0.,0.,0.], [0.,1.,0.,0.], [0.,0.,1.,0.]]],
+            "P 1 21 1" | "P 21" => &[
+                [[ 1.,0.,0.,0.], [0.,1.,0.,0.0], [0.,0., 1.,0.]],
+                [[-1.,0.,0.,0.], [0.,1.,0.,0.5], [0.,0.,-1.,0.]]],
+            "C 1 2 1" | "C 2" => &[
+                [[ 1.,0.,0.,0.0], [0.,1.,0.,0.0], [0.,0., 1.,0.]],
+                [[-1.,0.,0.,0.0], [0.,1.,0.,0.0], [0.,0.,-1.,0.]],
+                [[ 1.,0.,0.,0.5], [0.,1.,0.,0.5], [0.,0., 1.,0.]],
+                [[-1.,0.,0.,0.5], [0.,1.,0.,0.5], [0.,0.,-1.,0.]]],
+            "P 21 21 2" => &[
+                [[ 1.,0.,0.,0.0], [0., 1.,0.,0.0], [0.,0., 1.,0.]],
+                [[-1.,0.,0.,0.0], [0.,-1.,0.,0.0], [0.,0., 1.,0.]],
+                [[-1.,0.,0.,0.5], [0., 1.,0.,0.5], [0.,0.,-1.,0.]],
+                [[ 1.,0.,0.,0.5], [0.,-1.,0.,0.5], [0.,0.,-1.,0.]]],
+            "P 21 21 21" => &[
+                [[ 1.,0.,0.,0.0], [0., 1.,0.,0.0], [0.,0., 1.,0.0]],
+                [[-1.,0.,0.,0.5], [0.,-1.,0.,0.0], [0.,0., 1.,0.5]],
+                [[-1.,0.,0.,0.0], [0., 1.,0.,0.5], [0.,0.,-1.,0.5]],
+                [[ 1.,0.,0.,0.5], [0.,-1.,0.,0.5], [0.,0.,-1.,0.0]]],
+            _ => return None,
+        };
+        let orth = self.orthogonalization();
+        let frac = match orth.try_inverse() {Some(m) => m, None => return None};
+        Some(ops.iter().map(|op| {
+            let mut t = Transform::identity();
+            for row in 0..3 {
+                t.set_row(row, &op[row]);
+            }
+            Transform{rotation   : orth * t.rotation * frac,
+                      translation: orth * t.translation}
+        }).collect())
+    }
+}
+
+impl fmt::Display for UnitCell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CRYST1{:9.3}{:9.3}{:9.3}{:7.2}{:7.2}{:7.2} {:<11}{:4}          ",
+               self.a, self.b, self.c, self.alpha, self.beta, self.gamma,
+               self.space_group, self.z)
+    }
+}
+
+impl fmt::Display for NcsOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in 0..3 {
+            try!(write!(f, "MTRIX{}{:4}{:10.6}{:10.6}{:10.6}     {:10.5}    {}",
+                        row + 1, self.serial, self.transform.rotation[(row, 0)],
+                        self.transform.rotation[(row, 1)],
+                        self.transform.rotation[(row, 2)],
+                        self.transform.translation[row],
+                        if self.given {"1"} else {" "}));
+            try!(write!(f, "{:20}", ""));
+            if row != 2 {
+                try!(writeln!(f, ""));
+            }
+        }
+        Ok(())
+    }
+}
+
+// ------------------------------ copy generation ------------------------------
+
+const CHAIN_IDS: &'static str =
+    "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// Picks chain IDs that are not used yet.
+struct ChainNamer {
+    used : HashSet<char>,
+}
+
+impl ChainNamer {
+    fn new(model: &Model) -> ChainNamer {
+        let mut used = HashSet::new();
+        used.extend(model.atoms.iter().map(|a| a.chain_id()));
+        used.extend(model.hetatms.iter().map(|a| a.chain_id()));
+        ChainNamer{used: used}
+    }
+
+    fn next(&mut self) -> Result<char, String> {
+        match CHAIN_IDS.chars().find(|c| !self.used.contains(c)) {
+            Some(c) => {self.used.insert(c); Ok(c)}
+            None    => Err("no chain ID is left for a new copy".to_owned()),
+        }
+    }
+}
+
+/// Appends copies of `chains` in `src` moved by `op` into `dst`.
+/// Chain IDs are renamed as `rename` says. TERs are put for chains that
+/// have TER in `src`.
+fn append_copy(dst: &mut Model, src: &Model, chains: &[char], op: &Transform,
+               rename: &dyn Fn(char) -> char) {
+    for chain in chains.iter() {
+        let mut last: Option<Atom> = None;
+        for atom in src.atoms.iter().filter(|a| a.chain_id() == *chain) {
+            let pos = op.apply(&atom.vec());
+            let mut copy = *atom;
+            copy.set_chain_id(rename(*chain));
+            copy.x = pos[0]; copy.y = pos[1]; copy.z = pos[2];
+            dst.atoms.push(copy);
+            last = Some(copy);
+        }
+        if let Some(last) = last {
+            if src.ters.iter().any(|t| t.chain_id() == *chain) {
+                dst.ters.push(Ter::new(&last));
+            }
+        }
+    }
+    for chain in chains.iter() {
+        for hetatm in src.hetatms.iter().filter(|a| a.chain_id() == *chain) {
+            let pos = op.apply(&hetatm.vec());
+            let mut copy = *hetatm;
+            copy.set_chain_id(rename(*chain));
+            copy.x = pos[0]; copy.y = pos[1]; copy.z = pos[2];
+            dst.hetatms.push(copy);
+        }
+    }
+}
+
+fn chains_of(model: &Model) -> Vec<char> {
+    let mut chains = Vec::new();
+    for c in model.atoms.iter().map(|a| a.chain_id())
+                   .chain(model.hetatms.iter().map(|a| a.chain_id())) {
+        if !chains.contains(&c) {
+            chains.push(c);
+        }
+    }
+    chains
+}
+
+/// Generates the biological assembly `id` listed in REMARK 350.
+/// The first copy of each chain keeps its chain ID; the others get IDs
+/// not used in the model. Atoms are renumbered.
+pub fn biological_assembly(structure: &Structure, id: i32) -> Result<Structure, String> {
+    let biomolecule = match structure.header.biomolecules.iter().find(|b| b.id == id) {
+        Some(b) => b,
+        None    => return Err(format!("BIOMOLECULE {} is not found", id)),
+    };
+    let mut assembly = Structure{header: structure.header.clone(), ..Structure::new()};
+    for model in structure.models.iter() {
+        let mut namer = ChainNamer::new(model);
+        let mut dst   = Model::new(model.serial);
+        let mut first = HashSet::new();
+        for group in biomolecule.groups.iter() {
+            for op in group.operators.iter() {
+                let mut names = Vec::new();
+                for chain in group.chains.iter() {
+                    names.push(if first.insert(*chain) {*chain} else {try!(namer.next())});
+                }
+                append_copy(&mut dst, model, &group.chains, op, &|c| {
+                    names[group.chains.iter().position(|x| *x == c).unwrap()]
+                });
+            }
+        }
+        dst.renumber();
+        assembly.models.push(dst);
+    }
+    Ok(assembly)
+}
+
+/// Applies MTRIX operators that are not marked as given, appending the
+/// generated copies with new chain IDs.
+pub fn expand_ncs(structure: &Structure) -> Result<Structure, String> {
+    let mut expanded = Structure{header: structure.header.clone(), ..Structure::new()};
+    for model in structure.models.iter() {
+        let mut namer = ChainNamer::new(model);
+        let chains = chains_of(model);
+        let mut dst = model.clone();
+        for ncs in structure.header.ncs_operators.iter().filter(|o| !o.given) {
+            if ncs.transform.is_identity() {
+                continue;
+            }
+            let mut names = Vec::new();
+            for _ in chains.iter() {
+                names.push(try!(namer.next()));
+            }
+            append_copy(&mut dst, model, &chains, &ncs.transform, &|c| {
+                names[chains.iter().position(|x| *x == c).unwrap()]
+            });
+        }
+        dst.renumber();
+        expanded.models.push(dst);
+    }
+    expanded.header.ncs_operators.clear();
+    Ok(expanded)
+}
+
+fn positions(model: &Model) -> Vec<Vector3<f64>> {
+    model.atoms.iter().map(|a| Vector3::new(a.x, a.y, a.z))
+        .chain(model.hetatms.iter().map(|a| Vector3::new(a.x, a.y, a.z)))
+        .collect()
+}
+
+/// Builds symmetry mates of the asymmetric unit that have at least one atom
+/// within `cutoff` of it. Operators are taken from REMARK 290 if present,
+/// otherwise from the space group in CRYST1. Neighboring unit cells
+/// (-1, 0, +1 in each direction) are searched.
+pub fn symmetry_mates(structure: &Structure, cutoff: f64) -> Result<Structure, String> {
+    let cell = match structure.header.unit_cell {
+        Some(ref c) => c,
+        None        => return Err("CRYST1 record is not found".to_owned()),
+    };
+    let operators = if !structure.header.crystal_symmetry.is_empty() {
+        structure.header.crystal_symmetry.clone()
+    } else {
+        match cell.symmetry_operators() {
+            Some(ops) => ops,
+            None => return Err(format!("unknown space group: {}", cell.space_group)),
+        }
+    };
+    let orth = cell.orthogonalization();
+
+    let mut mates = Structure{header: structure.header.clone(), ..Structure::new()};
+    for model in structure.models.iter() {
+        let mut namer = ChainNamer::new(model);
+        let chains = chains_of(model);
+        let original = positions(model);
+        let cells = CellList::new(&original, cutoff);
+        let mut dst = model.clone();
+        for op in operators.iter() {
+            for i in -1..2 {
+                for j in -1..2 {
+                    for k in -1..2 {
+                        let shift = orth * Vector3::new(i as f64, j as f64, k as f64);
+                        let moved = Transform{rotation   : op.rotation,
+                                              translation: op.translation + shift};
+                        if moved.is_identity() {
+                            continue;
+                        }
+                        if !original.iter().any(|v| cells.any_within(&moved.apply(v), cutoff)) {
+                            continue;
+                        }
+                        let mut names = Vec::new();
+                        for _ in chains.iter() {
+                            names.push(try!(namer.next()));
+                        }
+                        append_copy(&mut dst, model, &chains, &moved, &|c| {
+                            names[chains.iter().position(|x| *x == c).unwrap()]
+                        });
+                    }
+                }
+            }
+        }
+        dst.renumber();
+        mates.models.push(dst);
+    }
+    Ok(mates)
+}
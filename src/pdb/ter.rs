@@ -5,6 +5,7 @@ use std::str::FromStr;
 use std::fmt;
 use super::AtomData;
 
+#[derive(Copy, Clone, Debug)]
 pub struct Ter {
     serial  : i32,
     resname : ArrayString<[u8;3]>,
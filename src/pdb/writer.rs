@@ -0,0 +1,53 @@
+use std::io::{self, Write, BufWriter};
+use super::{Model, Structure};
+use super::AtomData;
+
+pub struct Writer<W: Write> {
+    writer : BufWriter<W>,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn new(inner: W) -> Self {
+        Writer {
+            writer: BufWriter::new(inner)
+        }
+    }
+
+    /// Writes header, models, CONECT and END records.
+    /// MODEL/ENDMDL are written only if the structure has several models.
+    pub fn write_structure(&mut self, structure: &Structure) -> io::Result<()> {
+        try!(write!(self.writer, "{}", structure.header));
+        let multiple = structure.models.len() > 1;
+        for model in structure.models.iter() {
+            if multiple {
+                try!(writeln!(self.writer, "MODEL     {:>4}{:66}", model.serial, ""));
+            }
+            try!(self.write_model(model));
+            if multiple {
+                try!(writeln!(self.writer, "ENDMDL{:74}", ""));
+            }
+        }
+        try!(write!(self.writer, "{}", structure.bonds));
+        try!(writeln!(self.writer, "END{:77}", ""));
+        self.writer.flush()
+    }
+
+    /// Writes ATOMs with TERs placed after the atoms that precede them,
+    /// followed by HETATMs.
+    pub fn write_model(&mut self, model: &Model) -> io::Result<()> {
+        let mut ters = model.ters.iter().peekable();
+        for atom in model.atoms.iter() {
+            while ters.peek().map_or(false, |t| t.atom_number() < atom.atom_number()) {
+                try!(writeln!(self.writer, "{}", ters.next().unwrap()));
+            }
+            try!(writeln!(self.writer, "{}", atom));
+        }
+        for ter in ters {
+            try!(writeln!(self.writer, "{}", ter));
+        }
+        for hetatm in model.hetatms.iter() {
+            try!(writeln!(self.writer, "{}", hetatm));
+        }
+        Ok(())
+    }
+}
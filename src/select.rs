@@ -0,0 +1,348 @@
+//! module select: atom selection language.
+//!
+//! ```text
+//! chain A and resid 10-50 and name CA
+//! within 5.0 of resname LIG
+//! not hydrogen
+//! (backbone or name CB) and not resname PRO GLY
+//! ```
+//!
+//! Keywords that take values (`name`, `resname`, `chain`, `resid`, `serial`,
+//! `element`, `altloc`) accept one or more values; numeric ones accept ranges
+//! like `10-50`. Single keywords are `all`, `none`, `hydrogen`, `heavy`,
+//! `backbone`, `sidechain`, `base`, `protein`, `dna`, `rna`, `nucleic`,
+//! `water`, `ion`, `ligand` and `hetero`. The backbone of nucleotides is
+//! their phosphates and sugars.
+//! `not` binds tighter than `and`, and `and` binds tighter than `or`.
+use nalgebra::Vector3;
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::string::String;
+use std::vec::Vec;
+use super::Particle;
+use super::element;
+use super::neighbor::CellList;
+use super::nucleic::{self, Group};
+use super::pdb::{AtomData, Model, ResidueKind, Structure, classify};
+
+/// Names of the backbone atoms of amino acids.
+pub const BACKBONE: &'static [&'static str] = &["N", "CA", "C", "O", "OXT"];
+
+/// Whether a residue is a water molecule, e.g. HOH, WAT or SOL.
+pub fn is_water(residue_name: &str) -> bool {
+    classify(residue_name) == ResidueKind::Water
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    All,
+    None,
+    Name(Vec<String>),
+    ResName(Vec<String>),
+    Chain(Vec<char>),
+    ResId(Vec<(i32, i32)>),
+    Serial(Vec<(i32, i32)>),
+    Element(Vec<String>),
+    Altloc(Vec<char>),
+    Hydrogen,
+    Backbone,
+    Base,
+    Kind(Vec<ResidueKind>),
+    Hetero,
+    Not(Box<Node>),
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Within(f64, Box<Node>),
+    /// `Within` after the reference atoms are found.
+    Near(f64, CellList),
+}
+
+fn is_hydrogen<T: AtomData>(atom: &T) -> bool {
+    element::of(atom).map_or(false, |e| e.atomic_number == 1)
+}
+
+impl Node {
+    fn matches<T: AtomData + Particle>(&self, atom: &T) -> bool {
+        match *self {
+            Node::All                => true,
+            Node::None               => false,
+            Node::Name(ref names)    => names.iter().any(|n| n == atom.atom_name()),
+            Node::ResName(ref names) => names.iter().any(|n| n == atom.residue_name()),
+            Node::Chain(ref ids)     => ids.contains(&atom.chain_id()),
+            Node::ResId(ref ranges)  => {
+                let n = atom.residue_number();
+                ranges.iter().any(|&(lo, hi)| lo <= n && n <= hi)
+            }
+            Node::Serial(ref ranges) => {
+                let n = atom.atom_number();
+                ranges.iter().any(|&(lo, hi)| lo <= n && n <= hi)
+            }
+            Node::Element(ref elems) => elems.iter().any(|e| e == atom.element_symbol()),
+            Node::Altloc(ref ids)    => ids.contains(&atom.alternate_location()),
+            Node::Hydrogen => is_hydrogen(atom),
+            Node::Backbone => match classify(atom.residue_name()) {
+                ResidueKind::AminoAcid => BACKBONE.contains(&atom.atom_name()),
+                ResidueKind::Dna | ResidueKind::Rna =>
+                    nucleic::group(atom.atom_name()) != Group::Base,
+                _ => false,
+            },
+            Node::Base     => classify(atom.residue_name()).is_nucleotide() &&
+                              nucleic::group(atom.atom_name()) == Group::Base,
+            Node::Kind(ref kinds) => kinds.contains(&classify(atom.residue_name())),
+            Node::Hetero   => atom.record_name() == "HETATM",
+            Node::Not(ref n)         => !n.matches(atom),
+            Node::And(ref l, ref r)  => l.matches(atom) && r.matches(atom),
+            Node::Or(ref l, ref r)   => l.matches(atom) || r.matches(atom),
+            Node::Within(_, _)       => panic!("selection `within` is not compiled"),
+            Node::Near(cutoff, ref cells) => cells.any_within(&atom.vec(), cutoff),
+        }
+    }
+
+    /// Replaces `Within` by `Near` using `refs` that returns positions of
+    /// the atoms matching a node.
+    fn resolve(&self, refs: &dyn Fn(&Node) -> Vec<Vector3<f64>>) -> Node {
+        match *self {
+            Node::Not(ref n)        => Node::Not(Box::new(n.resolve(refs))),
+            Node::And(ref l, ref r) => Node::And(Box::new(l.resolve(refs)),
+                                                 Box::new(r.resolve(refs))),
+            Node::Or(ref l, ref r)  => Node::Or(Box::new(l.resolve(refs)),
+                                                Box::new(r.resolve(refs))),
+            Node::Within(cutoff, ref n) => {
+                Node::Near(cutoff, CellList::new(&refs(&n.resolve(refs)), cutoff))
+            }
+            ref other => other.clone(),
+        }
+    }
+}
+
+/// A parsed selection. `within` needs the set of atoms to search, so a
+/// `Selection` is compiled into a `Predicate` for a set of atoms.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Selection {
+    node : Node,
+}
+
+/// A selection ready to be tested against each atom.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Predicate {
+    node : Node,
+}
+
+impl Predicate {
+    pub fn matches<T: AtomData + Particle>(&self, atom: &T) -> bool {
+        self.node.matches(atom)
+    }
+}
+
+impl Selection {
+    pub fn all() -> Selection {
+        Selection{node: Node::All}
+    }
+
+    /// Compiles the selection. `within` searches atoms in `context`.
+    pub fn compile<T: AtomData + Particle>(&self, context: &[T]) -> Predicate {
+        Predicate{node: self.node.resolve(&|n: &Node| {
+            context.iter().filter(|a| n.matches(*a)).map(|a| a.vec()).collect()
+        })}
+    }
+
+    /// Compiles the selection. `within` searches both ATOMs and HETATMs.
+    pub fn compile_model(&self, model: &Model) -> Predicate {
+        Predicate{node: self.node.resolve(&|n: &Node| {
+            model.atoms.iter().filter(|a| n.matches(*a)).map(|a| a.vec())
+                .chain(model.hetatms.iter().filter(|a| n.matches(*a)).map(|a| a.vec()))
+                .collect()
+        })}
+    }
+
+    /// Indices of the selected atoms.
+    pub fn indices<T: AtomData + Particle>(&self, atoms: &[T]) -> Vec<usize> {
+        let pred = self.compile(atoms);
+        atoms.iter().enumerate()
+             .filter(|&(_, a)| pred.matches(a)).map(|(i, _)| i).collect()
+    }
+
+    pub fn select<T: AtomData + Particle + Clone>(&self, atoms: &[T]) -> Vec<T> {
+        let pred = self.compile(atoms);
+        atoms.iter().filter(|a| pred.matches(*a)).cloned().collect()
+    }
+
+    /// Keeps the selected atoms in each model. TERs are kept if an atom of
+    /// the same chain remains, and bonds if both atoms remain in some model.
+    pub fn select_structure(&self, structure: &Structure) -> Structure {
+        let mut selected = Structure{header: structure.header.clone(), ..Structure::new()};
+        for model in structure.models.iter() {
+            let pred = self.compile_model(model);
+            let mut dst = Model::new(model.serial);
+            dst.atoms   = model.atoms.iter().filter(|a| pred.matches(*a)).cloned().collect();
+            dst.hetatms = model.hetatms.iter().filter(|a| pred.matches(*a)).cloned().collect();
+            dst.ters    = model.ters.iter().filter(|t| {
+                dst.atoms.iter().any(|a| a.chain_id() == t.chain_id())
+            }).cloned().collect();
+            selected.models.push(dst);
+        }
+        let kept: HashSet<i32> = selected.models.iter().flat_map(|m| {
+            m.atoms.iter().map(|a| a.atom_number())
+             .chain(m.hetatms.iter().map(|a| a.atom_number()))
+        }).collect();
+        selected.bonds = structure.bonds.clone();
+        selected.bonds.retain(|serial| kept.contains(&serial));
+        selected
+    }
+}
+
+// ---------------------------------- parser -----------------------------------
+
+fn tokenize(s: &str) -> Vec<String> {
+    s.replace("(", " ( ").replace(")", " ) ")
+     .split_whitespace().map(|t| t.to_owned()).collect()
+}
+
+fn is_reserved(token: &str) -> bool {
+    match token {
+        "and" | "or" | "not" | "within" | "of" | "(" | ")" => true,
+        _ => false,
+    }
+}
+
+struct Parser {
+    tokens : Vec<String>,
+    pos    : usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(|t| t.as_str())
+    }
+
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        match self.next() {
+            Some(ref t) if t == token => Ok(()),
+            Some(t) => Err(format!("expected `{}`, found `{}`", token, t)),
+            None    => Err(format!("expected `{}`, found end of selection", token)),
+        }
+    }
+
+    fn or(&mut self) -> Result<Node, String> {
+        let mut lhs = try!(self.and());
+        while self.peek() == Some("or") {
+            self.pos += 1;
+            let rhs = try!(self.and());
+            lhs = Node::Or(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Node, String> {
+        let mut lhs = try!(self.unary());
+        while self.peek() == Some("and") {
+            self.pos += 1;
+            let rhs = try!(self.unary());
+            lhs = Node::And(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Node, String> {
+        let token = match self.next() {
+            Some(t) => t, None => return Err("unexpected end of selection".to_owned())
+        };
+        match token.as_str() {
+            "not" => Ok(Node::Not(Box::new(try!(self.unary())))),
+            "(" => {
+                let node = try!(self.or());
+                try!(self.expect(")"));
+                Ok(node)
+            }
+            "within" => {
+                let cutoff = match self.next().map(|t| t.parse::<f64>()) {
+                    Some(Ok(x)) => x,
+                    _ => return Err("`within` needs a distance".to_owned()),
+                };
+                try!(self.expect("of"));
+                Ok(Node::Within(cutoff, Box::new(try!(self.unary()))))
+            }
+            "all"       => Ok(Node::All),
+            "none"      => Ok(Node::None),
+            "hydrogen"  => Ok(Node::Hydrogen),
+            "heavy"     => Ok(Node::Not(Box::new(Node::Hydrogen))),
+            "backbone"  => Ok(Node::Backbone),
+            "sidechain" => Ok(Node::And(Box::new(Node::Kind(vec![ResidueKind::AminoAcid])),
+                                        Box::new(Node::Not(Box::new(Node::Backbone))))),
+            "base"      => Ok(Node::Base),
+            "protein"   => Ok(Node::Kind(vec![ResidueKind::AminoAcid])),
+            "dna"       => Ok(Node::Kind(vec![ResidueKind::Dna])),
+            "rna"       => Ok(Node::Kind(vec![ResidueKind::Rna])),
+            "nucleic"   => Ok(Node::Kind(vec![ResidueKind::Dna, ResidueKind::Rna])),
+            "water"     => Ok(Node::Kind(vec![ResidueKind::Water])),
+            "ion"       => Ok(Node::Kind(vec![ResidueKind::Ion])),
+            "ligand"    => Ok(Node::Kind(vec![ResidueKind::Ligand])),
+            "hetero"    => Ok(Node::Hetero),
+            "name"      => Ok(Node::Name(try!(self.values(&token)))),
+            "resname"   => Ok(Node::ResName(try!(self.values(&token)))),
+            "element"   => Ok(Node::Element(try!(self.values(&token)))),
+            "chain"     => Ok(Node::Chain(try!(self.chars(&token)))),
+            "altloc"    => Ok(Node::Altloc(try!(self.chars(&token)))),
+            "resid"     => Ok(Node::ResId(try!(self.ranges(&token)))),
+            "serial"    => Ok(Node::Serial(try!(self.ranges(&token)))),
+            _ => Err(format!("unknown keyword `{}`", token)),
+        }
+    }
+
+    fn values(&mut self, keyword: &str) -> Result<Vec<String>, String> {
+        let mut values = Vec::new();
+        while self.peek().map_or(false, |t| !is_reserved(t)) {
+            values.push(self.next().unwrap());
+        }
+        if values.is_empty() {
+            return Err(format!("`{}` needs at least one value", keyword));
+        }
+        Ok(values)
+    }
+
+    fn chars(&mut self, keyword: &str) -> Result<Vec<char>, String> {
+        let values = try!(self.values(keyword));
+        values.iter().map(|v| {
+            if v.len() == 1 {Ok(v.chars().next().unwrap())} else {
+                Err(format!("`{}` takes one-letter values: `{}`", keyword, v))
+            }
+        }).collect()
+    }
+
+    fn ranges(&mut self, keyword: &str) -> Result<Vec<(i32, i32)>, String> {
+        let values = try!(self.values(keyword));
+        values.iter().map(|v| {
+            // the first character can be a minus sign of a negative number
+            let range = match v.char_indices().skip(1).find(|&(_, c)| c == '-') {
+                Some((idx, _)) => (v[..idx].parse::<i32>(), v[idx+1..].parse::<i32>()),
+                None      => (v.parse::<i32>(), v.parse::<i32>()),
+            };
+            match range {
+                (Ok(lo), Ok(hi)) => Ok((lo, hi)),
+                _ => Err(format!("`{}` takes numbers or ranges: `{}`", keyword, v)),
+            }
+        }).collect()
+    }
+}
+
+impl FromStr for Selection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser{tokens: tokenize(s), pos: 0};
+        if parser.tokens.is_empty() {
+            return Err("empty selection".to_owned());
+        }
+        let node = try!(parser.or());
+        if let Some(t) = parser.peek() {
+            return Err(format!("unexpected `{}` in selection", t));
+        }
+        Ok(Selection{node: node})
+    }
+}
@@ -0,0 +1,154 @@
+//! module similarity: structural similarity scores, TM-score and GDT.
+use nalgebra::Vector3;
+use std::string::String;
+use std::vec::Vec;
+use super::Particle;
+use super::pdb::{AtomData, ResidueId};
+use super::align::{Scoring, align};
+use super::superpose::{superpose, rmsd};
+
+/// The length-dependent scale of TM-score.
+pub fn tm_d0(length: usize) -> f64 {
+    if length <= 15 {
+        return 0.5;
+    }
+    let d0 = 1.24 * ((length - 15) as f64).cbrt() - 1.8;
+    if d0 < 0.5 {0.5} else {d0}
+}
+
+fn distances(model: &[Vector3<f64>], reference: &[Vector3<f64>], init: &[usize])
+    -> Option<Vec<f64>> {
+    let lhs: Vec<Vector3<f64>> = init.iter().map(|i| model[*i]).collect();
+    let rhs: Vec<Vector3<f64>> = init.iter().map(|i| reference[*i]).collect();
+    let sp = match superpose(&lhs, &rhs) {Ok(sp) => sp, Err(_) => return None};
+    Some(model.iter().zip(reference.iter())
+              .map(|(m, r)| (sp.apply(m) - r).norm()).collect())
+}
+
+/// Maximizes `score` over superpositions. Starting from fragments of
+/// several lengths, a superposition is repeatedly refined using the pairs
+/// closer than `cutoff`.
+fn search(model: &[Vector3<f64>], reference: &[Vector3<f64>], cutoff: f64,
+          score: &dyn Fn(&[f64]) -> f64) -> f64 {
+    let n = model.len();
+    let mut best = 0.0;
+    let mut fragment = n;
+    loop {
+        let step = if fragment / 2 > 0 {fragment / 2} else {1};
+        let mut start = 0;
+        while start + fragment <= n {
+            let mut selected: Vec<usize> = (start..start+fragment).collect();
+            for _ in 0..20 {
+                let ds = match distances(model, reference, &selected) {
+                    Some(ds) => ds, None => break
+                };
+                let s = score(&ds);
+                if s > best {
+                    best = s;
+                }
+                let next: Vec<usize> = (0..n).filter(|i| ds[*i] < cutoff).collect();
+                if next.len() < 3 || next == selected {
+                    break;
+                }
+                selected = next;
+            }
+            start += step;
+        }
+        if fragment <= 4 {
+            break;
+        }
+        fragment = if fragment / 2 < 4 {4} else {fragment / 2};
+    }
+    best
+}
+
+fn check<P: Particle, Q: Particle>(model: &[P], reference: &[Q])
+    -> Result<(Vec<Vector3<f64>>, Vec<Vector3<f64>>), String> {
+    if model.len() != reference.len() {
+        return Err(format!("the numbers of particles differ: {} and {}",
+                           model.len(), reference.len()));
+    }
+    if model.len() < 3 {
+        return Err("at least 3 pairs are needed".to_owned());
+    }
+    Ok((model.iter().map(|p| p.vec()).collect(),
+        reference.iter().map(|p| p.vec()).collect()))
+}
+
+/// TM-score of paired particles (typically CA atoms), normalized by
+/// `length`, the number of residues in the reference.
+pub fn tm_score<P: Particle, Q: Particle>(model: &[P], reference: &[Q], length: usize)
+    -> Result<f64, String> {
+    let (m, r) = try!(check(model, reference));
+    let d0 = tm_d0(length);
+    let d0_search = if d0 < 4.5 {4.5} else if d0 > 8.0 {8.0} else {d0};
+    Ok(search(&m, &r, d0_search, &|ds: &[f64]| {
+        ds.iter().fold(0.0, |acc, d| acc + 1.0 / (1.0 + (d / d0).powi(2))) / length as f64
+    }))
+}
+
+/// The average fraction of particles within each cutoff, each maximized
+/// over superpositions and normalized by `length`.
+pub fn gdt<P: Particle, Q: Particle>(model: &[P], reference: &[Q], length: usize,
+                                     cutoffs: &[f64]) -> Result<f64, String> {
+    let (m, r) = try!(check(model, reference));
+    let sum = cutoffs.iter().fold(0.0, |acc, cutoff| {
+        let c = *cutoff;
+        acc + search(&m, &r, c, &|ds: &[f64]| {
+            ds.iter().filter(|d| **d < c).count() as f64 / length as f64
+        })
+    });
+    Ok(sum / cutoffs.len() as f64)
+}
+
+/// GDT-TS, with cutoffs of 1, 2, 4 and 8 angstroms.
+pub fn gdt_ts<P: Particle, Q: Particle>(model: &[P], reference: &[Q], length: usize)
+    -> Result<f64, String> {
+    gdt(model, reference, length, &[1.0, 2.0, 4.0, 8.0])
+}
+
+/// GDT-HA, with cutoffs of 0.5, 1, 2 and 4 angstroms.
+pub fn gdt_ha<P: Particle, Q: Particle>(model: &[P], reference: &[Q], length: usize)
+    -> Result<f64, String> {
+    gdt(model, reference, length, &[0.5, 1.0, 2.0, 4.0])
+}
+
+/// Scores comparing two chains.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Similarity {
+    /// The number of CA pairs.
+    pub aligned  : usize,
+    /// RMSD of the pairs after the optimal superposition.
+    pub rmsd     : f64,
+    pub tm_score : f64,
+    pub gdt_ts   : f64,
+    pub gdt_ha   : f64,
+}
+
+/// Compares CA atoms of two chains. The CA atoms are paired by sequence
+/// alignment and scores are normalized by the length of `reference`.
+pub fn compare<T, U>(model: &[T], reference: &[U]) -> Result<Similarity, String>
+    where T: AtomData + Particle, U: AtomData + Particle {
+    let model_ca: Vec<&T> = model.iter().filter(|a| a.atom_name() == "CA").collect();
+    let ref_ca:   Vec<&U> = reference.iter().filter(|a| a.atom_name() == "CA").collect();
+
+    let model_ids: Vec<_> = model_ca.iter().map(|a| ResidueId::of(*a)).collect();
+    let ref_ids:   Vec<_> = ref_ca.iter().map(|a| ResidueId::of(*a)).collect();
+    let pairs: Vec<(usize, usize)> = align(&model_ids, &ref_ids, &Scoring::default())
+        .into_iter().filter_map(|p| match p {
+            (Some(i), Some(j)) => Some((i, j)), _ => None
+        }).collect();
+
+    let lhs: Vec<Vector3<f64>> = pairs.iter().map(|&(i, _)| model_ca[i].vec()).collect();
+    let rhs: Vec<Vector3<f64>> = pairs.iter().map(|&(_, j)| ref_ca[j].vec()).collect();
+    let length = ref_ca.len();
+    let sp = try!(superpose(&lhs, &rhs));
+    let fitted: Vec<Vector3<f64>> = lhs.iter().map(|v| sp.apply(v)).collect();
+    Ok(Similarity{
+        aligned  : pairs.len(),
+        rmsd     : try!(rmsd(&fitted, &rhs)),
+        tm_score : try!(tm_score(&lhs, &rhs, length)),
+        gdt_ts   : try!(gdt_ts(&lhs, &rhs, length)),
+        gdt_ha   : try!(gdt_ha(&lhs, &rhs, length)),
+    })
+}
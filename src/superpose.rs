@@ -0,0 +1,104 @@
+//! module superpose: optimal superposition (Kabsch algorithm) and RMSD.
+use nalgebra::{Matrix3, Vector3};
+use std::string::String;
+use super::{Particle, ParticleMut};
+use super::pdb::Structure;
+
+/// The rotation and translation that move `mobile` onto `target`, i.e.
+/// `target ~ rotation * mobile + translation`, and the RMSD after fitting.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Superposition {
+    pub rotation    : Matrix3<f64>,
+    pub translation : Vector3<f64>,
+    pub rmsd        : f64,
+}
+
+impl Superposition {
+    pub fn apply(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.rotation * v + self.translation
+    }
+}
+
+/// Root mean square deviation without fitting.
+pub fn rmsd<P: Particle, Q: Particle>(lhs: &[P], rhs: &[Q]) -> Result<f64, String> {
+    if lhs.len() != rhs.len() || lhs.is_empty() {
+        return Err(format!("the numbers of particles differ or are zero: {} and {}",
+                           lhs.len(), rhs.len()));
+    }
+    let sum = lhs.iter().zip(rhs.iter())
+                 .fold(0.0, |acc, (l, r)| acc + (l.vec() - r.vec()).norm_squared());
+    Ok((sum / lhs.len() as f64).sqrt())
+}
+
+/// Finds the optimal superposition of `mobile` onto `target`.
+pub fn superpose<P: Particle, Q: Particle>(mobile: &[P], target: &[Q])
+    -> Result<Superposition, String> {
+    let weights = vec![1.0; mobile.len()];
+    superpose_weighted(mobile, target, &weights)
+}
+
+/// Finds the optimal superposition of `mobile` onto `target` weighting
+/// each pair, e.g. by mass.
+pub fn superpose_weighted<P: Particle, Q: Particle>(mobile: &[P], target: &[Q],
+                                                    weights: &[f64])
+    -> Result<Superposition, String> {
+    if mobile.len() != target.len() || mobile.len() != weights.len() {
+        return Err(format!("the numbers of particles differ: {}, {} and {} weights",
+                           mobile.len(), target.len(), weights.len()));
+    }
+    let total = weights.iter().fold(0.0, |acc, w| acc + w);
+    if mobile.is_empty() || total <= 0.0 {
+        return Err("no particle to superpose".to_owned());
+    }
+
+    let mut mc = Vector3::<f64>::zeros();
+    let mut tc = Vector3::<f64>::zeros();
+    for ((m, t), w) in mobile.iter().zip(target.iter()).zip(weights.iter()) {
+        mc += m.vec() * *w;
+        tc += t.vec() * *w;
+    }
+    mc /= total;
+    tc /= total;
+
+    // covariance matrix between centered coordinates
+    let mut cov = Matrix3::<f64>::zeros();
+    for ((m, t), w) in mobile.iter().zip(target.iter()).zip(weights.iter()) {
+        cov += (m.vec() - mc) * (t.vec() - tc).transpose() * *w;
+    }
+
+    let svd = cov.svd(true, true);
+    let u  = svd.u.unwrap();
+    let vt = svd.v_t.unwrap();
+    // avoid reflection
+    let d = if (vt.transpose() * u.transpose()).determinant() < 0.0 {-1.0} else {1.0};
+    let rotation = vt.transpose() * Matrix3::new(1.0, 0.0, 0.0,
+                                                 0.0, 1.0, 0.0,
+                                                 0.0, 0.0, d) * u.transpose();
+    let translation = tc - rotation * mc;
+
+    let mut sum = 0.0;
+    for ((m, t), w) in mobile.iter().zip(target.iter()).zip(weights.iter()) {
+        sum += (rotation * m.vec() + translation - t.vec()).norm_squared() * *w;
+    }
+    Ok(Superposition{
+        rotation    : rotation,
+        translation : translation,
+        rmsd        : (sum / total).sqrt(),
+    })
+}
+
+/// Moves the particles by the superposition.
+pub fn transform<P: ParticleMut>(particles: &mut [P], sp: &Superposition) {
+    for p in particles.iter_mut() {
+        let v = sp.apply(&p.vec());
+        p.set_vec(v);
+    }
+}
+
+/// Moves all the atoms in all the models by the superposition.
+pub fn transform_structure(structure: &mut Structure, sp: &Superposition) {
+    for model in structure.models.iter_mut() {
+        transform(&mut model.atoms,   sp);
+        transform(&mut model.hetatms, sp);
+    }
+}
@@ -0,0 +1,109 @@
+//! module xyz: XYZ files of elements and coordinates in angstroms.
+//!
+//! Each frame in a file becomes a model. An atom is read as an ATOM record
+//! named after its element, all in a residue UNK 1 of chain A.
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::string::String;
+use super::element;
+use super::pdb::{Atom, AtomBuilder, AtomData, Model, Structure};
+
+pub struct Reader<R> {
+    reader : BufReader<R>,
+}
+
+impl<R: Read> Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Reader{reader: BufReader::new(inner)}
+    }
+
+    /// Reads all the frames. The comment line of the first frame is the
+    /// title.
+    pub fn read_structure(&mut self) -> Result<Structure, String> {
+        let mut structure = Structure::new();
+        let mut lines = self.reader.by_ref().lines();
+        loop {
+            let count = match lines.next() {
+                Some(line) => try!(line.map_err(|e| e.to_string())),
+                None       => break,
+            };
+            if count.trim().is_empty() {
+                continue;
+            }
+            let count = try!(count.trim().parse::<usize>().map_err(
+                |e| format!("{} at\n{}", e, count)));
+            let mut next = || -> Result<String, String> {
+                match lines.next() {
+                    Some(line) => line.map_err(|e| e.to_string()),
+                    None       => Err("unexpected end of file".to_owned()),
+                }
+            };
+            let comment = try!(next());
+            let mut model = Model::new(structure.models.len() as i32 + 1);
+            for i in 0..count {
+                model.atoms.push(try!(read_atom(&try!(next()), i as i32 + 1)));
+            }
+            if structure.models.is_empty() {
+                structure.header.title = comment.trim().to_owned();
+            }
+            structure.models.push(model);
+        }
+        Ok(structure)
+    }
+}
+
+fn read_atom(line: &str, serial: i32) -> Result<Atom, String> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 4 {
+        return Err(format!("invalid atom line:\n{}", line));
+    }
+    let coordinate = |i: usize| -> Result<f64, String> {
+        fields[i].parse::<f64>().map_err(|e| format!("{} at\n{}", e, line))
+    };
+    // elements may also be written as atomic numbers
+    let symbol = match fields[0].parse::<u8>() {
+        Ok(number) => try!(element::by_number(number).ok_or(
+            format!("unknown element at\n{}", line))).symbol,
+        Err(_) => try!(element::by_symbol(fields[0]).ok_or(
+            format!("unknown element at\n{}", line))).symbol,
+    };
+    Ok(AtomBuilder::new()
+        .atom_number(serial)
+        .atom_residue_chain(&symbol.to_uppercase(), "UNK", 'A')
+        .element(&symbol.to_uppercase())
+        .pos(try!(coordinate(1)), try!(coordinate(2)), try!(coordinate(3)))
+        .occupancy(1.0)
+        .temperature_factor(0.0)
+        .finalize())
+}
+
+pub struct Writer<W: Write> {
+    writer : BufWriter<W>,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn new(inner: W) -> Self {
+        Writer{writer: BufWriter::new(inner)}
+    }
+
+    /// Writes each model as a frame. Elements are inferred from atom names
+    /// if they are not written (see `element::of`); unknown ones are X.
+    pub fn write_structure(&mut self, structure: &Structure) -> io::Result<()> {
+        for model in structure.models.iter() {
+            try!(writeln!(self.writer, "{}", model.len()));
+            try!(writeln!(self.writer, "{}", structure.header.title));
+            for atom in model.atoms.iter() {
+                try!(self.write_atom(atom, atom.x, atom.y, atom.z));
+            }
+            for hetatm in model.hetatms.iter() {
+                try!(self.write_atom(hetatm, hetatm.x, hetatm.y, hetatm.z));
+            }
+        }
+        self.writer.flush()
+    }
+
+    fn write_atom<T: AtomData>(&mut self, atom: &T, x: f64, y: f64, z: f64)
+        -> io::Result<()> {
+        let symbol = element::of(atom).map_or("X", |e| e.symbol);
+        writeln!(self.writer, "{:<2} {:12.6} {:12.6} {:12.6}", symbol, x, y, z)
+    }
+}
@@ -0,0 +1,43 @@
+extern crate rustymill as mill;
+use mill::pdb::{AtomData, ResidueId};
+use mill::align::Scoring;
+
+fn atoms(lines: &[&str]) -> Vec<mill::pdb::Atom> {
+    lines.iter().map(|l| l.parse::<mill::pdb::Atom>().unwrap()).collect()
+}
+
+#[test]
+fn align_sequence() {
+    let lhs: Vec<ResidueId> = ["MET", "ALA", "GLY", "LYS", "TRP"].iter().enumerate()
+        .map(|(i, n)| ResidueId::new('A', i as i32 + 1, ' ', n)).collect();
+    let rhs: Vec<ResidueId> = ["MET", "GLY", "LEU", "TRP"].iter().enumerate()
+        .map(|(i, n)| ResidueId::new('A', i as i32 + 1, ' ', n)).collect();
+    let path = mill::align::align(&lhs, &rhs, &Scoring::default());
+    assert_eq!(path, vec![(Some(0), Some(0)), (Some(1), None), (Some(2), Some(1)),
+                          (Some(3), Some(2)), (Some(4), Some(3))]);
+}
+
+#[test]
+fn match_atoms() {
+    let wild = atoms(&[
+    "ATOM      1  N   MET A   1       1.000   0.000   0.000  1.00 35.88           N  ",
+    "ATOM      2  CA  MET A   1       2.000   0.000   0.000  1.00 35.88           C  ",
+    "ATOM      3  CA  ALA A   2       3.000   0.000   0.000  1.00 35.88           C  ",
+    "ATOM      4  CB  ALA A   2       3.000   1.000   0.000  1.00 35.88           C  ",
+    "ATOM      5  CA  GLY A   3       4.000   0.000   0.000  1.00 35.88           C  ",
+    "ATOM      6  CA  TRP A   4       5.000   0.000   0.000  1.00 35.88           C  ",
+    ]);
+    let mutant = atoms(&[
+    "ATOM      1  CA  MET B   1       1.000   2.000   0.000  1.00 35.88           C  ",
+    "ATOM      2  CA  ALA B   2       2.000   2.000   0.000  1.00 35.88           C  ",
+    "ATOM      3  CB  LEU B   3       3.000   3.000   0.000  1.00 35.88           C  ",
+    "ATOM      4  CA  LEU B   3       3.000   2.000   0.000  1.00 35.88           C  ",
+    "ATOM      5  CA  TRP B   4       4.000   2.000   0.000  1.00 35.88           C  ",
+    ]);
+    let pairs = mill::align::match_atoms(&wild, &mutant, &Scoring::default());
+    assert_eq!(pairs, vec![(1, 0), (2, 1), (4, 3), (5, 4)]);
+    assert_eq!(wild[pairs[2].0].residue_name(), "GLY");
+
+    let sp = mill::align::superpose_matched(&wild, &mutant, &Scoring::default()).unwrap();
+    assert!(sp.rmsd < 1e-6);
+}
@@ -0,0 +1,146 @@
+extern crate rustymill as mill;
+extern crate nalgebra;
+use nalgebra::Vector3;
+use mill::Atom;
+use mill::backmap::{self, Backmapping, place};
+use mill::dihedral::dihedral;
+use mill::pdb::{AtomData, Model, Structure};
+
+mod common;
+use common::{alpha, backbone, trace};
+
+#[test]
+fn place_atoms() {
+    let (a, b, c) = (Vector3::new(1.0, 1.0, 0.0), Vector3::new(0.0, 0.0, 0.0),
+                     Vector3::new(1.5, 0.0, 0.0));
+    for torsion in [-120.0, -60.0, 0.0, 75.0, 180.0].iter() {
+        let d = place(&a, &b, &c, 1.3, 110.0, *torsion);
+        assert!(((d - c).norm() - 1.3).abs() < 1e-10);
+        let angle = (b - c).angle(&(d - c)).to_degrees();
+        assert!((angle - 110.0).abs() < 1e-8);
+        let phi = dihedral(&a, &b, &c, &d);
+        assert!((phi - torsion).abs() < 1e-8 || (phi - torsion).abs() > 359.9);
+    }
+}
+
+#[test]
+fn rebuild_backbone() {
+    let mut angles = vec![(-57.0, -47.0); 8];
+    angles.extend(vec![(-120.0, 130.0); 8]);
+    let original = backbone(&angles);
+    let alpha = alpha(&original);
+    let rebuilt = backmap::backbone(&alpha).unwrap();
+    assert_eq!(rebuilt.len(), 16);
+    // the fragments are homopolymers; the peptide at the junction of the
+    // helix and the strand deviates most
+    let mut errors = Vec::new();
+    for (r, o) in rebuilt.iter().zip(original.iter()) {
+        assert_eq!(r.ca, o[1]);
+        assert!(((r.c - r.o).norm() - 1.231).abs() < 1e-6);
+        errors.push((r.n - o[0]).norm());
+        errors.push((r.c - o[2]).norm());
+    }
+    assert!(errors.iter().all(|e| *e < 0.8));
+    assert!(errors.iter().sum::<f64>() / (errors.len() as f64) < 0.3);
+    assert!(rebuilt[..15].iter().all(|r| r.oxt.is_none()));
+    assert!(rebuilt[15].oxt.is_some());
+
+    // short chains are rebuilt as extended ones
+    assert_eq!(backmap::backbone(&alpha[..3]).unwrap().len(), 3);
+    let lone = backmap::backbone(&alpha[..1]).unwrap();
+    assert_eq!(lone[0].ca, alpha[0]);
+    assert!(((lone[0].n - lone[0].ca).norm() - 1.458).abs() < 1e-6);
+    assert!(((lone[0].c - lone[0].ca).norm() - 1.525).abs() < 1e-6);
+    assert!(lone[0].oxt.is_some());
+    assert!(backmap::backbone(&[]).is_err());
+}
+
+#[test]
+fn side_chains() {
+    let names = ["MET", "ALA", "GLY", "PHE", "TYR", "LYS", "TRP", "ILE", "THR", "PRO"];
+    let original = backbone(&vec![(-120.0, 130.0); names.len()]);
+    let atoms = Backmapping::new(true).chain(&trace(&alpha(&original), &names, 'A'), 1).unwrap();
+    let residue = |number: i32| -> Vec<&Atom> {
+        atoms.iter().filter(|a| a.residue_number() == number).collect()
+    };
+    let atom_names = |number: i32| -> Vec<&str> {
+        residue(number).iter().map(|a| a.atom_name()).collect()
+    };
+    assert_eq!(atom_names(2), vec!["N", "CA", "C", "O", "CB"]);
+    assert_eq!(atom_names(3), vec!["N", "CA", "C", "O"]);
+    assert_eq!(atom_names(5), vec!["N", "CA", "C", "O", "CB", "CG", "CD1", "CD2", "CE1", "CE2",
+                                   "CZ", "OH"]);
+    assert_eq!(atom_names(10), vec!["N", "CA", "C", "O", "CB", "CG", "CD", "OXT"]);
+    assert_eq!(residue(7).len(), 14);
+    assert_eq!(atoms.iter().map(|a| a.atom_number()).collect::<Vec<i32>>(),
+               (1..atoms.len() as i32 + 1).collect::<Vec<i32>>());
+    assert!(atoms.iter().all(|a| a.element_symbol() == &a.atom_name()[..1]));
+
+    let find = |number: i32, name: &str| {
+        let a = residue(number).into_iter().find(|a| a.atom_name() == name).unwrap();
+        Vector3::new(a.x, a.y, a.z)
+    };
+    // L-amino acids
+    for number in [1, 2, 4, 8].iter() {
+        let chirality = dihedral(&find(*number, "N"), &find(*number, "C"), &find(*number, "CA"),
+                                 &find(*number, "CB"));
+        assert!((chirality - 122.69).abs() < 1e-6);
+    }
+    // the aromatic ring is closed
+    assert!(((find(4, "CZ") - find(4, "CE2")).norm() - 1.39).abs() < 0.01);
+    // no side-chain atom clashes with another residue
+    let backbone = ["N", "CA", "C", "O", "OXT"];
+    for a in atoms.iter().filter(|a| !backbone.contains(&a.atom_name())) {
+        for b in atoms.iter().filter(|b| b.residue_number() != a.residue_number()) {
+            let d = (Vector3::new(a.x, a.y, a.z) - Vector3::new(b.x, b.y, b.z)).norm();
+            assert!(d > 2.5, "{} {} {}", a, b, d);
+        }
+    }
+}
+
+#[test]
+fn valine() {
+    // N-CA-CB-CG1 and N-CA-CB-CG2 of a valine in the t rotamer
+    let reference = (177.0, -63.0);
+    let names = ["ALA", "VAL", "ALA"];
+    let original = backbone(&vec![(-120.0, 130.0); names.len()]);
+    let atoms = Backmapping::new(true).chain(&trace(&alpha(&original), &names, 'A'), 1).unwrap();
+    let find = |name: &str| {
+        let a = atoms.iter().find(|a| a.residue_number() == 2 && a.atom_name() == name).unwrap();
+        Vector3::new(a.x, a.y, a.z)
+    };
+    let chi = |name: &str| dihedral(&find("N"), &find("CA"), &find("CB"), &find(name));
+    let offset = |a: f64, b: f64| (b - a + 720.0) % 360.0;
+    assert!((offset(chi("CG1"), chi("CG2")) - offset(reference.0, reference.1)).abs() < 1.0);
+}
+
+#[test]
+fn structure() {
+    let original = backbone(&vec![(-57.0, -47.0); 6]);
+    let mut model = Model::new(1);
+    model.atoms = trace(&alpha(&original), &["ALA"; 6], 'A');
+    // a chain break in chain B
+    let mut second = trace(&alpha(&original), &["GLY"; 6], 'B');
+    for atom in second[3..].iter_mut() {
+        atom.x += 10.0;
+    }
+    model.atoms.extend(second);
+    let mut structure = Structure::new();
+    structure.models.push(model);
+
+    let rebuilt = Backmapping::new(false).structure(&structure).unwrap();
+    // a lone CA between two breaks
+    let mut lone = structure.clone();
+    for atom in lone.models[0].atoms[7..].iter_mut() {
+        atom.x += 10.0;
+    }
+    let model = &Backmapping::new(true).structure(&lone).unwrap().models[0];
+    assert_eq!(model.atoms.len(), 6 * 5 + 1 + 6 * 4 + 3);
+    assert_eq!(model.atoms.iter().filter(|a| a.atom_name() == "OXT").count(), 4);
+
+    let model = &rebuilt.models[0];
+    assert_eq!(model.atoms.len(), 6 * 4 + 1 + 6 * 4 + 2);
+    assert_eq!(model.atoms.iter().filter(|a| a.atom_name() == "OXT").count(), 3);
+    assert_eq!(model.ters.len(), 2);
+    assert!(model.atoms.iter().all(|a| a.atom_name() != "CB"));
+}
@@ -0,0 +1,140 @@
+extern crate rustymill as mill;
+use mill::cg::{Mapping, Member, Position, Scheme, THREE_SITE_DNA};
+use mill::pdb::{AtomData, Reader};
+
+const DATA: &'static [u8] = b"\
+ATOM      1  N   GLY A   1       0.000   0.000   0.000  1.00 10.00           N  
+ATOM      2  CA  GLY A   1       1.460   0.000   0.000  1.00 20.00           C  
+ATOM      3  C   GLY A   1       2.000   1.400   0.000  1.00 30.00           C  
+ATOM      4  O   GLY A   1       1.300   2.400   0.000  1.00 40.00           O  
+ATOM      5  N   SER A   2       3.300   1.500   0.000  1.00 10.00           N  
+ATOM      6  CA  SER A   2       4.000   2.800   0.000  1.00 10.00           C  
+ATOM      7  C   SER A   2       5.500   2.600   0.000  1.00 10.00           C  
+ATOM      8  O   SER A   2       6.000   1.500   0.000  1.00 10.00           O  
+ATOM      9  CB  SER A   2       3.600   3.600   1.200  1.00 10.00           C  
+ATOM     10  OG  SER A   2       3.600   3.600   2.600  1.00 10.00           O  
+TER      11      SER A   2                                                      
+ATOM     12  N   ALA B   1      10.000   0.000   0.000  1.00 10.00           N  
+ATOM     13  CA  ALA B   1      11.460   0.000   0.000  1.00 10.00           C  
+ATOM     14  C   ALA B   1      12.000   1.400   0.000  1.00 10.00           C  
+HETATM   15  O   HOH A 101      20.000   0.000   0.000  1.00 10.00           O  
+END                                                                             
+";
+
+#[test]
+fn alpha() {
+    let structure = Reader::new(DATA).read_structure().unwrap();
+    let cg = Mapping::new(Position::Alpha).structure(&structure).unwrap();
+    let model = &cg.models[0];
+    assert_eq!(model.atoms.len(), 3);
+    assert!(model.hetatms.is_empty());
+    assert_eq!(model.ters.len(), 2);
+
+    let bead = &model.atoms[1];
+    assert_eq!(bead.atom_name(),      "CA");
+    assert_eq!(bead.residue_name(),   "SER");
+    assert_eq!(bead.residue_number(), 2);
+    assert_eq!(bead.atom_number(),    2);
+    assert_eq!((bead.x, bead.y, bead.z), (4.0, 2.8, 0.0));
+    assert_eq!(model.atoms[0].temperature_factor(), 25.0);
+    assert_eq!(model.atoms[2].chain_id(), 'B');
+}
+
+#[test]
+fn side_chain() {
+    let structure = Reader::new(DATA).read_structure().unwrap();
+    let mut mapping = Mapping::new(Position::SideChain);
+    mapping.name = "SC".to_owned();
+    let cg = mapping.structure(&structure).unwrap();
+    let atoms = &cg.models[0].atoms;
+    assert_eq!(atoms[0].atom_name(), "SC");
+    mapping.name = "Cα1".to_owned();
+    assert!(mapping.structure(&structure).is_err());
+    mapping.name = "SIDECHAIN".to_owned();
+    assert_eq!(mapping.structure(&structure).unwrap().models[0].atoms[0].atom_name(), "SIDE");
+    // glycine has no side chain
+    assert_eq!((atoms[0].x, atoms[0].y), (1.46, 0.0));
+    // CB and OG, weighted by mass
+    let z = (12.011 * 1.2 + 15.999 * 2.6) / (12.011 + 15.999);
+    assert!((atoms[1].z - z).abs() < 1e-9);
+}
+
+#[test]
+fn center_of_mass() {
+    let structure = Reader::new(DATA).read_structure().unwrap();
+    let cg = Mapping::new(Position::CenterOfMass).structure(&structure).unwrap();
+    let bead = &cg.models[0].atoms[2];
+    assert_eq!(bead.atom_name(), "CM");
+    let x = (14.007 * 10.0 + 12.011 * (11.46 + 12.0)) / (14.007 + 2.0 * 12.011);
+    assert!((bead.x - x).abs() < 1e-9);
+}
+
+const SCHEME: &'static str = r#"
+# a Martini-like mapping
+weights = "equal"
+
+[GLY]
+BB = ["N", "CA", "C", "O"]
+
+[SER]
+BB  = [
+    "N", "CA",   # an array may span lines
+    "C", "O",
+]
+SC1 = ["CB:1.0", "OG:3.0"]
+"#;
+
+#[test]
+fn parse_scheme() {
+    let scheme = SCHEME.parse::<Scheme>().unwrap();
+    assert_eq!(scheme.residues.len(), 2);
+    let ser = scheme.residue("SER").unwrap();
+    assert_eq!(ser.len(), 2);
+    assert_eq!(ser[0].atoms.len(), 4);
+    assert_eq!(ser[1].name, "SC1");
+    assert_eq!(ser[1].atoms[1], Member{name: "OG".to_owned(), weight: Some(3.0)});
+    assert_eq!(ser[0].atoms[0].weight, Some(1.0));
+
+    let dna = THREE_SITE_DNA.parse::<Scheme>().unwrap();
+    assert_eq!(dna.residue("DA").unwrap()[0].atoms[3].name, "O5'");
+    assert_eq!(dna.residue("DA").unwrap()[0].atoms[3].weight, None);
+
+    assert!("[GLY]\nBB = [\"N\"".parse::<Scheme>().is_err());
+    assert!("[GLY]\nBB = [\"N\"]\nBB = [\"CA\"]".parse::<Scheme>().is_err());
+    assert!("weights = \"heavy\"".parse::<Scheme>().is_err());
+    assert!("[GLY]\nCα1 = [\"CA\"]".parse::<Scheme>().is_err());
+    assert!("[GLY]\nBACKBONE = [\"CA\"]".parse::<Scheme>().is_err());
+    assert!("BB = [\"N\"]".parse::<Scheme>().is_err());
+}
+
+#[test]
+fn apply_scheme() {
+    let structure = Reader::new(DATA).read_structure().unwrap();
+    let scheme = SCHEME.parse::<Scheme>().unwrap();
+    let (cg, report) = scheme.structure(&structure).unwrap();
+    let atoms = &cg.models[0].atoms;
+    assert_eq!(atoms.len(), 3);
+    assert_eq!(atoms[2].atom_name(), "SC1");
+    assert_eq!(atoms[2].atom_number(), 3);
+    assert!((atoms[2].z - (1.2 + 3.0 * 2.6) / 4.0).abs() < 1e-9);
+    assert_eq!(cg.models[0].ters.len(), 1);
+
+    // ALA of chain B is not defined
+    assert_eq!(report.unmapped.len(), 1);
+    assert_eq!(report.unmapped[0].to_string(), "B:ALA1");
+    assert!(report.missing.is_empty());
+}
+
+#[test]
+fn report_missing_atoms() {
+    let structure = Reader::new(DATA).read_structure().unwrap();
+    let scheme = "[ALA]\nBB = [\"N\", \"CA\", \"C\", \"O\"]\nSC1 = [\"CB\"]"
+        .parse::<Scheme>().unwrap();
+    let (cg, report) = scheme.structure(&structure).unwrap();
+    // BB is placed without O and SC1 is not placed
+    assert_eq!(cg.models[0].atoms.len(), 1);
+    assert_eq!(report.missing.len(), 2);
+    assert_eq!(report.missing[0].bead, "BB");
+    assert_eq!(report.missing[0].atoms, vec!["O".to_owned()]);
+    assert_eq!(report.missing[1].atoms, vec!["CB".to_owned()]);
+}
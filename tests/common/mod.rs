@@ -0,0 +1,45 @@
+//! Structures with ideal geometry shared by the tests.
+#![allow(dead_code)]
+use nalgebra::Vector3;
+use mill::{Atom, AtomBuilder};
+pub use mill::backmap::place;
+
+/// CA positions of an ideal alpha helix: 100 degrees and 1.5 angstroms per
+/// residue on a radius of 2.3 angstroms.
+pub fn helix(residues: usize) -> Vec<Vector3<f64>> {
+    (0..residues).map(|i| {
+        let t = (100.0 * i as f64).to_radians();
+        Vector3::new(2.3 * t.cos(), 2.3 * t.sin(), 1.5 * i as f64)
+    }).collect()
+}
+
+/// N, CA and C of a chain with ideal geometry and the given phi and psi.
+pub fn backbone(angles: &[(f64, f64)]) -> Vec<[Vector3<f64>; 3]> {
+    let mut residues = vec![[Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.458, 0.0, 0.0),
+                             Vector3::new(2.009, 1.422, 0.0)]];
+    for k in 1..angles.len() {
+        let [n, ca, c] = residues[k - 1];
+        let next_n  = place(&n, &ca, &c, 1.329, 116.2, angles[k - 1].1);
+        let next_ca = place(&ca, &c, &next_n, 1.458, 121.7, 180.0);
+        let next_c  = place(&c, &next_n, &next_ca, 1.525, 111.2, angles[k].0);
+        residues.push([next_n, next_ca, next_c]);
+    }
+    residues
+}
+
+/// CA atoms at the given positions, numbered from 1.
+pub fn trace(positions: &[Vector3<f64>], names: &[&str], chain_id: char) -> Vec<Atom> {
+    positions.iter().zip(names.iter()).enumerate().map(|(i, (p, name))| {
+        AtomBuilder::new()
+            .atom_number(i as i32 + 1)
+            .atom_residue_chain("CA", name, chain_id)
+            .residue_number(i as i32 + 1)
+            .pos(p[0], p[1], p[2])
+            .finalize()
+    }).collect()
+}
+
+/// The CA positions of a backbone.
+pub fn alpha(residues: &[[Vector3<f64>; 3]]) -> Vec<Vector3<f64>> {
+    residues.iter().map(|r| r[1]).collect()
+}
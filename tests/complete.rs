@@ -0,0 +1,281 @@
+extern crate rustymill as mill;
+extern crate nalgebra;
+use nalgebra::Vector3;
+use mill::Atom;
+use mill::backmap::Backmapping;
+use mill::complete::{Completion, Naming, Report, protonation_state};
+use mill::dihedral::dihedral;
+use mill::pdb::{AtomData, AtomDataMut, HetatmBuilder, Model, Structure};
+
+mod common;
+use common::{alpha, backbone, trace};
+
+const NAMES: [&'static str; 20] = ["MET", "GLY", "ALA", "SER", "CYS", "THR", "VAL", "ILE",
+                                   "LEU", "PHE", "TYR", "TRP", "HIS", "ASP", "ASN", "GLU",
+                                   "GLN", "LYS", "ARG", "PRO"];
+
+/// Heavy atoms of a strand with side chains, numbered from 1.
+fn strand(names: &[&str]) -> Model {
+    let residues = backbone(&vec![(-120.0, 130.0); names.len()]);
+    let mut model = Model::new(1);
+    model.atoms = Backmapping::new(true).chain(&trace(&alpha(&residues), names, 'A'), 1).unwrap();
+    model
+}
+
+fn vec(atom: &Atom) -> Vector3<f64> {
+    Vector3::new(atom.x, atom.y, atom.z)
+}
+
+fn find<'a>(atoms: &'a [Atom], number: i32, name: &str) -> Option<&'a Atom> {
+    atoms.iter().find(|a| a.residue_number() == number && a.atom_name() == name)
+}
+
+fn atom_names(atoms: &[Atom], number: i32) -> Vec<&str> {
+    atoms.iter().filter(|a| a.residue_number() == number).map(|a| a.atom_name()).collect()
+}
+
+fn is_hydrogen(atom: &Atom) -> bool {
+    atom.atom_name().starts_with('H')
+}
+
+#[test]
+fn heavy_atoms() {
+    let original = strand(&NAMES);
+    let mut model = original.clone();
+    // the side chain of LYS beyond CB, O of GLY and OXT
+    model.atoms.retain(|a| {
+        !(a.residue_number() == 18 && ["CG", "CD", "CE", "NZ"].contains(&a.atom_name())) &&
+            !(a.residue_number() == 2 && a.atom_name() == "O") && a.atom_name() != "OXT"
+    });
+    // an old name and a residue without CA
+    for atom in model.atoms.iter_mut() {
+        if atom.residue_number() == 8 && atom.atom_name() == "CD1" {
+            atom.set_atom_name("CD");
+        }
+    }
+    model.atoms.retain(|a| !(a.residue_number() == 3 && a.atom_name() == "CA"));
+
+    let completion = Completion{hydrogens: false, ..Completion::new()};
+    let mut report = Report::new();
+    let completed = completion.model(&model, &mut report);
+    let added: Vec<(String, Vec<String>)> = report.added.iter()
+        .map(|a| (a.residue.to_string(), a.atoms.clone())).collect();
+    assert_eq!(added, vec![
+        ("A:GLY2".to_owned(), vec!["O".to_owned()]),
+        ("A:LYS18".to_owned(), vec!["CG".to_owned(), "CD".to_owned(), "CE".to_owned(),
+                                   "NZ".to_owned()]),
+        ("A:PRO20".to_owned(), vec!["OXT".to_owned()]),
+    ]);
+    assert_eq!(report.incomplete.iter().map(|r| r.to_string()).collect::<Vec<String>>(),
+               vec!["A:ALA3"]);
+    assert!(report.unknown.is_empty());
+
+    let atoms = &completed.atoms;
+    assert_eq!(atom_names(atoms, 8), atom_names(&original.atoms, 8));
+    assert_eq!(atom_names(atoms, 18), atom_names(&original.atoms, 18));
+    assert_eq!(atoms.len(), original.atoms.len() - 1);
+    assert!(atoms.iter().enumerate().all(|(i, a)| a.atom_number() == i as i32 + 1));
+    assert_eq!(completed.ters.len(), 1);
+    let distance = |number: i32, lhs: &str, rhs: &str| {
+        (vec(find(atoms, number, lhs).unwrap()) - vec(find(atoms, number, rhs).unwrap())).norm()
+    };
+    assert!((distance(18, "CB", "CG") - 1.52).abs() < 0.01);
+    assert!((distance(18, "CE", "NZ") - 1.49).abs() < 0.01);
+    assert!((distance(2, "C", "O") - 1.231).abs() < 1e-6);
+    assert!((distance(20, "C", "OXT") - 1.25).abs() < 1e-6);
+    // the carbonyl is trans to the next N
+    let o = vec(find(atoms, 2, "O").unwrap());
+    let n = vec(find(atoms, 3, "N").unwrap());
+    let ca = vec(find(atoms, 2, "CA").unwrap());
+    let c = vec(find(atoms, 2, "C").unwrap());
+    assert!((dihedral(&n, &ca, &c, &o).abs() - 180.0).abs() < 1e-6);
+}
+
+#[test]
+fn hydrogens() {
+    let completed = Completion::new().model(&strand(&NAMES), &mut Report::new());
+    let atoms = &completed.atoms;
+    let counts: Vec<usize> = (1..21).map(|number| {
+        atoms.iter().filter(|a| a.residue_number() == number && is_hydrogen(a)).count()
+    }).collect();
+    // the N-terminus has three hydrogens and PRO none
+    assert_eq!(counts, vec![11, 3, 5, 5, 5, 7, 9, 11, 11, 9, 9, 10, 7, 4, 6, 6, 8, 13, 13, 7]);
+    assert_eq!(atom_names(atoms, 13)[10..].to_vec(),
+               vec!["H", "HA", "HB2", "HB3", "HD2", "HE1", "HE2"]);
+    assert!(atoms.iter().filter(|a| is_hydrogen(a)).all(|a| a.element_symbol() == "H"));
+
+    for h in atoms.iter().filter(|a| is_hydrogen(a)) {
+        let nearest = atoms.iter().filter(|a| !is_hydrogen(a)).map(|a| {
+            ((vec(a) - vec(h)).norm(), a.atom_name())
+        }).fold((1e9, ""), |m, x| if x.0 < m.0 {x} else {m});
+        let length = match &nearest.1[..1] {
+            "C" => 1.09, "N" => 1.01, "O" => 0.96, _ => 1.34,
+        };
+        assert!((nearest.0 - length).abs() < 1e-6, "{} {:?}", h, nearest);
+        // no hydrogen is near the other atoms
+        for a in atoms.iter().filter(|a| a.atom_number() != h.atom_number()) {
+            let d = (vec(a) - vec(h)).norm();
+            assert!(d > 1.5 || d == nearest.0, "{} {} {}", h, a, d);
+        }
+    }
+
+    let position = |number: i32, name: &str| vec(find(atoms, number, name).unwrap());
+    // HA and CB of L-amino acids, HA3 of GLY at the place of CB
+    for number in 3..21 {
+        let (n, c, ca) = (position(number, "N"), position(number, "C"), position(number, "CA"));
+        let cb = dihedral(&n, &c, &ca, &position(number, "CB"));
+        let ha = dihedral(&n, &c, &ca, &position(number, "HA"));
+        assert!((cb - ha - 240.0).abs() < 5.0 || (cb - ha + 120.0).abs() < 5.0);
+    }
+    // CG2 of VAL follows CG1 by 120 degrees as in a reference valine, and HB by 240
+    let chi = |name: &str| dihedral(&position(7, "N"), &position(7, "CA"), &position(7, "CB"),
+                                    &position(7, name));
+    let offset = |a: f64, b: f64| (b - a + 720.0) % 360.0;
+    assert!((offset(chi("CG1"), chi("CG2")) - offset(177.0, -63.0)).abs() < 1.0);
+    assert!((offset(chi("CG1"), chi("HB")) - 240.0).abs() < 5.0);
+    let (n, c, ca) = (position(2, "N"), position(2, "C"), position(2, "CA"));
+    assert!((dihedral(&n, &c, &ca, &position(2, "HA3")) - 120.0).abs() < 1e-6);
+    // the amide H is in the peptide plane
+    let h = dihedral(&position(3, "O"), &position(3, "C"), &position(4, "N"),
+                     &position(4, "H"));
+    assert!((h.abs() - 180.0).abs() < 5.0);
+
+    // hydrogens are rebuilt
+    let again = Completion::new().model(&completed, &mut Report::new());
+    assert_eq!(again.atoms.len(), atoms.len());
+    assert!(again.atoms.iter().zip(atoms.iter()).all(|(a, b)| (vec(a) - vec(b)).norm() < 1e-3));
+    // or kept
+    let mut moved = completed.clone();
+    for atom in moved.atoms.iter_mut().filter(|a| is_hydrogen(a)) {
+        atom.x += 0.1;
+    }
+    let heavy = Completion{hydrogens: false, ..Completion::new()};
+    let kept = heavy.model(&moved, &mut Report::new());
+    assert_eq!(kept.atoms.len(), atoms.len());
+    assert!(kept.atoms.iter().zip(atoms.iter()).all(|(a, b)| {
+        let moved = if is_hydrogen(a) {0.1} else {0.0};
+        a.atom_name() == b.atom_name() && ((vec(a) - vec(b)).norm() - moved).abs() < 1e-3
+    }));
+}
+
+#[test]
+fn naming() {
+    let model = strand(&["SER", "ILE", "HIS", "LYS", "PRO", "GLY"]);
+    let charmm = Completion{naming: Naming::Charmm, ..Completion::new()};
+    let atoms = charmm.model(&model, &mut Report::new()).atoms;
+    assert_eq!(atom_names(&atoms, 1), vec!["N", "CA", "C", "O", "CB", "OG", "HT1", "HT2",
+                                           "HT3", "HA", "HB1", "HB2", "HG1"]);
+    assert_eq!(atom_names(&atoms, 2), vec!["N", "CA", "C", "O", "CB", "CG1", "CG2", "CD", "HN",
+                                           "HA", "HB", "HG11", "HG12", "HG21", "HG22", "HG23",
+                                           "HD1", "HD2", "HD3"]);
+    assert_eq!(atom_names(&atoms, 6), vec!["N", "CA", "C", "OT1", "OT2", "HN", "HA1", "HA2"]);
+    assert_eq!(find(&atoms, 3, "CA").unwrap().residue_name(), "HSE");
+    assert_eq!(find(&atoms, 4, "CA").unwrap().residue_name(), "LYS");
+    assert!(find(&atoms, 4, "HZ3").is_some());
+
+    let amber = Completion{naming: Naming::Amber, ph: Some(7.0), ..Completion::new()};
+    let atoms = amber.model(&model, &mut Report::new()).atoms;
+    assert_eq!(atom_names(&atoms, 1)[6..].to_vec(),
+               vec!["H1", "H2", "H3", "HA", "HB2", "HB3", "HG"]);
+    assert_eq!(find(&atoms, 3, "CA").unwrap().residue_name(), "HIE");
+    assert_eq!(atom_names(&atoms, 6)[3..].to_vec(), vec!["O", "OXT", "H", "HA2", "HA3"]);
+
+    // an N-terminal PRO
+    let atoms = charmm.model(&strand(&["PRO", "ALA"]), &mut Report::new()).atoms;
+    assert_eq!(atom_names(&atoms, 1)[7..].to_vec(),
+               vec!["HN1", "HN2", "HA", "HB1", "HB2", "HG1", "HG2", "HD1", "HD2"]);
+
+    let atoms = Completion{hydrogens: false, ..charmm}.model(&model, &mut Report::new()).atoms;
+    assert!(atoms.iter().all(|a| !is_hydrogen(a)));
+    assert_eq!(atom_names(&atoms, 2)[7], "CD");
+}
+
+#[test]
+fn protonation() {
+    assert_eq!(protonation_state("HIS", None), "HIE");
+    assert_eq!(protonation_state("HSD", None), "HID");
+    assert_eq!(protonation_state("ASH", None), "ASH");
+    assert_eq!(protonation_state("LYS", None), "LYS");
+    assert_eq!(protonation_state("HIS", Some(5.0)), "HIP");
+    assert_eq!(protonation_state("HID", Some(7.0)), "HID");
+    assert_eq!(protonation_state("HIP", Some(7.0)), "HIE");
+    assert_eq!(protonation_state("ASP", Some(3.0)), "ASH");
+    assert_eq!(protonation_state("ASH", Some(7.0)), "ASP");
+    assert_eq!(protonation_state("GLU", Some(4.0)), "GLH");
+    assert_eq!(protonation_state("LYS", Some(11.0)), "LYN");
+    assert_eq!(protonation_state("LYS", Some(7.0)), "LYS");
+
+    let model = strand(&["GLY", "HIS", "ASP", "GLU", "LYS", "GLY"]);
+    let hydrogens = |ph: f64, number: i32| -> Vec<String> {
+        let completion = Completion{naming: Naming::Amber, ph: Some(ph), ..Completion::new()};
+        let atoms = completion.model(&model, &mut Report::new()).atoms;
+        let residue = find(&atoms, number, "CA").unwrap().residue_name().to_owned();
+        let mut names = vec![residue];
+        names.extend(atoms.iter().filter(|a| a.residue_number() == number && is_hydrogen(a))
+                          .skip(4).map(|a| a.atom_name().to_owned()));
+        names
+    };
+    assert_eq!(hydrogens(2.0, 2), vec!["HIP", "HD1", "HD2", "HE1", "HE2"]);
+    assert_eq!(hydrogens(7.0, 2), vec!["HIE", "HD2", "HE1", "HE2"]);
+    assert_eq!(hydrogens(2.0, 3), vec!["ASH", "HD2"]);
+    assert_eq!(hydrogens(7.0, 3), vec!["ASP"]);
+    assert_eq!(hydrogens(2.0, 4), vec!["GLH", "HG2", "HG3", "HE2"]);
+    assert_eq!(hydrogens(12.0, 5)[0], "LYN");
+    assert_eq!(hydrogens(12.0, 5)[7..].to_vec(), vec!["HZ2", "HZ3"]);
+    assert_eq!(hydrogens(7.0, 5)[7..].to_vec(), vec!["HZ1", "HZ2", "HZ3"]);
+
+    // a disulfide bond between two chains
+    let cys = strand(&["ALA", "CYS", "ALA"]);
+    let sg = find(&cys.atoms, 2, "SG").unwrap().clone();
+    let mut other = cys.clone();
+    for atom in other.atoms.iter_mut() {
+        atom.x += 2.04;
+        atom.y = 2.0 * sg.y - atom.y;
+        atom.z = 2.0 * sg.z - atom.z;
+        atom.set_chain_id('B');
+    }
+    let mut model = cys.clone();
+    model.atoms.extend(other.atoms);
+    let mut structure = Structure::new();
+    structure.models.push(model);
+    let amber = Completion{naming: Naming::Amber, ..Completion::new()};
+    let (completed, report) = amber.structure(&structure);
+    assert!(report.is_empty());
+    let atoms = &completed.models[0].atoms;
+    assert_eq!(completed.models[0].ters.len(), 2);
+    assert_eq!(atoms.iter().filter(|a| a.residue_name() == "CYX").count(), 2 * 10);
+    assert!(atoms.iter().all(|a| a.atom_name() != "HG"));
+    let atoms = Completion::new().model(&cys, &mut Report::new()).atoms;
+    assert!(find(&atoms, 2, "HG").is_some());
+}
+
+#[test]
+fn ligand() {
+    let mut model = strand(&["ALA", "SER"]);
+    let ca = find(&model.atoms, 1, "CA").unwrap().atom_number();
+    let first = model.atoms.len() as i32 + 1;
+    for (i, name) in ["C1", "O1"].iter().enumerate() {
+        model.hetatms.push(HetatmBuilder::new()
+            .atom_number(first + i as i32)
+            .atom_residue_chain(name, "LIG", 'B')
+            .residue_number(1)
+            .pos(10.0 + 1.4 * i as f64, 10.0, 10.0)
+            .finalize());
+    }
+    let mut structure = Structure::new();
+    structure.models.push(model);
+    structure.bonds.add(first, first + 1);
+    structure.bonds.add(ca, first);
+
+    let (completed, _) = Completion::new().structure(&structure);
+    let model = &completed.models[0];
+    let mut serials: Vec<i32> = model.atoms.iter().map(|a| a.atom_number()).collect();
+    serials.extend(model.hetatms.iter().map(|a| a.atom_number()));
+    assert_eq!(serials, (1..serials.len() as i32 + 1).collect::<Vec<i32>>());
+    let (c1, o1) = (model.hetatms[0].atom_number(), model.hetatms[1].atom_number());
+    assert_eq!(c1, model.atoms.len() as i32 + 1);
+    let ca = find(&model.atoms, 1, "CA").unwrap().atom_number();
+    assert_eq!(completed.bonds.len(), 2);
+    assert!(completed.bonds.contains(c1, o1));
+    assert!(completed.bonds.contains(ca, c1));
+}
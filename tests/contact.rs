@@ -0,0 +1,92 @@
+extern crate rustymill as mill;
+use mill::pdb::{Atom, AtomBuilder, ResidueData};
+use mill::contact::{Definition, NativeContacts, contacts};
+
+/// CA atoms of a beta hairpin: two strands 5 angstroms apart.
+fn hairpin() -> Vec<Atom> {
+    (0..16).map(|i| {
+        let (x, y) = if i < 8 {(3.8 * i as f64, 0.0)} else {(3.8 * (15 - i) as f64, 5.0)};
+        AtomBuilder::new()
+            .atom_number(i + 1).residue_number(i + 1)
+            .atom_residue_chain("CA", "ALA", 'A')
+            .pos(x, y, 0.0)
+            .element("C")
+            .finalize()
+    }).collect()
+}
+
+fn stretch(atoms: &mut [Atom], factor: f64) {
+    for atom in atoms.iter_mut() {
+        atom.y *= factor;
+    }
+}
+
+#[test]
+fn parse_definition() {
+    assert_eq!("ca".parse::<Definition>(), Ok(Definition::Alpha(8.0)));
+    assert_eq!("heavy:5".parse::<Definition>(), Ok(Definition::HeavyAtom(5.0)));
+    assert_eq!("shadow".parse::<Definition>(), Ok(Definition::Shadow(6.0, 1.0)));
+    assert_eq!("shadow:6.5:0.5".parse::<Definition>(), Ok(Definition::Shadow(6.5, 0.5)));
+    assert!("ca:x".parse::<Definition>().is_err());
+    assert!("sasa".parse::<Definition>().is_err());
+}
+
+#[test]
+fn ca_contacts() {
+    let atoms = hairpin();
+    let found = contacts(&atoms, Definition::Alpha(8.0));
+
+    let mut expected = 0;
+    for i in 0..atoms.len() {
+        for j in i+4..atoms.len() {
+            let (a, b) = (&atoms[i], &atoms[j]);
+            let d = ((a.x-b.x).powi(2) + (a.y-b.y).powi(2)).sqrt();
+            if d <= 8.0 {
+                expected += 1;
+            }
+        }
+    }
+    assert_eq!(found.len(), expected);
+    for c in found.iter() {
+        assert!(c.rhs.residue_number() - c.lhs.residue_number() >= 4);
+        assert!(c.distance <= 8.0);
+    }
+    // residue 1 faces residue 16
+    assert!(found.iter().any(|c| c.lhs.residue_number() == 1 && c.rhs.residue_number() == 16));
+}
+
+#[test]
+fn fraction_of_native_contacts() {
+    let reference = hairpin();
+    let native = NativeContacts::new(&reference, Definition::Alpha(8.0));
+    assert!(!native.is_empty());
+    assert_eq!(native.q(&reference, 1.2).unwrap(), 1.0);
+
+    let mut unfolded = hairpin();
+    stretch(&mut unfolded, 10.0);
+    assert_eq!(native.q(&unfolded, 1.2).unwrap(), 0.0);
+
+    let smooth = native.q_smooth(&reference, 5.0, 1.2).unwrap();
+    assert!(smooth > 0.99 && smooth <= 1.0);
+    assert!(native.q_smooth(&unfolded, 5.0, 1.2).unwrap() < 1e-6);
+
+    let missing: Vec<Atom> = reference.iter().take(10).cloned().collect();
+    assert!(native.q(&missing, 1.2).is_err());
+}
+
+#[test]
+fn shadow_contacts() {
+    let atoms: Vec<Atom> = [(1, 0.0), (5, 3.0), (9, 6.0)].iter().map(|&(n, x)| {
+        AtomBuilder::new()
+            .atom_number(n).residue_number(n)
+            .atom_residue_chain("CB", "ALA", 'A')
+            .pos(x, 0.0, 0.0)
+            .element("C")
+            .finalize()
+    }).collect();
+    assert_eq!(contacts(&atoms, Definition::HeavyAtom(6.5)).len(), 3);
+
+    let shadow = contacts(&atoms, Definition::Shadow(6.5, 1.0));
+    assert_eq!(shadow.len(), 2);
+    assert!(!shadow.iter().any(|c| c.lhs.residue_number() == 1 && c.rhs.residue_number() == 9));
+}
@@ -0,0 +1,84 @@
+extern crate rustymill as mill;
+use mill::dcd;
+use mill::pdb::{AtomData, Reader, Structure};
+
+const TOPOLOGY: &'static [u8] = b"\
+CRYST1   30.000   30.000   30.000  90.00  90.00  90.00 P 1           1          
+ATOM      1  CA  ALA A   1       0.000   0.000   0.000  1.00  0.00           C  
+ATOM      2  CA  ALA A   2       3.800   0.000   0.000  1.00  0.00           C  
+HETATM    3  O   HOH A 101       5.000   5.000   5.000  1.00  0.00           O  
+END                                                                             
+";
+
+fn trajectory() -> Structure {
+    let topology = Reader::new(TOPOLOGY).read_structure().unwrap();
+    let mut structure = topology.clone();
+    let mut moved = topology.models[0].clone();
+    moved.serial = 2;
+    for atom in moved.atoms.iter_mut() {
+        atom.y += 1.5;
+    }
+    structure.models.push(moved);
+    structure
+}
+
+#[test]
+fn write_and_read() {
+    let structure = trajectory();
+    let mut buffer = Vec::new();
+    dcd::Writer::new(&mut buffer).write_structure(&structure).unwrap();
+    assert_eq!(&buffer[0..8], b"\x54\0\0\0CORD");
+
+    let frames = dcd::Reader::new(&buffer[..]).read_frames().unwrap();
+    assert_eq!(frames.len(), 2);
+    // coordinates are single precision
+    for (lhs, rhs) in frames[1].positions.iter().zip(structure.models[1].positions().iter()) {
+        assert!((lhs - rhs).norm() < 1e-6);
+    }
+    let cell = frames[0].unit_cell.as_ref().unwrap();
+    assert!((cell.b - 30.0).abs() < 1e-9 && (cell.beta - 90.0).abs() < 1e-9);
+
+    let read = dcd::Reader::new(&buffer[..]).read_structure().unwrap();
+    assert_eq!(read.models[0].atoms[2].atom_name(), "X");
+}
+
+#[test]
+fn with_topology() {
+    let structure = trajectory();
+    let mut buffer = Vec::new();
+    dcd::Writer::new(&mut buffer).write_structure(&structure).unwrap();
+    let read = dcd::Reader::new(&buffer[..]).read_structure().unwrap();
+
+    let topology = Reader::new(TOPOLOGY).read_structure().unwrap();
+    let named = read.with_topology(&topology).unwrap();
+    assert_eq!(named.models.len(), 2);
+    assert_eq!(named.models[1].atoms[1].atom_name(), "CA");
+    assert_eq!(named.models[1].atoms[1].y, 1.5);
+    assert_eq!(named.models[1].hetatms[0].residue_name(), "HOH");
+
+    let mut short = topology.clone();
+    short.models[0].hetatms.clear();
+    assert!(read.with_topology(&short).is_err());
+}
+
+#[test]
+fn broken_records() {
+    let mut buffer = Vec::new();
+    dcd::Writer::new(&mut buffer).write_structure(&trajectory()).unwrap();
+    // the record of the number of atoms follows the header and the titles
+    let titles = buffer[92] as usize | (buffer[93] as usize) << 8;
+    let natoms = 92 + 4 + titles + 4;
+    assert_eq!(&buffer[natoms..natoms + 8], &[4, 0, 0, 0, 3, 0, 0, 0]);
+
+    let mut negative = buffer.clone();
+    negative[natoms + 4..natoms + 8].copy_from_slice(&[0xff, 0xff, 0xff, 0xff]);
+    assert!(dcd::Reader::new(&negative[..]).read_frames().is_err());
+
+    // a unit cell of one double
+    let cell = natoms + 12;
+    assert_eq!(&buffer[cell..cell + 4], &[48, 0, 0, 0]);
+    let mut short = buffer[..cell].to_vec();
+    short.extend(&[8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 8, 0, 0, 0]);
+    short.extend(&buffer[cell + 56..]);
+    assert!(dcd::Reader::new(&short[..]).read_frames().is_err());
+}
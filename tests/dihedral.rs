@@ -0,0 +1,111 @@
+extern crate rustymill as mill;
+extern crate nalgebra;
+use nalgebra::Vector3;
+use mill::pdb::{Atom, AtomBuilder, AtomDataMut, Structure, Model};
+use mill::dihedral::{dihedral, dihedrals, per_model};
+
+mod common;
+use common::place;
+
+/// A poly-ALA backbone (N, CA, C, O, CB) with uniform phi and psi.
+fn backbone(n: usize, phi: f64, psi: f64) -> Vec<Atom> {
+    let mut atoms = Vec::new();
+    for (i, &[n, ca, c]) in common::backbone(&vec![(phi, psi); n]).iter().enumerate() {
+        let o  = place(&n, &ca, &c, 1.231, 120.5, psi + 180.0);
+        let cb = place(&c, &n, &ca, 1.53, 110.5, -122.5);
+        for &(name, p) in [("N", n), ("CA", ca), ("C", c), ("O", o), ("CB", cb)].iter() {
+            let serial = atoms.len() as i32 + 1;
+            atoms.push(AtomBuilder::new()
+                .atom_number(serial).residue_number(i as i32 + 1)
+                .atom_residue_chain(name, "ALA", 'A')
+                .pos(p[0], p[1], p[2])
+                .finalize());
+        }
+    }
+    atoms
+}
+
+#[test]
+fn dihedral_of_points() {
+    let a = Vector3::new(1.0, 0.0, 0.0);
+    let b = Vector3::new(0.0, 0.0, 0.0);
+    let c = Vector3::new(0.0, 1.0, 0.0);
+    for &t in [-179.0, -120.0, -60.0, 0.0, 45.0, 90.0, 180.0].iter() {
+        let d = place(&a, &b, &c, 1.5, 109.5, t);
+        assert!((dihedral(&a, &b, &c, &d) - t).abs() < 1e-9);
+    }
+    let cis = Vector3::new(1.0, 1.0, 0.0);
+    assert!(dihedral(&a, &b, &c, &cis).abs() < 1e-9);
+    assert!(dihedral(&a, &b, &(b * 2.0 - a), &cis).is_nan());
+}
+
+#[test]
+fn alpha_helix() {
+    let atoms = backbone(6, -57.0, -47.0);
+    let angles = dihedrals(&atoms);
+    assert_eq!(angles.len(), 6);
+
+    assert_eq!(angles[0].phi, None);
+    assert!((angles[0].psi.unwrap() + 47.0).abs() < 1e-6);
+    assert_eq!(angles[5].psi,   None);
+    assert_eq!(angles[5].omega, None);
+    for residue in angles[1..5].iter() {
+        assert!((residue.phi.unwrap()   + 57.0).abs() < 1e-6);
+        assert!((residue.psi.unwrap()   + 47.0).abs() < 1e-6);
+        assert!((residue.omega.unwrap().abs() - 180.0).abs() < 1e-6);
+        // ALA has no chi
+        assert!(residue.chi.is_empty());
+    }
+}
+
+#[test]
+fn chain_break_and_frames() {
+    let mut atoms = backbone(6, -120.0, 130.0);
+    for atom in atoms.iter_mut().skip(15) {
+        atom.x += 10.0;
+    }
+    let angles = dihedrals(&atoms);
+    assert!(angles[1].psi.is_some());
+    assert_eq!(angles[2].psi,   None);
+    assert_eq!(angles[2].omega, None);
+    assert_eq!(angles[3].phi,   None);
+    assert!((angles[3].psi.unwrap() - 130.0).abs() < 1e-6);
+
+    let mut structure = Structure::new();
+    for serial in 1..4 {
+        let mut model = Model::new(serial);
+        model.atoms = backbone(4, -30.0 * (serial + 1) as f64, 120.0);
+        structure.models.push(model);
+    }
+    let frames = per_model(&structure).unwrap();
+    assert_eq!(frames.len(), 3);
+    for (i, frame) in frames.iter().enumerate() {
+        let expected = -30.0 * (i + 2) as f64;
+        assert!((frame[1].phi.unwrap() - expected).abs() < 1e-6);
+    }
+    assert!(per_model(&Structure::new()).is_err());
+}
+
+#[test]
+fn side_chain() {
+    let mut atoms = backbone(1, -60.0, -45.0);
+    let (n, ca, cb) = (atoms[0].clone(), atoms[1].clone(), atoms[4].clone());
+    let v = |a: &Atom| Vector3::new(a.x, a.y, a.z);
+    let cg = place(&v(&n), &v(&ca), &v(&cb), 1.52, 114.0, -65.0);
+    let cd = place(&v(&ca), &v(&cb), &cg, 1.52, 111.0, 178.0);
+    for atom in atoms.iter_mut() {
+        atom.set_residue_name("GLN");
+    }
+    for &(name, p) in [("CG", cg), ("CD", cd)].iter() {
+        atoms.push(AtomBuilder::new()
+            .atom_number(atoms.len() as i32 + 1).residue_number(1)
+            .atom_residue_chain(name, "GLN", 'A')
+            .pos(p[0], p[1], p[2]).finalize());
+    }
+    let angles = dihedrals(&atoms);
+    assert_eq!(angles[0].chi.len(), 3);
+    assert!((angles[0].chi[0].unwrap() + 65.0).abs() < 1e-6);
+    assert!((angles[0].chi[1].unwrap() - 178.0).abs() < 1e-6);
+    // OE1 is missing
+    assert_eq!(angles[0].chi[2], None);
+}
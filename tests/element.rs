@@ -0,0 +1,45 @@
+extern crate rustymill as mill;
+use mill::element;
+
+#[test]
+fn periodic_table() {
+    assert_eq!(element::ELEMENTS.len(), 118);
+    for (i, e) in element::ELEMENTS.iter().enumerate() {
+        assert_eq!(e.atomic_number as usize, i + 1);
+    }
+    let fe = element::by_symbol("FE").unwrap();
+    assert_eq!(fe.symbol,        "Fe");
+    assert_eq!(fe.atomic_number, 26);
+    assert_eq!(element::by_number(26), Some(fe));
+    assert_eq!(element::by_number(0),   None);
+    assert_eq!(element::by_number(119), None);
+    assert_eq!(element::by_symbol("Xx"), None);
+    assert_eq!(element::mass("c"), Some(12.011));
+    assert_eq!(element::by_symbol("D").unwrap().atomic_number, 1);
+}
+
+#[test]
+fn infer_element() {
+    let symbol = |atom: &str, res: &str| element::infer(atom, res).map(|e| e.symbol);
+    assert_eq!(symbol("CA",   "ALA"), Some("C"));
+    assert_eq!(symbol("CA",   "CA"),  Some("Ca"));
+    assert_eq!(symbol("HG",   "SER"), Some("H"));
+    assert_eq!(symbol("HG",   "HG"),  Some("Hg"));
+    assert_eq!(symbol("1HG1", "VAL"), Some("H"));
+    assert_eq!(symbol("SE",   "MSE"), Some("Se"));
+    assert_eq!(symbol("SD",   "MET"), Some("S"));
+    assert_eq!(symbol("OXT",  "GLY"), Some("O"));
+    assert_eq!(symbol("FE",   "HEM"), Some("Fe"));
+    assert_eq!(symbol("NA",   "HEM"), Some("N"));
+    assert_eq!(symbol("CAA",  "HEM"), Some("C"));
+    assert_eq!(symbol("CL1",  "LIG"), Some("Cl"));
+    assert_eq!(symbol("ZN",   "ZN"),  Some("Zn"));
+    assert_eq!(symbol("P",    "DA"),  Some("P"));
+
+    let atom = "ATOM      2  CA  ALA A   1       2.353 105.696  14.456  0.50 36.67            "
+        .parse::<mill::pdb::Atom>().unwrap();
+    assert_eq!(element::of(&atom).unwrap().symbol, "C");
+    let ion = "HETATM    3 CA    CA A 101       2.353 105.696  14.456  1.00 36.67            "
+        .parse::<mill::pdb::Hetatm>().unwrap();
+    assert_eq!(element::of(&ion).unwrap().symbol, "Ca");
+}
@@ -0,0 +1,94 @@
+extern crate rustymill as mill;
+extern crate nalgebra;
+use nalgebra::Vector3;
+use mill::enm::{ElasticNetwork, Network, correlation};
+
+mod common;
+use common::helix;
+
+#[test]
+fn hessian() {
+    let particles = vec![Vector3::new(0.0, 0.0, 0.0), Vector3::new(3.8, 0.0, 0.0),
+                         Vector3::new(30.0, 0.0, 0.0)];
+    let mut enm = ElasticNetwork::new(Network::Anisotropic);
+    enm.gamma = 2.0;
+    let h = enm.matrix(&particles);
+    assert_eq!((h.nrows(), h.ncols()), (9, 9));
+    assert_eq!(h[(0, 0)], 2.0);
+    assert_eq!(h[(0, 3)], -2.0);
+    assert_eq!(h[(1, 1)], 0.0);
+    assert_eq!(h[(6, 6)], 0.0);
+
+    let k = ElasticNetwork::new(Network::Gaussian).matrix(&particles);
+    assert_eq!((k.nrows(), k.ncols()), (3, 3));
+    assert_eq!((k[(0, 0)], k[(0, 1)], k[(1, 1)], k[(2, 2)]), (1.0, -1.0, 1.0, 0.0));
+
+    // particles at the same position are not connected
+    let mut particles = helix(12);
+    let copy = particles[5];
+    particles.push(copy);
+    let enm = ElasticNetwork::new(Network::Anisotropic);
+    assert!(enm.matrix(&particles).iter().all(|x| x.is_finite()));
+    let modes = enm.modes(&particles).unwrap();
+    assert!(modes.modes.iter().all(|m| m.eigenvalue.is_finite()));
+}
+
+#[test]
+fn anisotropic_modes() {
+    let particles = helix(12);
+    let modes = ElasticNetwork::new(Network::Anisotropic).modes(&particles).unwrap();
+    assert_eq!(modes.modes.len(), 3 * 12 - 6);
+    for pair in modes.modes.windows(2) {
+        assert!(0.0 < pair[0].eigenvalue && pair[0].eigenvalue <= pair[1].eigenvalue);
+    }
+    for mode in modes.modes.iter() {
+        assert!((mode.vector.norm() - 1.0).abs() < 1e-8);
+        // orthogonal to the translations
+        let dx: f64 = (0..12).map(|i| mode.vector[3 * i]).sum();
+        assert!(dx.abs() < 1e-8);
+    }
+    let fluctuations = modes.fluctuations(None);
+    assert_eq!(fluctuations.len(), 12);
+    assert!(fluctuations[0] > fluctuations[6] && fluctuations[11] > fluctuations[5]);
+    let slow = modes.fluctuations(Some(3));
+    assert!(slow.iter().zip(fluctuations.iter()).all(|(s, f)| s < f));
+}
+
+#[test]
+fn gaussian_modes() {
+    let particles = helix(12);
+    let modes = ElasticNetwork::new(Network::Gaussian).modes(&particles).unwrap();
+    assert_eq!(modes.modes.len(), 11);
+    let fluctuations = modes.fluctuations(None);
+    assert!(fluctuations[0] > fluctuations[6]);
+    assert!(modes.animate(&particles, 0, 1.0, 10).is_err());
+
+    // two parts too far apart have two zero modes
+    let mut apart = helix(6);
+    apart.extend(helix(6).into_iter().map(|p| p + Vector3::new(100.0, 0.0, 0.0)));
+    let modes = ElasticNetwork::new(Network::Gaussian).modes(&apart).unwrap();
+    assert_eq!(modes.modes.len(), 10);
+}
+
+#[test]
+fn animate() {
+    let particles = helix(10);
+    let modes = ElasticNetwork::new(Network::Anisotropic).modes(&particles).unwrap();
+    let frames = modes.animate(&particles, 0, 1.5, 4).unwrap();
+    assert_eq!(frames.len(), 4);
+    let rmsd = |frame: &Vec<Vector3<f64>>| {
+        (frame.iter().zip(particles.iter()).map(|(a, b)| (a - b).norm_squared())
+             .sum::<f64>() / particles.len() as f64).sqrt()
+    };
+    assert!(rmsd(&frames[0]) < 1e-12);
+    assert!((rmsd(&frames[1]) - 1.5).abs() < 1e-8);
+    assert!((rmsd(&frames[3]) - 1.5).abs() < 1e-8);
+    assert!(modes.animate(&particles, 24, 1.0, 4).is_err());
+}
+
+#[test]
+fn pearson() {
+    assert!((correlation(&[1.0, 2.0, 3.0], &[2.0, 4.0, 6.0]).unwrap() - 1.0).abs() < 1e-12);
+    assert!((correlation(&[1.0, 2.0, 3.0], &[3.0, 2.0, 1.0]).unwrap() + 1.0).abs() < 1e-12);
+    assert_eq!(correlation(&[1.0, 2.0], &[1.0, 1.0]), None);
+}
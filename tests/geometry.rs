@@ -0,0 +1,44 @@
+extern crate rustymill as mill;
+extern crate nalgebra  as na;
+
+fn atoms() -> Vec<mill::pdb::Atom> {
+    vec![
+    "ATOM      1  C1  LIG A   1       1.000   0.000   0.000  1.00 35.88           C  ",
+    "ATOM      2  C2  LIG A   1      -1.000   0.000   0.000  1.00 35.88           C  ",
+    "ATOM      3  O1  LIG A   1       0.000   2.000   0.000  1.00 35.88           O  ",
+    "ATOM      4  O2  LIG A   1       0.000  -2.000   0.000  1.00 35.88           O  ",
+    ].into_iter().map(|l| l.parse::<mill::pdb::Atom>().unwrap()).collect()
+}
+
+#[test]
+fn geometry() {
+    let atoms = atoms();
+    assert_eq!(mill::geometry::center_of_geometry(&atoms), Some(na::Vector3::zeros()));
+    assert_eq!(mill::geometry::center_of_geometry(Vec::<na::Vector3<f64>>::new()), None);
+
+    let rg = mill::geometry::radius_of_gyration(&atoms, None).unwrap();
+    assert!((rg - 2.5_f64.sqrt()).abs() < 1e-10);
+
+    let (lower, upper) = mill::geometry::bounding_box(&atoms).unwrap();
+    assert_eq!(lower, na::Vector3::new(-1.0, -2.0, 0.0));
+    assert_eq!(upper, na::Vector3::new( 1.0,  2.0, 0.0));
+    assert_eq!(mill::geometry::end_to_end_distance(&atoms), Some(5.0_f64.sqrt()));
+
+    let props = mill::geometry::Properties::of(&atoms).unwrap();
+    let (mc, mo) = (12.011, 15.999);
+    assert!(props.center_of_mass.norm() < 1e-10);
+    // the smallest moment is around the y axis, where oxygens are
+    assert!((props.principal_moments[0] - 2.0 * mc).abs() < 1e-8);
+    assert!((props.principal_moments[1] - 8.0 * mo).abs() < 1e-8);
+    assert!((props.principal_moments[2] - 2.0 * mc - 8.0 * mo).abs() < 1e-8);
+    assert!(props.principal_axes.column(0)[1].abs() > 1.0 - 1e-8);
+}
+
+#[test]
+fn not_a_number() {
+    let mut atoms = atoms();
+    atoms[0].x = std::f64::NAN;
+    // no panic in sorting the moments
+    let (moments, _) = mill::geometry::principal_axes(&atoms, &[1.0; 4]).unwrap();
+    assert!(moments.iter().any(|m| m.is_nan()));
+}
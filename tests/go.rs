@@ -0,0 +1,74 @@
+extern crate rustymill as mill;
+extern crate nalgebra;
+use nalgebra::Vector3;
+use mill::Atom;
+use mill::contact::Definition;
+use mill::go::{ForceConstants, GoModel};
+
+mod common;
+
+/// CA atoms of ALA in an ideal alpha helix moved by `offset` along x.
+fn helix(chain: char, residues: usize, offset: f64) -> Vec<Atom> {
+    let positions: Vec<Vector3<f64>> = common::helix(residues).into_iter()
+        .map(|p| p + Vector3::new(offset, 0.0, 0.0)).collect();
+    common::trace(&positions, &vec!["ALA"; residues], chain)
+}
+
+#[test]
+fn native_geometry() {
+    let model = GoModel::new(&helix('A', 10, 0.0), Definition::Alpha(8.0)).unwrap();
+    assert_eq!(model.beads.len(), 10);
+    assert_eq!(model.bonds.len(), 9);
+    assert_eq!(model.angles.len(), 8);
+    assert_eq!(model.dihedrals.len(), 7);
+    assert_eq!(model.dihedrals[0].beads, [0, 1, 2, 3]);
+    assert!(model.bonds.iter().all(|b| (b.native - 3.83).abs() < 0.01));
+    let phi = model.dihedrals[0].native;
+    assert!(0.0 < phi && phi < 90.0);
+    assert!(model.dihedrals.iter().all(|d| (d.native - phi).abs() < 1e-6));
+
+    // only (i, i+4) are within 8 angstroms
+    let contacts: Vec<[usize; 2]> = model.contacts.iter().map(|c| c.beads).collect();
+    assert_eq!(contacts, vec![[0, 4], [1, 5], [2, 6], [3, 7], [4, 8], [5, 9]]);
+    assert!(model.contacts.iter().all(|c| (c.native - 6.2).abs() < 0.01));
+}
+
+#[test]
+fn chains() {
+    let mut atoms = helix('A', 5, 0.0);
+    atoms.extend(helix('B', 5, 3.0));
+    let model = GoModel::new(&atoms, Definition::Alpha(6.0)).unwrap();
+    assert_eq!(model.bonds.len(), 8);
+    assert_eq!(model.dihedrals.len(), 4);
+    assert!(model.contacts.iter().all(|c| c.beads[0] < 5 && c.beads[1] >= 5));
+    assert!(!model.contacts.is_empty());
+
+    assert!(GoModel::new(&atoms[..0], Definition::default()).is_err());
+}
+
+#[test]
+fn write_topologies() {
+    let model = GoModel::new(&helix('A', 6, 0.0), Definition::Alpha(8.0)).unwrap();
+    let constants = ForceConstants::default();
+
+    let mut toml = Vec::new();
+    model.write_toml(&mut toml, &constants).unwrap();
+    let toml = String::from_utf8(toml).unwrap();
+    assert!(toml.contains("{chain = \"A\", residue = \"ALA\", number = 1, \
+                           position = [2.300, 0.000, 0.000]},"));
+    assert!(toml.contains("    {beads = [1, 5], distance = 6.203},"));
+
+    let mut top = Vec::new();
+    model.write_top(&mut top, &constants, "helix").unwrap();
+    let top = String::from_utf8(top).unwrap();
+    let section = |name: &str| -> Vec<String> {
+        top.split(&format!("[ {} ]\n", name)).nth(1).unwrap().lines()
+            .skip(1).take_while(|l| !l.is_empty()).map(|l| l.to_owned()).collect()
+    };
+    assert_eq!(section("atoms").len(), 6);
+    assert_eq!(section("bonds")[0].split_whitespace().nth(3), Some("0.38298"));
+    assert_eq!(section("dihedrals").len(), 6);
+    assert_eq!(section("pairs").len(), 2);
+    assert_eq!(section("exclusions"), vec!["     1      5", "     2      6"]);
+    assert_eq!(section("molecules"), vec!["helix 1"]);
+}
@@ -0,0 +1,79 @@
+extern crate rustymill as mill;
+use mill::gro;
+use mill::pdb::AtomData;
+
+const DATA: &'static [u8] = b"\
+two waters
+    6
+    1SOL     OW    1   0.126   1.624   1.679
+    1SOL    HW1    2   0.190   1.661   1.747
+    1SOL    HW2    3   0.177   1.568   1.613
+    2SOL     OW    4   1.275   0.053   0.622
+    2SOL    HW1    5   1.337   0.002   0.680
+    2SOL    HW2    6   1.326   0.120   0.568
+   1.86206   1.86206   1.86206
+two waters, moved
+    6
+    1SOL     OW    1   0.12600   1.62400   1.67900
+    1SOL    HW1    2   0.190   1.661   1.747
+    1SOL    HW2    3   0.177   1.568   1.613
+    2SOL     OW    4   1.275   0.053   0.622
+    2SOL    HW1    5   1.337   0.002   0.680
+    2SOL    HW2    6   1.326   0.120   0.568
+   1.86206   1.86206   1.86206
+";
+
+#[test]
+fn read_frames() {
+    let structure = gro::Reader::new(DATA).read_structure().unwrap();
+    assert_eq!(structure.models.len(), 2);
+    assert_eq!(structure.header.title, "two waters");
+    let atom = &structure.models[0].atoms[3];
+    assert_eq!(atom.atom_name(),      "OW");
+    assert_eq!(atom.residue_name(),   "SOL");
+    assert_eq!(atom.residue_number(), 2);
+    assert_eq!(atom.atom_number(),    4);
+    assert!((atom.x - 12.75).abs() < 1e-9);
+    // the width of coordinates follows the decimal points
+    assert!((structure.models[1].atoms[0].z - 16.79).abs() < 1e-9);
+
+    let cell = structure.header.unit_cell.unwrap();
+    assert!((cell.a - 18.6206).abs() < 1e-9);
+    assert!((cell.gamma - 90.0).abs() < 1e-9);
+}
+
+#[test]
+fn blank_titles() {
+    let text = String::from_utf8(DATA.to_vec()).unwrap()
+        .replace("two waters, moved", "").replace("two waters", "   ");
+    let structure = gro::Reader::new(text.as_bytes()).read_structure().unwrap();
+    assert_eq!(structure.models.len(), 2);
+    assert_eq!(structure.header.title, "");
+    assert_eq!(structure.models[1].atoms.len(), 6);
+    assert!(gro::Reader::new(&b"\n"[..]).read_structure().is_err());
+}
+
+#[test]
+fn write_and_read() {
+    let structure = gro::Reader::new(DATA).read_structure().unwrap();
+    let mut buffer = Vec::new();
+    gro::Writer::new(&mut buffer).write_structure(&structure).unwrap();
+    let text = String::from_utf8(buffer.clone()).unwrap();
+    assert_eq!(text.lines().nth(2).unwrap(), "    1SOL     OW    1   0.126   1.624   1.679");
+    assert_eq!(text.lines().nth(8).unwrap(), "   1.86206   1.86206   1.86206");
+
+    let read = gro::Reader::new(&buffer[..]).read_structure().unwrap();
+    assert_eq!(read.models.len(), 2);
+    assert_eq!(read.models[1].positions(), structure.models[1].positions());
+}
+
+#[test]
+fn triclinic_box() {
+    let cell = gro::unit_cell(&[50.0, 0.0, 0.0].into(), &[0.0, 50.0, 0.0].into(),
+                              &[25.0, 25.0, 35.355339].into());
+    assert!((cell.alpha - 60.0).abs() < 1e-4);
+    assert!((cell.beta  - 60.0).abs() < 1e-4);
+    assert!((cell.gamma - 90.0).abs() < 1e-4);
+    let v = gro::box_vectors(&cell);
+    assert!((v[2][0] - 25.0).abs() < 1e-4 && (v[2][1] - 25.0).abs() < 1e-4);
+}
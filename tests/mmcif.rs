@@ -0,0 +1,91 @@
+extern crate rustymill as mill;
+use mill::mmcif;
+use mill::pdb::AtomData;
+
+const DATA: &'static [u8] = b"\
+data_1ABC
+#
+_struct.title 'A test structure'
+_cell.length_a    50.000
+_cell.length_b    60.000
+_cell.length_c    70.000
+_cell.angle_alpha 90.00
+_cell.angle_beta  90.00
+_cell.angle_gamma 90.00
+_symmetry.space_group_name_H-M 'P 21 21 21'
+#
+loop_
+_atom_site.group_PDB
+_atom_site.id
+_atom_site.type_symbol
+_atom_site.label_atom_id
+_atom_site.label_alt_id
+_atom_site.label_comp_id
+_atom_site.label_asym_id
+_atom_site.label_seq_id
+_atom_site.pdbx_PDB_ins_code
+_atom_site.Cartn_x
+_atom_site.Cartn_y
+_atom_site.Cartn_z
+_atom_site.occupancy
+_atom_site.B_iso_or_equiv
+_atom_site.pdbx_formal_charge
+_atom_site.auth_seq_id
+_atom_site.auth_asym_id
+_atom_site.pdbx_PDB_model_num
+ATOM   1 P  P     . DG  A 1 ? 1.000 2.000 3.000 1.00 10.00 ? 11  C 1
+ATOM   2 O  \"O5'\" . DG  A 1 ? 2.000 2.000 3.000 1.00 10.00 ? 11  C 1
+HETATM 3 ZN ZN    . ZN  B . ? 5.000 5.000 5.000 0.50 20.00 2 101 C 1
+ATOM   1 P  P     . DG  A 1 ? 1.500 2.000 3.000 1.00 10.00 ? 11  C 2
+ATOM   2 O  \"O5'\" . DG  A 1 ? 2.500 2.000 3.000 1.00 10.00 ? 11  C 2
+HETATM 3 ZN ZN    . ZN  B . ? 5.500 5.000 5.000 0.50 20.00 2 101 C 2
+#
+";
+
+#[test]
+fn read_atom_site() {
+    let structure = mmcif::Reader::new(DATA).read_structure().unwrap();
+    assert_eq!(structure.header.id_code, "1ABC");
+    assert_eq!(structure.header.title, "A test structure");
+    assert_eq!(structure.header.unit_cell.as_ref().unwrap().space_group, "P 21 21 21");
+    assert_eq!(structure.models.len(), 2);
+    assert_eq!(structure.models[1].serial, 2);
+
+    let model = &structure.models[0];
+    assert_eq!(model.atoms.len(), 2);
+    assert_eq!(model.atoms[1].atom_name(), "O5'");
+    // author chain IDs and residue numbers are preferred
+    assert_eq!(model.atoms[1].chain_id(), 'C');
+    assert_eq!(model.atoms[1].residue_number(), 11);
+    assert_eq!(model.hetatms[0].residue_name(), "ZN");
+    assert_eq!(model.hetatms[0].charge(), "2+");
+    assert_eq!(model.hetatms[0].occupancy(), 0.5);
+}
+
+#[test]
+fn non_ascii_names() {
+    let text = String::from_utf8(DATA.to_vec()).unwrap();
+    let long = text.replace("ZN    .", "ZINC1 .");
+    let structure = mmcif::Reader::new(long.as_bytes()).read_structure().unwrap();
+    assert_eq!(structure.models[0].hetatms[0].atom_name(), "ZINC");
+    let accented = text.replace("\"O5'\"", "O\u{e9}\u{e9}");
+    assert!(mmcif::Reader::new(accented.as_bytes()).read_structure().is_err());
+}
+
+#[test]
+fn write_and_read() {
+    let structure = mmcif::Reader::new(DATA).read_structure().unwrap();
+    let mut buffer = Vec::new();
+    mmcif::Writer::new(&mut buffer).write_structure(&structure).unwrap();
+
+    let read = mmcif::Reader::new(&buffer[..]).read_structure().unwrap();
+    assert_eq!(read.header.id_code, "1ABC");
+    assert_eq!(read.header.unit_cell, structure.header.unit_cell);
+    assert_eq!(read.models.len(), 2);
+    for (lhs, rhs) in read.models.iter().zip(structure.models.iter()) {
+        assert_eq!(lhs.positions(), rhs.positions());
+        assert_eq!(lhs.atoms[1].atom_name(), "O5'");
+        assert_eq!(lhs.hetatms[0].chain_id(), 'C');
+        assert_eq!(lhs.hetatms[0].charge(), "2+");
+    }
+}
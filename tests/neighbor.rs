@@ -0,0 +1,98 @@
+extern crate rustymill as mill;
+extern crate nalgebra  as na;
+use na::Vector3;
+use mill::neighbor::CellList;
+
+/// Deterministic pseudo-random points in [0, size).
+fn points(n: usize, size: f64) -> Vec<Vector3<f64>> {
+    let mut state: u64 = 12345;
+    let mut next = || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (state >> 11) as f64 / (1u64 << 53) as f64 * size
+    };
+    (0..n).map(|_| Vector3::new(next(), next(), next())).collect()
+}
+
+fn min_image(d: Vector3<f64>, size: f64) -> Vector3<f64> {
+    d.map(|x| x - (x / size).round() * size)
+}
+
+#[test]
+fn radius_query() {
+    let pos = points(500, 20.0);
+    let cells = CellList::new(&pos, 3.0);
+    assert_eq!(cells.len(), 500);
+    for center in pos.iter().take(20) {
+        for &radius in [1.0, 3.0, 7.5].iter() {
+            let expected: Vec<usize> = (0..pos.len())
+                .filter(|&i| (pos[i] - center).norm() <= radius).collect();
+            assert_eq!(cells.within(center, radius), expected);
+            assert_eq!(cells.any_within(center, radius), !expected.is_empty());
+        }
+    }
+    let outside = Vector3::new(100.0, 100.0, 100.0);
+    assert!(cells.within(&outside, 5.0).is_empty());
+}
+
+#[test]
+fn nearest_query() {
+    let pos = points(300, 15.0);
+    let cells = CellList::new(&pos, 2.0);
+    let query = Vector3::new(30.0, 7.0, 7.0);
+    let mut expected: Vec<(usize, f64)> = pos.iter().enumerate()
+        .map(|(i, p)| (i, (p - query).norm())).collect();
+    expected.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+    let found = cells.nearest(&query, 5);
+    assert_eq!(found.len(), 5);
+    for (f, e) in found.iter().zip(expected.iter()) {
+        assert_eq!(f.0, e.0);
+        assert!((f.1 - e.1).abs() < 1e-12);
+    }
+    assert_eq!(cells.nearest(&query, 1000).len(), 300);
+    assert!(cells.nearest(&query, 0).is_empty());
+}
+
+#[test]
+fn all_pairs() {
+    let pos = points(400, 12.0);
+    let cells = CellList::new(&pos, 2.5);
+    let mut expected = Vec::new();
+    for i in 0..pos.len() {
+        for j in i+1..pos.len() {
+            if (pos[i] - pos[j]).norm() <= 2.5 {
+                expected.push((i, j));
+            }
+        }
+    }
+    let found: Vec<(usize, usize)> = cells.pairs(2.5).iter().map(|&(i, j, _)| (i, j)).collect();
+    assert_eq!(found, expected);
+
+    let others = points(10, 12.0);
+    for &(i, j, d) in cells.pairs_with(&others, 2.0).iter() {
+        assert!((pos[i] - others[j]).norm() <= 2.0);
+        assert!((pos[i] - others[j]).norm() - d < 1e-12);
+    }
+}
+
+#[test]
+fn periodic_box() {
+    let size = 10.0;
+    let pos = points(300, size);
+    let cells = CellList::periodic(&pos, 2.0, Vector3::new(size, size, size));
+    for center in pos.iter().take(30) {
+        let expected: Vec<usize> = (0..pos.len())
+            .filter(|&i| min_image(pos[i] - center, size).norm() <= 2.5).collect();
+        assert_eq!(cells.within(center, 2.5), expected);
+    }
+
+    // across the boundary
+    let pair = vec![Vector3::new(0.2, 5.0, 5.0), Vector3::new(9.9, 5.0, 5.0)];
+    let cells = CellList::periodic(&pair, 1.0, Vector3::new(size, size, size));
+    let pairs = cells.pairs(1.0);
+    assert_eq!(pairs.len(), 1);
+    assert!((pairs[0].2 - 0.3).abs() < 1e-12);
+    let d = cells.displacement(&pair[0], &pair[1]);
+    assert!((d[0] + 0.3).abs() < 1e-12);
+    assert_eq!(cells.nearest(&Vector3::new(-0.5, 5.0, 5.0), 1)[0].0, 1);
+}
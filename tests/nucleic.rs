@@ -0,0 +1,146 @@
+extern crate rustymill as mill;
+use mill::AtomBuilder;
+use mill::Atom;
+use mill::nucleic::{self, Group, PairKind, HBOND_CUTOFF};
+use mill::pdb::{classify, AtomData, AtomDataMut, ResidueKind};
+use mill::pdb::residue::one_letter_code;
+use mill::select::Selection;
+
+/// Base atoms in the standard reference frame (Olson et al., 2001).
+fn base(name: &str) -> &'static [(&'static str, f64, f64)] {
+    match name {
+        "DA" => &[("C1'", -2.479, 5.346), ("N9", -1.291, 4.498), ("C8", 0.024, 4.897),
+                  ("N7", 0.877, 3.902), ("C5", 0.071, 2.771), ("C6", 0.369, 1.398),
+                  ("N6", 1.611, 0.909), ("N1", -0.668, 0.532), ("C2", -1.912, 1.023),
+                  ("N3", -2.320, 2.290), ("C4", -1.267, 3.124)],
+        "DT" => &[("C1'", -2.481, 5.354), ("N1", -1.284, 4.500), ("C2", -1.462, 3.135),
+                  ("O2", -2.562, 2.608), ("N3", -0.298, 2.407), ("C4", 0.994, 2.897),
+                  ("O4", 1.944, 2.119), ("C5", 1.106, 4.338), ("C7", 2.466, 4.961),
+                  ("C6", -0.024, 5.057)],
+        "DG" => &[("C1'", -2.477, 5.399), ("N9", -1.289, 4.551), ("C8", 0.023, 4.962),
+                  ("N7", 0.870, 3.969), ("C5", 0.071, 2.833), ("C6", 0.424, 1.460),
+                  ("O6", 1.554, 0.955), ("N1", -0.700, 0.641), ("C2", -1.999, 1.087),
+                  ("N2", -2.949, 0.139), ("N3", -2.342, 2.364), ("C4", -1.265, 3.177)],
+        "DC" => &[("C1'", -2.477, 5.402), ("N1", -1.285, 4.542), ("C2", -1.472, 3.158),
+                  ("O2", -2.628, 2.709), ("N3", -0.391, 2.344), ("C4", 0.837, 2.868),
+                  ("N4", 1.875, 2.027), ("C5", 1.056, 4.275), ("C6", -0.023, 5.068)],
+        _ => panic!("unknown base"),
+    }
+}
+
+fn complement(name: &str) -> &'static str {
+    match name {"DA" => "DT", "DT" => "DA", "DG" => "DC", "DC" => "DG", _ => panic!()}
+}
+
+/// An ideal B-DNA duplex: twist 36 degrees and rise 3.38 A. The second
+/// strand is flipped about the x axis of each base pair.
+fn duplex(sequence: &[&str]) -> Vec<Atom> {
+    let mut atoms = Vec::new();
+    let n = sequence.len();
+    for (strand, chain) in [(0, 'A'), (1, 'B')].iter() {
+        for k in 0..n {
+            let step = if *strand == 0 {k} else {n - 1 - k};
+            let name = if *strand == 0 {sequence[step]} else {complement(sequence[step])};
+            let (sin, cos) = (36.0f64 * step as f64).to_radians().sin_cos();
+            for &(atom, x, y) in base(name).iter() {
+                let (y, z) = if *strand == 0 {(y, 0.0)} else {(-y, -0.0)};
+                atoms.push(AtomBuilder::new()
+                    .atom_number(atoms.len() as i32 + 1)
+                    .atom_residue_chain(atom, name, *chain)
+                    .residue_number(k as i32 + 1)
+                    .pos(x * cos - y * sin, x * sin + y * cos, z + 3.38 * step as f64)
+                    .finalize());
+            }
+        }
+    }
+    atoms
+}
+
+#[test]
+fn classify_residues() {
+    assert_eq!(classify("ALA"), ResidueKind::AminoAcid);
+    assert_eq!(classify("DG"),  ResidueKind::Dna);
+    assert_eq!(classify("DA5"), ResidueKind::Dna);
+    assert_eq!(classify("U"),   ResidueKind::Rna);
+    assert_eq!(classify("HOH"), ResidueKind::Water);
+    assert_eq!(classify("ZN"),  ResidueKind::Ion);
+    assert_eq!(classify("ATP"), ResidueKind::Ligand);
+    assert!(ResidueKind::Rna.is_nucleotide() && ResidueKind::AminoAcid.is_polymer());
+}
+
+#[test]
+fn groups() {
+    assert_eq!(nucleic::group("P"),   Group::Phosphate);
+    assert_eq!(nucleic::group("O1P"), Group::Phosphate);
+    assert_eq!(nucleic::group("O5'"), Group::Phosphate);
+    assert_eq!(nucleic::group("C1*"), Group::Sugar);
+    assert_eq!(nucleic::group("O2'"), Group::Sugar);
+    assert_eq!(nucleic::group("N9"),  Group::Base);
+
+    let atoms = duplex(&["DA", "DG"]);
+    let bases = "dna and base".parse::<Selection>().unwrap().select(&atoms);
+    let backbone = "backbone".parse::<Selection>().unwrap().select(&atoms);
+    assert_eq!(bases.len() + backbone.len(), atoms.len());
+    assert_eq!(backbone.len(), 4);
+}
+
+#[test]
+fn watson_crick_pairs() {
+    let atoms = duplex(&["DA", "DG", "DC", "DT"]);
+    let pairs = nucleic::base_pairs(&atoms, HBOND_CUTOFF);
+    let found: Vec<String> = pairs.iter().map(|p| {
+        format!("{} {} {}", p.lhs, p.rhs, p.kind)
+    }).collect();
+    assert_eq!(found, vec!["A:DA1 B:DT4 watson-crick", "A:DG2 B:DC3 watson-crick",
+                           "B:DA1 A:DT4 watson-crick", "B:DG2 A:DC3 watson-crick"]);
+    assert_eq!(pairs[1].hbonds.len(), 3);
+    assert!(pairs.iter().all(|p| p.hbonds.iter().all(|d| 2.8 < *d && *d < 3.1)));
+}
+
+#[test]
+fn hoogsteen_pair() {
+    let adenine: Vec<Atom> = base("DA").iter().map(|&(name, x, y)| {
+        AtomBuilder::new().atom_residue_chain(name, "DA", 'A').pos(x, y, 0.0).finalize()
+    }).collect();
+    // N3 and O4 of thymine 2.9 A away from N7 and N6
+    let mut atoms = adenine.clone();
+    for &(name, x, y) in [("N3", 3.777, 3.902), ("C4", 4.5, 2.5), ("O4", 4.511, 0.909)].iter() {
+        atoms.push(AtomBuilder::new().atom_residue_chain(name, "DT", 'B')
+                       .pos(x, y, 0.0).finalize());
+    }
+    let pairs = nucleic::base_pairs(&atoms, HBOND_CUTOFF);
+    assert_eq!(pairs.len(), 1);
+    assert_eq!(pairs[0].kind, PairKind::Hoogsteen);
+}
+
+#[test]
+fn stacking() {
+    let atoms = duplex(&["DA", "DG", "DC"]);
+    let stacks = nucleic::stackings(&atoms);
+    let found: Vec<String> = stacks.iter().map(|s| format!("{} {}", s.lhs, s.rhs)).collect();
+    assert_eq!(found, vec!["A:DA1 A:DG2", "A:DG2 A:DC3", "B:DG1 B:DC2", "B:DC2 B:DT3"]);
+    assert!(stacks.iter().all(|s| s.angle < 1e-6 && s.distance < 4.5));
+}
+
+#[test]
+fn amber_rna_names() {
+    assert_eq!(one_letter_code("RA"),  Some('A'));
+    assert_eq!(one_letter_code("RU5"), Some('U'));
+    assert_eq!(one_letter_code("DT3"), Some('T'));
+    assert_eq!(one_letter_code("G3"),  Some('G'));
+    assert_eq!(one_letter_code("R3"),  None);
+
+    // RG5 RC3 in both strands
+    let mut atoms = duplex(&["DG", "DC"]);
+    for atom in atoms.iter_mut() {
+        let name = match (atom.residue_name(), atom.residue_number()) {
+            ("DG", 1) => "RG5", ("DC", 2) => "RC3", _ => panic!(),
+        };
+        atom.set_residue_name(name);
+    }
+    assert!(atoms.iter().all(|a| classify(a.residue_name()) == ResidueKind::Rna));
+    let pairs = nucleic::base_pairs(&atoms, HBOND_CUTOFF);
+    assert_eq!(pairs.len(), 2);
+    assert!(pairs.iter().all(|p| p.kind == PairKind::WatsonCrick));
+    assert_eq!(nucleic::stackings(&atoms).len(), 2);
+}
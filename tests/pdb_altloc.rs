@@ -0,0 +1,42 @@
+extern crate rustymill as mill;
+use mill::pdb::AtomData;
+use mill::pdb::AltlocPolicy;
+
+fn atoms() -> Vec<mill::pdb::Atom> {
+    vec![
+    "ATOM      1  N   SER A  10       1.000   1.000   1.000  1.00 35.88           N  ",
+    "ATOM      2  CA ASER A  10       2.000   1.000   1.000  0.40 36.67           C  ",
+    "ATOM      3  CA BSER A  10       2.100   1.100   1.000  0.60 36.67           C  ",
+    "ATOM      4  OG ASER A  10       3.000   1.000   1.000  0.40 36.67           O  ",
+    "ATOM      5  OG BSER A  10       3.100   1.100   1.000  0.60 36.67           O  ",
+    "ATOM      6  N  BGLY A  11       4.000   1.000   1.000  0.70 36.67           N  ",
+    "ATOM      7  N  AGLY A  11       4.100   1.100   1.000  0.30 36.67           N  ",
+    ].into_iter().map(|l| l.parse::<mill::pdb::Atom>().unwrap()).collect()
+}
+
+#[test]
+fn resolve_altlocs() {
+    let atoms = atoms();
+
+    let highest = mill::pdb::resolve_altlocs(&atoms, AltlocPolicy::HighestOccupancy);
+    assert_eq!(highest.len(), 4);
+    assert_eq!(highest.iter().map(|a| a.atom_number()).collect::<Vec<_>>(), vec![1, 3, 5, 6]);
+    assert!(highest.iter().all(|a| a.alternate_location() == ' '));
+
+    let first = mill::pdb::resolve_altlocs(&atoms, AltlocPolicy::First);
+    assert_eq!(first.iter().map(|a| a.atom_number()).collect::<Vec<_>>(), vec![1, 2, 4, 6]);
+
+    let keep = mill::pdb::resolve_altlocs(&atoms, AltlocPolicy::Keep('A'));
+    assert_eq!(keep.iter().map(|a| a.atom_number()).collect::<Vec<_>>(), vec![1, 2, 4, 7]);
+
+    let sets = mill::pdb::conformers(&atoms);
+    assert_eq!(sets.len(), 2);
+    assert_eq!(sets[0].0, 'A');
+    assert_eq!(sets[0].1.len(), 4);
+    assert_eq!(sets[1].0, 'B');
+    assert_eq!(sets[1].1.iter().map(|a| a.atom_number()).collect::<Vec<_>>(), vec![1, 3, 5, 6]);
+
+    assert_eq!("highest".parse::<AltlocPolicy>(), Ok(AltlocPolicy::HighestOccupancy));
+    assert_eq!("B".parse::<AltlocPolicy>(),       Ok(AltlocPolicy::Keep('B')));
+    assert!("unknown".parse::<AltlocPolicy>().is_err());
+}
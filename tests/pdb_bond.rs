@@ -0,0 +1,103 @@
+extern crate rustymill as mill;
+use mill::pdb::{Reader, Writer, BondGraph, infer_bonds};
+
+const PEPTIDE: &'static [u8] = b"\
+ATOM      1  N   ARG A  10       1.281 106.699  14.383  1.00 35.88           N
+ATOM      2  CA  ARG A  10       2.353 105.696  14.456  1.00 36.67           C
+ATOM      3  C   ARG A  10       3.559 106.257  15.222  1.00 37.37           C
+ATOM      4  O   ARG A  10       3.753 107.471  15.270  1.00 37.74           O
+ATOM      5  CB  ARG A  10       2.774 105.306  13.039  1.00 37.25           C
+ATOM      6  CG  ARG A  10       1.754 104.432  12.321  1.00 38.44           C
+ATOM      7  CD  ARG A  10       1.698 104.678  10.815  1.00 38.51           C
+ATOM      8  NE  ARG A  10       2.984 104.447  10.163  1.00 39.94           N
+ATOM      9  CZ  ARG A  10       3.202 104.534   8.850  1.00 40.03           C
+ATOM     10  NH1 ARG A  10       2.218 104.840   8.007  1.00 40.76           N
+ATOM     11  NH2 ARG A  10       4.421 104.308   8.373  1.00 40.45           N
+ATOM     12  N   GLY A  11       4.300 105.300  15.700  1.00 30.00           N
+ATOM     13  CA  GLY A  11       5.600 105.700  16.200  1.00 30.00           C
+ATOM     14  C   GLY A  11       6.500 104.600  16.700  1.00 30.00           C
+ATOM     15  N   GLY A  20      30.000 105.300  15.700  1.00 30.00           N
+ATOM     16  CA  GLY A  20      31.300 105.700  16.200  1.00 30.00           C
+HETATM   17  C   CO2 A 101      20.000  20.000  20.000  1.00 20.00           C
+HETATM   18  O1  CO2 A 101      21.160  20.000  20.000  1.00 20.00           O
+HETATM   19  O2  CO2 A 101      18.840  20.000  20.000  1.00 20.00           O
+";
+
+#[test]
+fn infer_template_bonds() {
+    let structure = Reader::new(PEPTIDE).read_structure().unwrap();
+    let bonds = infer_bonds(&structure.models[0]);
+
+    assert!(bonds.contains(1, 2));
+    assert!(bonds.contains(2, 3));
+    assert!(bonds.contains(3, 4));
+    assert!(bonds.contains(2, 5));
+    assert!(bonds.contains(9, 11));
+    assert!(!bonds.contains(1, 3));
+    assert_eq!(bonds.neighbors(9), vec![8, 10, 11]);
+
+    // peptide bond to the next residue, but not across a chain break
+    assert!(bonds.contains(3, 12));
+    assert!(bonds.contains(12, 13));
+    assert!(bonds.contains(13, 14));
+    assert!(!bonds.contains(14, 15));
+    assert!(bonds.contains(15, 16));
+
+    // HETATMs by distance
+    assert_eq!(bonds.neighbors(17), vec![18, 19]);
+    assert!(!bonds.contains(18, 19));
+
+    assert_eq!(bonds.len(), 10 + 1 + 2 + 1 + 2);
+}
+
+#[test]
+fn conect_round_trip() {
+    let mut structure = Reader::new(PEPTIDE).read_structure().unwrap();
+    assert!(structure.bonds.is_empty());
+    structure.bonds = infer_bonds(&structure.models[0]);
+
+    let mut buffer = Vec::new();
+    Writer::new(&mut buffer).write_structure(&structure).unwrap();
+    let text = String::from_utf8(buffer).unwrap();
+    assert!(text.contains(&format!("{:<80}\n", "CONECT    9    8   10   11")));
+    assert!(text.contains(&format!("{:<80}\n", "CONECT   17   18   19")));
+
+    let reread = Reader::new(text.as_bytes()).read_structure().unwrap();
+    assert_eq!(reread.bonds, structure.bonds);
+    assert_eq!(reread.bonds, infer_bonds(&reread.models[0]));
+}
+
+#[test]
+fn conect_many_partners() {
+    let data: &[u8] = b"\
+CONECT    1    2    3    4    5
+CONECT    1    6
+";
+    let structure = Reader::new(data).read_structure().unwrap();
+    let mut expected = BondGraph::new();
+    for i in 2..7 {
+        expected.add(i, 1);
+    }
+    assert_eq!(structure.bonds, expected);
+    let lines: Vec<String> = expected.to_string().lines().map(|l| l.to_owned()).collect();
+    assert_eq!(lines.len(), 7);
+    assert!(lines.iter().all(|l| l.len() == 80));
+    assert_eq!(lines[0].trim_end(), "CONECT    1    2    3    4    5");
+    assert_eq!(lines[1].trim_end(), "CONECT    1    6");
+    assert_eq!(lines[6].trim_end(), "CONECT    6    1");
+}
+
+#[test]
+fn graph_neighbors() {
+    let mut graph = BondGraph::new();
+    for &(l, r) in [(5, 3), (5, 8), (1, 5), (3, 8), (5, 5)].iter() {
+        graph.add(l, r);
+    }
+    assert_eq!(graph.len(), 4);
+    assert_eq!(graph.neighbors(5), vec![1, 3, 8]);
+    assert_eq!(graph.neighbors(8), vec![3, 5]);
+    assert!(graph.neighbors(2).is_empty());
+    graph.retain(|serial| serial != 3);
+    assert_eq!(graph.neighbors(5), vec![1, 8]);
+    assert_eq!(graph.neighbors(8), vec![5]);
+}
@@ -0,0 +1,54 @@
+extern crate rustymill as mill;
+use mill::pdb::Atom;
+use mill::pdb::AtomData;
+use mill::pdb::ChainData;
+use mill::pdb::ResidueData;
+use mill::pdb::ChainIterator;
+use mill::pdb::Ter;
+
+#[test]
+fn chain_iter() {
+    let data: &[u8] = b"\
+ATOM      1  N   ARG A  10       1.281 106.699  14.383  0.50 35.88           N  
+ATOM      2  CA  ARG A  10       2.353 105.696  14.456  0.50 36.67           C  
+ATOM      3  N   GLY A  11       3.559 106.257  15.222  0.50 37.37           N  
+TER       4      GLY A  11                                                      
+ATOM      5  N   GLY A  12       3.753 107.471  15.270  0.50 37.74           N  
+ATOM      6  N   ALA B   1       2.774 105.306  13.039  0.50 37.25           N  
+ATOM      7  CA  ALA B   1       1.754 104.432  12.321  0.50 38.44           C  
+END                                                                             ";
+    let structure = mill::pdb::Reader::new(data).read_structure().unwrap();
+    let model = structure.first_model().unwrap();
+
+    let chains: Vec<_> = model.chains().unwrap().collect();
+    assert_eq!(chains.len(), 3);
+    assert_eq!(chains[0].chain_id(),   'A');
+    assert_eq!(chains[0].atoms.len(),  3);
+    assert_eq!(chains[1].chain_id(),   'A');
+    assert_eq!(chains[1].atoms.len(),  1);
+    assert_eq!(chains[2].chain_id(),   'B');
+    assert_eq!(chains[2].atoms.len(),  2);
+
+    let residues: Vec<_> = chains[0].residues().collect();
+    assert_eq!(residues.len(), 2);
+    assert_eq!(residues[0].residue_name(), "ARG");
+    assert_eq!(residues[1].residue_name(), "GLY");
+
+    // without TER, chains are split only by chain ID
+    let chains: Vec<_> = ChainIterator::new(&model.atoms).unwrap().collect();
+    assert_eq!(chains.len(), 2);
+    assert_eq!(chains[0].atoms.len(), 4);
+
+    // TERs in any order
+    let ters = [Ter::new(&model.atoms[2]), Ter::new(&model.atoms[0])];
+    let sizes: Vec<usize> = ChainIterator::with_ters(&model.atoms, &ters).unwrap()
+        .map(|c| c.atoms.len()).collect();
+    assert_eq!(sizes, vec![1, 2, 1, 2]);
+
+    let chain = mill::pdb::Chain::from(model.atoms.clone()).unwrap();
+    assert_eq!(chain.chain_id(), 'A');
+    assert_eq!(chain.atoms.len(), 4);
+    assert_eq!(chain.residues().count(), 3);
+    assert!(ChainIterator::<Atom>::new(&[]).is_none());
+    assert_eq!(chains[1].to_chain().atoms[1].atom_name(), "CA");
+}
@@ -0,0 +1,68 @@
+extern crate rustymill as mill;
+
+#[test]
+fn read_header() {
+    let data: &[u8] = b"\
+HEADER    HYDROLASE                               19-MAY-98   1BGL              
+TITLE     CRYSTAL STRUCTURE OF A VERY LONG NAMED PROTEIN IN COMPLEX WITH A      
+TITLE    2 SMALL MOLECULE                                                       
+COMPND    MOL_ID: 1;                                                            
+COMPND   2 MOLECULE: LYSOZYME;                                                  
+SOURCE    MOL_ID: 1;                                                            
+EXPDTA    X-RAY DIFFRACTION                                                     
+REMARK   2                                                                      
+REMARK   2 RESOLUTION.    1.74 ANGSTROMS.                                       
+REMARK   3   R VALUE            (WORKING SET) : 0.192                           
+REMARK   3   FREE R VALUE                     : 0.231                           
+REMARK 465   M RES C SSSEQI                                                     
+REMARK 465     MET A     1                                                      
+REMARK 465     GLY A    52A                                                     
+ATOM      1  N   ARG A  10       1.281 106.699  14.383  0.50 35.88           N  
+TER       2      ARG A  10                                                      
+END                                                                             ";
+    let mut reader = mill::pdb::Reader::new(data);
+    let structure = reader.read_structure().unwrap();
+    let header = &structure.header;
+
+    assert_eq!(header.classification,      "HYDROLASE");
+    assert_eq!(header.deposition_date,     "19-MAY-98");
+    assert_eq!(header.id_code,             "1BGL");
+    assert_eq!(header.title, "CRYSTAL STRUCTURE OF A VERY LONG NAMED PROTEIN IN COMPLEX WITH A SMALL MOLECULE");
+    assert_eq!(header.compound,            "MOL_ID: 1; MOLECULE: LYSOZYME;");
+    assert_eq!(header.source,              "MOL_ID: 1;");
+    assert_eq!(header.experimental_method, "X-RAY DIFFRACTION");
+    assert_eq!(header.resolution,          Some(1.74));
+    assert_eq!(header.r_work,              Some(0.192));
+    assert_eq!(header.r_free,              Some(0.231));
+    assert_eq!(header.missing_residues.len(), 2);
+    assert_eq!(header.missing_residues[0].residue_name,   "MET");
+    assert_eq!(header.missing_residues[0].chain_id,       'A');
+    assert_eq!(header.missing_residues[0].residue_number, 1);
+    assert_eq!(header.missing_residues[1].residue_number, 52);
+    assert_eq!(header.missing_residues[1].insertion_code, 'A');
+
+    assert_eq!(structure.models.len(), 1);
+    assert_eq!(structure.models[0].atoms.len(), 1);
+    assert_eq!(structure.models[0].ters.len(),  1);
+}
+
+#[test]
+fn write_header() {
+    let data: &[u8] = b"\
+HEADER    HYDROLASE                               19-MAY-98   1BGL              
+TITLE     CRYSTAL STRUCTURE OF A VERY LONG NAMED PROTEIN IN COMPLEX WITH A      
+TITLE    2 SMALL MOLECULE                                                       
+COMPND    MOL_ID: 1;                                                            
+EXPDTA    X-RAY DIFFRACTION                                                     
+REMARK   2 RESOLUTION.    1.74 ANGSTROMS.                                       
+ATOM      1  N   ARG A  10       1.281 106.699  14.383  0.50 35.88           N  
+TER       1      ARG A  10                                                      
+END                                                                             
+";
+    let structure = mill::pdb::Reader::new(data).read_structure().unwrap();
+
+    let mut buffer = Vec::<u8>::new();
+    mill::pdb::Writer::new(&mut buffer).write_structure(&structure).unwrap();
+    assert_eq!(String::from_utf8(buffer).unwrap(),
+               String::from_utf8(data.to_vec()).unwrap());
+}
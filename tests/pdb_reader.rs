@@ -34,3 +34,17 @@ ATOM     11  NH2 ARG A  10       4.421 104.308   8.373  0.50 40.45           N
     assert_eq!(chain[10].to_string(), "ATOM     11  NH2 ARG A  10       4.421 104.308   8.373  0.50 40.45           N  ");
 }
 
+
+#[test]
+fn pdb_reader_invalid_atom() {
+    let data: &[u8] = b"\
+ATOM      1  N   ARG A  10       1.281 106.699  14.383  0.50 35.88           N  
+ATOM      2  CA  ARG A  10       2.353 105.696
+ATOM      3  C   ARG A  10       3.559 106.257  15.222  0.50 37.37           C  ";
+    let error = mill::pdb::Reader::new(data).read_structure().unwrap_err();
+    assert!(error.starts_with("line 2: "), "{}", error);
+
+    let data: &[u8] = b"\
+HETATM    1  O   HOH A 101       x.xxx   1.000   2.000  1.00 20.00           O  ";
+    assert!(mill::pdb::Reader::new(data).read_structure().is_err());
+}
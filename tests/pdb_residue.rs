@@ -6,6 +6,7 @@ use mill::pdb::AtomData;
 use mill::pdb::ResidueData;
 use mill::pdb::ResidueIterator;
 use mill::pdb::Residue;
+use mill::pdb::ResidueId;
 
 #[test]
 fn residue() {
@@ -101,3 +102,39 @@ fn residue_iter() {
 
     }
 }
+
+#[test]
+fn residue_iter_same_name() {
+    let mut atoms = Vec::<Atom>::new();
+    atoms.push("ATOM     31  N   ALA A   2      11.281  86.699  94.383  0.50 35.88           N  ".parse::<mill::pdb::Atom>().unwrap());
+    atoms.push("ATOM     32  CA  ALA A   2      12.353  85.696  94.456  0.50 36.67           C  ".parse::<mill::pdb::Atom>().unwrap());
+    atoms.push("ATOM     33  N   ALA A   3      13.559  86.257  95.222  0.50 37.37           N  ".parse::<mill::pdb::Atom>().unwrap());
+    atoms.push("ATOM     34  CA  ALA A   3      13.753  87.471  95.270  0.50 37.74           C  ".parse::<mill::pdb::Atom>().unwrap());
+    atoms.push("ATOM     35  N   ALA A   3A     11.281  86.699  94.383  0.50 35.88           N  ".parse::<mill::pdb::Atom>().unwrap());
+    atoms.push("ATOM     36  O   HOH A 101      12.353  85.696  94.456  0.50 36.67           O  ".parse::<mill::pdb::Atom>().unwrap());
+    atoms.push("ATOM     37  O   HOH A 102      13.559  86.257  95.222  0.50 37.37           O  ".parse::<mill::pdb::Atom>().unwrap());
+
+    let residues: Vec<_> = ResidueIterator::new(&atoms).unwrap().collect();
+    assert_eq!(residues.len(), 5);
+    assert_eq!(residues[0].atoms.len(), 2);
+    assert_eq!(residues[1].atoms.len(), 2);
+    assert_eq!(residues[2].atoms.len(), 1);
+    assert_eq!(residues[2].insertion_code(), 'A');
+    assert_eq!(residues[3].residue_number(), 101);
+    assert_eq!(residues[4].residue_number(), 102);
+    assert_eq!(residues[2].residue_id().to_string(), "A:ALA3A");
+
+    let mut map = std::collections::HashMap::new();
+    for res in residues.iter() {
+        map.insert(res.residue_id(), res.atoms.len());
+    }
+    assert_eq!(map[&ResidueId::new('A', 3, ' ', "ALA")], 2);
+    assert_eq!(ResidueId::new('A', 1, ' ', "TIP3").residue_name(), "TIP");
+    assert_eq!(ResidueId::new('A', 1, ' ', "Aé").residue_name(), "Aé");
+    assert_eq!(ResidueId::new('A', 1, ' ', "ABé").residue_name(), "AB");
+
+    let mut res = Residue::<Atom>::new();
+    res.extend(atoms.iter().cloned());
+    assert_eq!(res.atoms.len(), 2);
+    assert_eq!(res.residue_number(), 2);
+}
@@ -0,0 +1,50 @@
+extern crate rustymill as mill;
+use mill::pdb::{AtomData, Reader};
+
+const DATA: &'static [u8] = b"\
+ATOM     10  N   ALA A   5       0.000   0.000   0.000  1.00  0.00           N  
+ATOM     11  CA  ALA A   5       1.460   0.000   0.000  1.00  0.00           C  
+ATOM     12  N   GLY A   5A      3.000   0.000   0.000  1.00  0.00           N  
+ATOM     13  CA  GLY A   5A      4.460   0.000   0.000  1.00  0.00           C  
+TER      14      GLY A   5A                                                      
+ATOM     20  N   SER B   9       9.000   0.000   0.000  1.00  0.00           N  
+ATOM     21  CA  SER B   9      10.460   0.000   0.000  1.00  0.00           C  
+TER      22      SER B   9                                                       
+HETATM   30  O   HOH A 101      20.000   0.000   0.000  1.00  0.00           O  
+CONECT   10   11
+CONECT   30   21
+END
+";
+
+#[test]
+fn renumber_atoms() {
+    let mut structure = Reader::new(DATA).read_structure().unwrap();
+    structure.renumber();
+    let model = &structure.models[0];
+    let serials: Vec<i32> = model.atoms.iter().map(|a| a.atom_number()).collect();
+    assert_eq!(serials, vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!(model.hetatms[0].atom_number(), 7);
+    assert_eq!(model.ters.len(), 2);
+    assert_eq!(model.ters[0].atom_number(), 4);
+    assert_eq!(model.ters[1].atom_number(), 6);
+    assert_eq!(model.chains().unwrap().count(), 2);
+
+    let bonds: Vec<(i32, i32)> = structure.bonds.iter().cloned().collect();
+    assert_eq!(bonds, vec![(1, 2), (6, 7)]);
+}
+
+#[test]
+fn renumber_residues() {
+    let mut structure = Reader::new(DATA).read_structure().unwrap();
+    structure.models[0].renumber_residues(1);
+    let model = &structure.models[0];
+    let residues: Vec<(char, i32, char)> = model.atoms.iter().map(|a| {
+        (a.chain_id(), a.residue_number(), a.insertion_code())
+    }).collect();
+    assert_eq!(residues, vec![('A', 1, ' '), ('A', 1, ' '), ('A', 2, ' '), ('A', 2, ' '),
+                              ('B', 1, ' '), ('B', 1, ' ')]);
+    assert_eq!(model.ters[0].residue_number(), 2);
+    assert_eq!(model.ters[1].residue_number(), 1);
+    assert_eq!(model.chains().unwrap().count(), 2);
+    assert_eq!(model.hetatms[0].residue_number(), 101);
+}
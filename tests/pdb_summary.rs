@@ -0,0 +1,67 @@
+extern crate rustymill as mill;
+use mill::pdb::{Reader, Summary};
+use mill::pdb::residue::one_letter_code;
+
+const DATA: &'static [u8] = b"\
+HEADER    TRANSFERASE                             01-JAN-00   1ABC              
+CRYST1   50.000   60.000   70.000  90.00  90.00  90.00 P 21 21 21    4          
+ATOM      1  N   GLY A   1       0.000   0.000   0.000  1.00  0.00           N  
+ATOM      2  CA  GLY A   1       1.460   0.000   0.000  1.00  0.00           C  
+ATOM      3  C   GLY A   1       2.500   0.000   0.000  1.00  0.00           C  
+ATOM      4  N   ALA A   2       3.830   0.000   0.000  1.00  0.00           N  
+ATOM      5  CA AALA A   2       5.290   0.000   0.000  0.60  0.00           C  
+ATOM      6  CA BALA A   2       5.290   0.100   0.000  0.40  0.00           C  
+ATOM      7  C   ALA A   2       6.330   0.000   0.000  1.00  0.00           C  
+ATOM      8  N   LYS A   5      20.000   0.000   0.000  1.00  0.00           N  
+ATOM      9  CA  LYS A   5      21.460   0.000   0.000  1.00  0.00           C  
+ATOM     10  C   LYS A   5      22.500   0.000   0.000  1.00  0.00           C  
+TER      11      LYS A   5                                                      
+ATOM     12  P    DG B   1      30.000   0.000   0.000  1.00  0.00           P  
+ATOM     13  P    DC B   2      36.000   0.000   0.000  1.00  0.00           P  
+HETATM   14 ZN    ZN A 101      40.000   0.000   0.000  1.00  0.00          ZN  
+HETATM   15  O   HOH A 201      45.000   0.000   0.000  1.00  0.00           O  
+HETATM   16  O   HOH A 202      48.000   0.000   0.000  1.00  0.00           O  
+HETATM   17  C1  LIG A 301      50.000   0.000   0.000  1.00  0.00           C  
+HETATM   18  C2  LIG A 301      51.500   0.000   0.000  1.00  0.00           C  
+END                                                                             
+";
+
+#[test]
+fn summarize() {
+    let structure = Reader::new(DATA).read_structure().unwrap();
+    let summary = Summary::of(&structure);
+    assert_eq!(summary.models,  1);
+    assert_eq!(summary.atoms,   12);
+    assert_eq!(summary.hetatms, 5);
+    assert_eq!(summary.waters,  2);
+    assert_eq!(summary.ions,    1);
+    assert_eq!(summary.ligands.len(), 1);
+    assert_eq!(summary.ligands[0].to_string(), "A:LIG301");
+    assert_eq!(summary.altlocs, vec!['A', 'B']);
+
+    assert_eq!(summary.chains.len(), 2);
+    let a = &summary.chains[0];
+    assert_eq!(a.chain_id, 'A');
+    assert_eq!(a.residues, 3);
+    assert_eq!(a.atoms,    10);
+    assert_eq!(a.sequence, "GAK");
+    assert_eq!(a.breaks.len(), 1);
+    assert_eq!(a.breaks[0].0.to_string(), "A:ALA2");
+    assert_eq!(a.breaks[0].1.to_string(), "A:LYS5");
+
+    let b = &summary.chains[1];
+    assert_eq!(b.sequence, "GC");
+    assert!(b.breaks.is_empty());
+
+    assert_eq!(structure.header.id_code, "1ABC");
+    assert_eq!(structure.header.unit_cell.as_ref().unwrap().a, 50.0);
+}
+
+#[test]
+fn one_letter() {
+    assert_eq!(one_letter_code("TRP"), Some('W'));
+    assert_eq!(one_letter_code("HIE"), Some('H'));
+    assert_eq!(one_letter_code("MSE"), Some('M'));
+    assert_eq!(one_letter_code("DT"),  Some('T'));
+    assert_eq!(one_letter_code("HOH"), None);
+}
@@ -0,0 +1,70 @@
+extern crate rustymill as mill;
+use mill::pdb::AtomData;
+
+#[test]
+fn biological_assembly() {
+    let data: &[u8] = b"\
+REMARK 350 BIOMOLECULE: 1                                                       
+REMARK 350 APPLY THE FOLLOWING TO CHAINS: A                                     
+REMARK 350   BIOMT1   1  1.000000  0.000000  0.000000        0.00000            
+REMARK 350   BIOMT2   1  0.000000  1.000000  0.000000        0.00000            
+REMARK 350   BIOMT3   1  0.000000  0.000000  1.000000        0.00000            
+REMARK 350   BIOMT1   2 -1.000000  0.000000  0.000000        0.00000            
+REMARK 350   BIOMT2   2  0.000000 -1.000000  0.000000        0.00000            
+REMARK 350   BIOMT3   2  0.000000  0.000000  1.000000       10.00000            
+ATOM      1  N   ARG A  10       1.000   2.000   3.000  1.00 35.88           N  
+ATOM      2  CA  ARG A  10       2.000   3.000   4.000  1.00 36.67           C  
+TER       3      ARG A  10                                                      
+ATOM      4  CA  GLY B  11       5.000   5.000   5.000  1.00 36.67           C  
+END                                                                             ";
+    let structure = mill::pdb::Reader::new(data).read_structure().unwrap();
+    assert_eq!(structure.header.biomolecules.len(), 1);
+    assert_eq!(structure.header.biomolecules[0].groups[0].chains, vec!['A']);
+    assert_eq!(structure.header.biomolecules[0].groups[0].operators.len(), 2);
+
+    let assembly = mill::pdb::biological_assembly(&structure, 1).unwrap();
+    let model = &assembly.models[0];
+    assert_eq!(model.atoms.len(), 4);
+    assert_eq!(model.ters.len(),  2);
+    assert_eq!(model.atoms[0].chain_id(), 'A');
+    assert_eq!(model.atoms[2].chain_id(), 'C');
+    assert_eq!(model.atoms[2].atom_number(), 3);
+    assert_eq!(model.atoms[2].x, -1.0);
+    assert_eq!(model.atoms[2].y, -2.0);
+    assert_eq!(model.atoms[2].z, 13.0);
+
+    assert!(mill::pdb::biological_assembly(&structure, 2).is_err());
+}
+
+#[test]
+fn symmetry_mates() {
+    let data: &[u8] = b"\
+CRYST1   10.000   10.000   10.000  90.00  90.00  90.00 P 1           1          
+ATOM      1  CA  GLY A   1       1.000   5.000   5.000  1.00 36.67           C  
+END                                                                             ";
+    let structure = mill::pdb::Reader::new(data).read_structure().unwrap();
+    let cell = structure.header.unit_cell.clone().unwrap();
+    assert_eq!(cell.a, 10.0);
+    assert_eq!(cell.space_group, "P 1");
+
+    // the copies in the six cells sharing a face are exactly 10 angstroms away
+    // and the others farther.
+    let mates = mill::pdb::symmetry_mates(&structure, 10.0).unwrap();
+    assert_eq!(mates.models[0].atoms.len(), 7);
+    let mates = mill::pdb::symmetry_mates(&structure, 5.0).unwrap();
+    assert_eq!(mates.models[0].atoms.len(), 1);
+}
+
+#[test]
+fn unit_cell_without_space_group() {
+    let line = "CRYST1   10.000   20.000   30.000  90.00  90.00 120.00";
+    assert_eq!(line.len(), 54);
+    let cell = mill::pdb::UnitCell::from_line(line).unwrap();
+    assert_eq!(cell.c, 30.0);
+    assert_eq!(cell.gamma, 120.0);
+    assert_eq!(cell.space_group, "");
+    assert_eq!(cell.z, 1);
+    let cell = mill::pdb::UnitCell::from_line(&format!("{} P 1", line)).unwrap();
+    assert_eq!(cell.space_group, "P 1");
+    assert!(mill::pdb::UnitCell::from_line("CRYST1   10.000").is_err());
+}
@@ -0,0 +1,47 @@
+extern crate rustymill as mill;
+use mill::pdb::AtomData;
+use mill::select::Selection;
+
+fn atoms() -> Vec<mill::pdb::Atom> {
+    vec![
+    "ATOM      1  N   ARG A  10       0.000   0.000   0.000  1.00 35.88           N  ",
+    "ATOM      2  CA  ARG A  10       1.000   0.000   0.000  1.00 36.67           C  ",
+    "ATOM      3  HA  ARG A  10       1.000   1.000   0.000  1.00 36.67           H  ",
+    "ATOM      4  CA  GLY A  11       4.000   0.000   0.000  1.00 37.37           C  ",
+    "ATOM      5  CA  GLY A  60      10.000   0.000   0.000  1.00 37.37           C  ",
+    "ATOM      6  CA  ALA B  20       0.000  10.000   0.000  1.00 37.74           C  ",
+    "ATOM      7  C1  LIG C   1       9.000   1.000   0.000  1.00 37.74           C  ",
+    ].into_iter().map(|l| l.parse::<mill::pdb::Atom>().unwrap()).collect()
+}
+
+fn serials(sel: &str) -> Vec<i32> {
+    let atoms = atoms();
+    sel.parse::<Selection>().unwrap().select(&atoms)
+       .iter().map(|a| a.atom_number()).collect()
+}
+
+#[test]
+fn selection() {
+    assert_eq!(serials("all"),                              vec![1, 2, 3, 4, 5, 6, 7]);
+    assert_eq!(serials("name CA"),                          vec![2, 4, 5, 6]);
+    assert_eq!(serials("chain A and resid 10-50 and name CA"), vec![2, 4]);
+    assert_eq!(serials("not hydrogen and chain A"),         vec![1, 2, 4, 5]);
+    assert_eq!(serials("resname GLY or chain B"),           vec![4, 5, 6]);
+    assert_eq!(serials("not (resname GLY or chain B)"),     vec![1, 2, 3, 7]);
+    assert_eq!(serials("within 2.0 of resname LIG"),        vec![5, 7]);
+    assert_eq!(serials("within 2.0 of resname LIG and not resname LIG"), vec![5]);
+    assert_eq!(serials("resid 10 20"),                      vec![1, 2, 3, 6]);
+    assert_eq!(serials("backbone"),                         vec![1, 2, 4, 5, 6]);
+    assert_eq!(serials("serial 3-4 or element N"),          vec![1, 3, 4]);
+
+    assert!("name".parse::<Selection>().is_err());
+    assert!("chain AB".parse::<Selection>().is_err());
+    assert!("(name CA".parse::<Selection>().is_err());
+    assert!("name CA)".parse::<Selection>().is_err());
+    assert!("within of name CA".parse::<Selection>().is_err());
+    assert!("foo".parse::<Selection>().is_err());
+    assert!("resid é".parse::<Selection>().is_err());
+    assert!("resid 1-é".parse::<Selection>().is_err());
+    assert!("chain é".parse::<Selection>().is_err());
+    assert!("resid -5--1".parse::<Selection>().is_ok());
+}
@@ -0,0 +1,43 @@
+extern crate rustymill as mill;
+extern crate nalgebra  as na;
+use mill::pdb::AtomData;
+
+fn helix(n: usize, chain: char) -> Vec<mill::pdb::Atom> {
+    (0..n).map(|i| {
+        let t = i as f64 * 100.0_f64.to_radians();
+        mill::pdb::AtomBuilder::new()
+            .atom_number(i as i32 + 1).residue_number(i as i32 + 1)
+            .atom_residue_chain("CA", "ALA", chain)
+            .pos(2.3 * t.cos(), 2.3 * t.sin(), 1.5 * i as f64)
+            .finalize()
+    }).collect()
+}
+
+#[test]
+fn tm_score_and_gdt() {
+    assert_eq!(mill::similarity::tm_d0(10), 0.5);
+    assert!((mill::similarity::tm_d0(100) - 3.652).abs() < 1e-3);
+
+    let reference = helix(40, 'A');
+    let mut model = helix(40, 'B');
+    for atom in model.iter_mut() {
+        let (x, y) = (atom.x, atom.y);
+        atom.x = -y + 3.0;
+        atom.y =  x;
+    }
+    let score = mill::similarity::compare(&model, &reference).unwrap();
+    assert_eq!(score.aligned, 40);
+    assert!(score.rmsd < 1e-6);
+    assert!((score.tm_score - 1.0).abs() < 1e-6);
+    assert!((score.gdt_ts - 1.0).abs() < 1e-6);
+    assert!((score.gdt_ha - 1.0).abs() < 1e-6);
+
+    // the latter half is displaced
+    for atom in model.iter_mut().skip(20) {
+        atom.x += 20.0;
+    }
+    let score = mill::similarity::compare(&model, &reference).unwrap();
+    assert!(score.tm_score < 0.7 && score.tm_score > 0.4);
+    assert!((score.gdt_ts - 0.5).abs() < 0.1);
+    assert_eq!(model[0].atom_name(), "CA");
+}
@@ -0,0 +1,42 @@
+extern crate rustymill as mill;
+extern crate nalgebra  as na;
+#[macro_use]
+extern crate approx;
+use mill::Particle;
+
+fn atoms() -> Vec<mill::pdb::Atom> {
+    vec![
+    "ATOM      1  N   ARG A  10       1.281 106.699  14.383  0.50 35.88           N  ",
+    "ATOM      2  CA  ARG A  10       2.353 105.696  14.456  0.50 36.67           C  ",
+    "ATOM      3  C   ARG A  10       3.559 106.257  15.222  0.50 37.37           C  ",
+    "ATOM      4  O   ARG A  10       3.753 107.471  15.270  0.50 37.74           O  ",
+    "ATOM      5  CB  ARG A  10       2.774 105.306  13.039  0.50 37.25           C  ",
+    "ATOM      6  CG  ARG A  10       1.754 104.432  12.321  0.50 38.44           C  ",
+    ].into_iter().map(|l| l.parse::<mill::pdb::Atom>().unwrap()).collect()
+}
+
+#[test]
+fn superpose() {
+    let target = atoms();
+    let rot = na::Rotation3::new(na::Vector3::new(0.3, -1.2, 0.7));
+    let mut mobile = target.clone();
+    for atom in mobile.iter_mut() {
+        let v = rot * atom.vec() + na::Vector3::new(10.0, -5.0, 3.0);
+        atom.x = v[0]; atom.y = v[1]; atom.z = v[2];
+    }
+    assert!(mill::superpose::rmsd(&mobile, &target).unwrap() > 1.0);
+
+    let sp = mill::superpose::superpose(&mobile, &target).unwrap();
+    assert!(sp.rmsd < 1e-6);
+    let inv = rot.inverse();
+    assert_relative_eq!(sp.rotation, *inv.matrix(), epsilon = 1e-6);
+
+    let weights = vec![14.0, 12.0, 12.0, 16.0, 12.0, 12.0];
+    let sp = mill::superpose::superpose_weighted(&mobile, &target, &weights).unwrap();
+    assert!(sp.rmsd < 1e-6);
+
+    mill::superpose::transform(&mut mobile, &sp);
+    assert!(mill::superpose::rmsd(&mobile, &target).unwrap() < 1e-6);
+
+    assert!(mill::superpose::superpose(&mobile[1..], &target).is_err());
+}
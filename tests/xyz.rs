@@ -0,0 +1,40 @@
+extern crate rustymill as mill;
+use mill::xyz;
+use mill::pdb::AtomData;
+
+const DATA: &'static [u8] = b"\
+3
+water
+O   0.000000   0.000000   0.117300
+H   0.000000   0.757200  -0.469200
+1   0.000000  -0.757200  -0.469200
+3
+water, stretched
+O   0.000000   0.000000   0.117300
+H   0.000000   0.857200  -0.469200
+H   0.000000  -0.857200  -0.469200
+";
+
+#[test]
+fn read_frames() {
+    let structure = xyz::Reader::new(DATA).read_structure().unwrap();
+    assert_eq!(structure.models.len(), 2);
+    assert_eq!(structure.header.title, "water");
+    let atoms = &structure.models[0].atoms;
+    assert_eq!(atoms[0].atom_name(), "O");
+    assert_eq!(atoms[2].element_symbol(), "H");
+    assert_eq!(atoms[2].atom_number(), 3);
+    assert_eq!(structure.models[1].atoms[1].y, 0.8572);
+}
+
+#[test]
+fn write_and_read() {
+    let structure = xyz::Reader::new(DATA).read_structure().unwrap();
+    let mut buffer = Vec::new();
+    xyz::Writer::new(&mut buffer).write_structure(&structure).unwrap();
+    let text = String::from_utf8(buffer.clone()).unwrap();
+    assert_eq!(text.lines().nth(4).unwrap(), "H      0.000000    -0.757200    -0.469200");
+
+    let read = xyz::Reader::new(&buffer[..]).read_structure().unwrap();
+    assert_eq!(read.models[1].positions(), structure.models[1].positions());
+}